@@ -0,0 +1,193 @@
+//! OSS访问日志解析
+//!
+//! 开启日志转存后，OSS按小时将访问日志写入目标存储空间，每行一条记录，字段以空格分隔，
+//! 包含空格的字段使用双引号或方括号包裹，缺失的字段记为 - 。
+//!
+//! ```
+//! use aliyun_oss_rs::access_log::{self, AccessLogEntry};
+//!
+//! let content = bucket.object("log/for-rs-test2024-01-01-00-00-00-0001").get_object().download().await?;
+//! for entry in access_log::parse(std::str::from_utf8(&content)?) {
+//!     let entry: AccessLogEntry = entry?;
+//!     if entry.status >= 500 {
+//!         println!("{} {} {}", entry.request_id, entry.operation, entry.status);
+//!     }
+//! }
+//! ```
+//!
+//! 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/oss/user-guide/logging)
+
+use crate::Error;
+use chrono::{DateTime, FixedOffset};
+use percent_encoding::percent_decode_str;
+use std::str::FromStr;
+
+/// 一条访问日志
+///
+/// 较早的日志不包含末尾的部分字段，缺失或记为 - 的字段为None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessLogEntry {
+    /// 请求者的IP地址
+    pub remote_ip: String,
+    /// OSS收到请求的时间
+    pub time: DateTime<FixedOffset>,
+    /// 请求方法，如 GET
+    pub method: String,
+    /// 请求地址，包含查询参数，如 /aliyun-logo.png?x-oss-process=image/resize,w_100
+    pub request_uri: String,
+    /// 返回的HTTP状态码
+    pub status: u16,
+    /// 下行流量，单位字节
+    pub sent_bytes: u64,
+    /// 请求耗时，单位毫秒
+    pub request_time: u64,
+    /// 请求的Referer
+    pub referer: Option<String>,
+    /// 请求的User-Agent
+    pub user_agent: Option<String>,
+    /// 请求的目标域名
+    pub host_name: String,
+    /// 请求ID
+    pub request_id: String,
+    /// 是否为日志转存产生的请求
+    pub logging_flag: bool,
+    /// 请求者的阿里云账号ID，匿名访问为None
+    pub requester_id: Option<String>,
+    /// 请求类型，如 GetObject
+    pub operation: String,
+    /// 存储空间名称
+    pub bucket: Option<String>,
+    /// 文件路径，已解码
+    pub key: Option<String>,
+    /// 文件大小，单位字节
+    pub object_size: Option<u64>,
+    /// OSS处理请求的耗时，单位毫秒
+    pub server_cost_time: Option<u64>,
+    /// 返回的错误码，如 NoSuchKey
+    pub error_code: Option<String>,
+    /// 请求的长度，单位字节
+    pub request_length: Option<u64>,
+    /// 存储空间拥有者的阿里云账号ID
+    pub user_id: Option<String>,
+    /// 文件大小的变化量，单位字节
+    pub delta_data_size: Option<i64>,
+    /// 是否为CDN回源请求，回源请求为 cdn
+    pub sync_request: Option<String>,
+    /// 文件的存储类型
+    pub storage_class: Option<String>,
+    /// 通过生命周期规则或CopyObject转换后的存储类型
+    pub target_storage_class: Option<String>,
+    /// 传输加速的接入点所在的地域
+    pub transmission_acceleration_access_point: Option<String>,
+    /// 请求使用的AccessKey ID
+    pub access_key_id: Option<String>,
+}
+
+impl AccessLogEntry {
+    /// 解析一行访问日志
+    pub fn parse(line: &str) -> Result<Self, Error> {
+        let fields = split_fields(line)?;
+        if fields.len() < 16 {
+            return Err(Error::InvalidAccessLog(format!("字段数量不足：{}", line)));
+        }
+        //按位置取字段，缺失的字段视为 -
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .copied()
+                .filter(|v| *v != "-" && !v.is_empty())
+        };
+        let required = |index: usize| {
+            field(index).map(|v| v.to_owned()).ok_or_else(|| {
+                Error::InvalidAccessLog(format!("缺少第{}个字段：{}", index + 1, line))
+            })
+        };
+        let number = |index: usize| -> Result<Option<u64>, Error> {
+            field(index)
+                .map(|v| v.parse().map_err(|_| invalid_field(index, v)))
+                .transpose()
+        };
+        let time = DateTime::parse_from_str(&required(3)?, "%d/%b/%Y:%H:%M:%S %z")
+            .map_err(|_| invalid_field(3, fields[3]))?;
+        //请求行，如 GET /aliyun-logo.png HTTP/1.1
+        let mut request = fields[4].split(' ');
+        let method = request.next().unwrap_or_default().to_owned();
+        let request_uri = request.next().unwrap_or_default().to_owned();
+        Ok(AccessLogEntry {
+            remote_ip: required(0)?,
+            time,
+            method,
+            request_uri,
+            status: required(5)?
+                .parse()
+                .map_err(|_| invalid_field(5, fields[5]))?,
+            sent_bytes: number(6)?.unwrap_or_default(),
+            request_time: number(7)?.unwrap_or_default(),
+            referer: field(8).map(|v| v.to_owned()),
+            user_agent: field(9).map(|v| v.to_owned()),
+            host_name: field(10).unwrap_or_default().to_owned(),
+            request_id: field(11).unwrap_or_default().to_owned(),
+            logging_flag: field(12) == Some("true"),
+            requester_id: field(13).map(|v| v.to_owned()),
+            operation: field(14).unwrap_or_default().to_owned(),
+            bucket: field(15).map(|v| v.to_owned()),
+            key: field(16).map(|v| percent_decode_str(v).decode_utf8_lossy().into_owned()),
+            object_size: number(17)?,
+            server_cost_time: number(18)?,
+            error_code: field(19).map(|v| v.to_owned()),
+            request_length: number(20)?,
+            user_id: field(21).map(|v| v.to_owned()),
+            delta_data_size: field(22)
+                .map(|v| v.parse().map_err(|_| invalid_field(22, v)))
+                .transpose()?,
+            sync_request: field(23).map(|v| v.to_owned()),
+            storage_class: field(24).map(|v| v.to_owned()),
+            target_storage_class: field(25).map(|v| v.to_owned()),
+            transmission_acceleration_access_point: field(26).map(|v| v.to_owned()),
+            access_key_id: field(27).map(|v| v.to_owned()),
+        })
+    }
+}
+
+impl FromStr for AccessLogEntry {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AccessLogEntry::parse(s)
+    }
+}
+
+/// 逐行解析日志文件的内容，跳过空行
+pub fn parse(content: &str) -> impl Iterator<Item = Result<AccessLogEntry, Error>> + '_ {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(AccessLogEntry::parse)
+}
+
+fn invalid_field(index: usize, value: &str) -> Error {
+    Error::InvalidAccessLog(format!("第{}个字段格式错误：{}", index + 1, value))
+}
+
+//按空格拆分字段，去除包裹字段的双引号和方括号
+fn split_fields(line: &str) -> Result<Vec<&str>, Error> {
+    let mut fields = Vec::with_capacity(32);
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let (field, next) = match rest.as_bytes()[0] {
+            b'"' => quoted(rest, '"', line)?,
+            b'[' => quoted(rest, ']', line)?,
+            _ => rest.split_once(' ').unwrap_or((rest, "")),
+        };
+        fields.push(field);
+        rest = next.trim_start();
+    }
+    Ok(fields)
+}
+
+//取出以close结尾的字段，返回字段内容和剩余部分
+fn quoted<'a>(rest: &'a str, close: char, line: &str) -> Result<(&'a str, &'a str), Error> {
+    let end = rest[1..]
+        .find(close)
+        .ok_or_else(|| Error::InvalidAccessLog(format!("引号或方括号未闭合：{}", line)))?;
+    Ok((&rest[1..end + 1], &rest[end + 2..]))
+}
@@ -0,0 +1,219 @@
+//! 摘要与签名算法的实现
+//!
+//! 通过feature选择HMAC-SHA1、HMAC-SHA256、SHA-256的实现：
+//! - backend-ring：使用ring，默认开启
+//! - backend-rustcrypto：使用纯Rust实现的RustCrypto，适用于无法编译ring的平台
+//! - backend-openssl：使用OpenSSL，可以搭配通过FIPS认证的OpenSSL使用
+//!
+//! 同时开启多个时，优先级为 backend-openssl > backend-rustcrypto > backend-ring
+//!
+//! MD5仅用于数据校验，不涉及安全性，统一使用纯Rust实现的md-5，任何平台都可以编译
+
+#[cfg(not(any(
+    feature = "backend-ring",
+    feature = "backend-rustcrypto",
+    feature = "backend-openssl"
+)))]
+compile_error!("需要开启 backend-ring、backend-rustcrypto、backend-openssl 中的至少一个feature");
+
+use md5::Digest;
+
+pub(crate) use self::imp::{HmacSha1, HmacSha256, Sha256};
+
+// MD5
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Md5(md5::Md5);
+impl Md5 {
+    pub fn new() -> Self {
+        Md5(md5::Md5::new())
+    }
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+    pub fn finish(&self) -> [u8; 16] {
+        self.0.clone().finalize().into()
+    }
+}
+
+#[cfg(feature = "backend-openssl")]
+mod imp {
+    use crate::Error;
+    use openssl::{
+        error::ErrorStack,
+        hash::MessageDigest,
+        pkey::{PKey, Private},
+        sha,
+        sign::Signer,
+    };
+
+    // HMAC-SHA1
+    pub(crate) struct HmacSha1 {
+        key: Result<PKey<Private>, ErrorStack>,
+    }
+    impl HmacSha1 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha1 {
+                key: PKey::hmac(key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            let crypto_error = |e: ErrorStack| Error::CryptoError(e.to_string());
+            let key = self.key.as_ref().map_err(|e| crypto_error(e.clone()))?;
+            let mut signer = Signer::new(MessageDigest::sha1(), key).map_err(crypto_error)?;
+            signer.update(data).map_err(crypto_error)?;
+            signer.sign_to_vec().map_err(crypto_error)
+        }
+    }
+
+    // HMAC-SHA256
+    pub(crate) struct HmacSha256 {
+        key: Result<PKey<Private>, ErrorStack>,
+    }
+    impl HmacSha256 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha256 {
+                key: PKey::hmac(key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            let crypto_error = |e: ErrorStack| Error::CryptoError(e.to_string());
+            let key = self.key.as_ref().map_err(|e| crypto_error(e.clone()))?;
+            let mut signer = Signer::new(MessageDigest::sha256(), key).map_err(crypto_error)?;
+            signer.update(data).map_err(crypto_error)?;
+            signer.sign_to_vec().map_err(crypto_error)
+        }
+    }
+
+    // SHA-256
+    #[derive(Clone)]
+    pub(crate) struct Sha256(sha::Sha256);
+    impl Sha256 {
+        pub fn new() -> Self {
+            Sha256(sha::Sha256::new())
+        }
+        pub fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+        pub fn finish(&self) -> [u8; 32] {
+            self.0.clone().finish()
+        }
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", not(feature = "backend-openssl")))]
+mod imp {
+    use crate::Error;
+    use hmac::{Hmac, Mac};
+    use sha2::Digest;
+
+    // HMAC-SHA1
+    pub(crate) struct HmacSha1 {
+        mac: Result<Hmac<sha1::Sha1>, hmac::digest::InvalidLength>,
+    }
+    impl HmacSha1 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha1 {
+                mac: Hmac::new_from_slice(key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            let mut mac = self
+                .mac
+                .clone()
+                .map_err(|e| Error::CryptoError(e.to_string()))?;
+            mac.update(data);
+            Ok(mac.finalize().into_bytes().to_vec())
+        }
+    }
+
+    // HMAC-SHA256
+    pub(crate) struct HmacSha256 {
+        mac: Result<Hmac<sha2::Sha256>, hmac::digest::InvalidLength>,
+    }
+    impl HmacSha256 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha256 {
+                mac: Hmac::new_from_slice(key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            let mut mac = self
+                .mac
+                .clone()
+                .map_err(|e| Error::CryptoError(e.to_string()))?;
+            mac.update(data);
+            Ok(mac.finalize().into_bytes().to_vec())
+        }
+    }
+
+    // SHA-256
+    #[derive(Clone)]
+    pub(crate) struct Sha256(sha2::Sha256);
+    impl Sha256 {
+        pub fn new() -> Self {
+            Sha256(sha2::Sha256::new())
+        }
+        pub fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+        pub fn finish(&self) -> [u8; 32] {
+            self.0.clone().finalize().into()
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "backend-ring",
+    not(any(feature = "backend-rustcrypto", feature = "backend-openssl"))
+))]
+mod imp {
+    use crate::Error;
+    use ring::{digest, hmac};
+
+    // HMAC-SHA1
+    pub(crate) struct HmacSha1 {
+        key: hmac::Key,
+    }
+    impl HmacSha1 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha1 {
+                key: hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(hmac::sign(&self.key, data).as_ref().to_vec())
+        }
+    }
+
+    // HMAC-SHA256
+    pub(crate) struct HmacSha256 {
+        key: hmac::Key,
+    }
+    impl HmacSha256 {
+        pub fn new(key: &[u8]) -> Self {
+            HmacSha256 {
+                key: hmac::Key::new(hmac::HMAC_SHA256, key),
+            }
+        }
+        pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(hmac::sign(&self.key, data).as_ref().to_vec())
+        }
+    }
+
+    // SHA-256
+    #[derive(Clone)]
+    pub(crate) struct Sha256(digest::Context);
+    impl Sha256 {
+        pub fn new() -> Self {
+            Sha256(digest::Context::new(&digest::SHA256))
+        }
+        pub fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+        pub fn finish(&self) -> [u8; 32] {
+            let mut value = [0; 32];
+            value.copy_from_slice(self.0.clone().finish().as_ref());
+            value
+        }
+    }
+}
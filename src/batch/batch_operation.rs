@@ -0,0 +1,157 @@
+use crate::{
+    common::{encode_key, Acl, RestoreTier},
+    Error, OssBucket,
+};
+use futures_util::{stream, StreamExt};
+
+/// 批量操作中的单个操作
+#[derive(Debug, Clone)]
+pub enum BatchOperation {
+    /// 删除文件
+    Delete { key: String },
+    /// 复制同一存储空间中的文件，source为源文件路径，target为目标文件路径
+    Copy { source: String, target: String },
+    /// 设置文件ACL
+    SetAcl { key: String, acl: Acl },
+    /// 设置文件标签，会覆盖文件原有的标签
+    SetTagging {
+        key: String,
+        tags: Vec<(String, String)>,
+    },
+    /// 解冻文件
+    Restore {
+        key: String,
+        days: Option<u32>,
+        tier: Option<RestoreTier>,
+    },
+}
+
+/// 批量操作的执行结果
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// 执行成功的操作
+    pub succeeded: Vec<BatchOperation>,
+    /// 执行失败的操作，以及最后一次执行的错误信息
+    pub failed: Vec<(BatchOperation, Error)>,
+}
+impl BatchReport {
+    /// 是否全部执行成功
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 批量操作
+///
+/// 以受控的并发数执行一组操作，单个操作失败不会影响其他操作，执行完成后返回每个操作的结果
+///
+/// ```
+/// let report = Batch::new(&bucket)
+///     .add_operation(BatchOperation::Delete { key: "tmp/1.txt".to_owned() })
+///     .add_operation(BatchOperation::SetAcl { key: "public/1.png".to_owned(), acl: Acl::PublicRead })
+///     .set_parallel(16)
+///     .send()
+///     .await;
+/// for (operation, e) in report.failed {
+///     println!("{:?} {}", operation, e);
+/// }
+/// ```
+pub struct Batch {
+    bucket: OssBucket,
+    operations: Vec<BatchOperation>,
+    parallel: usize,
+    max_retries: u32,
+}
+impl Batch {
+    pub fn new(bucket: &OssBucket) -> Self {
+        Batch {
+            bucket: bucket.clone(),
+            operations: Vec::new(),
+            parallel: 8,
+            max_retries: 2,
+        }
+    }
+    /// 添加操作
+    pub fn add_operation(mut self, operation: BatchOperation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+    /// 添加多个操作
+    pub fn add_operations(mut self, operations: impl IntoIterator<Item = BatchOperation>) -> Self {
+        self.operations.extend(operations);
+        self
+    }
+    /// 设置同时执行的操作数量
+    ///
+    /// 默认值：8
+    pub fn set_parallel(mut self, parallel: usize) -> Self {
+        self.parallel = parallel.max(1);
+        self
+    }
+    /// 设置操作失败时的最大重试次数，仅对网络异常、OSS服务端错误进行重试
+    ///
+    /// 默认值：2
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// 执行所有操作
+    ///
+    pub async fn send(self) -> BatchReport {
+        let (bucket, max_retries) = (&self.bucket, self.max_retries);
+        let mut results = stream::iter(self.operations)
+            .map(|operation| async move {
+                let mut retries = 0;
+                loop {
+                    match execute(bucket, &operation).await {
+                        Err(e) if retries < max_retries && e.is_retryable() => retries += 1,
+                        result => return (operation, result),
+                    }
+                }
+            })
+            .buffer_unordered(self.parallel);
+        let mut report = BatchReport::default();
+        while let Some((operation, result)) = results.next().await {
+            match result {
+                Ok(_) => report.succeeded.push(operation),
+                Err(e) => report.failed.push((operation, e)),
+            }
+        }
+        report
+    }
+}
+
+//执行单个操作
+async fn execute(bucket: &OssBucket, operation: &BatchOperation) -> Result<(), Error> {
+    match operation {
+        BatchOperation::Delete { key } => bucket.object(key).del_object().send().await,
+        BatchOperation::Copy { source, target } => {
+            let copy_source = format!(
+                "/{}/{}",
+                bucket.oss.bucket.clone().unwrap_or_default(),
+                encode_key(source)
+            );
+            bucket.object(target).copy_object(&copy_source).send().await
+        }
+        BatchOperation::SetAcl { key, acl } => {
+            bucket.object(key).put_object_acl(acl.clone()).send().await
+        }
+        BatchOperation::SetTagging { key, tags } => {
+            bucket
+                .object(key)
+                .put_object_tagging(tags.clone())
+                .send()
+                .await
+        }
+        BatchOperation::Restore { key, days, tier } => {
+            let mut restore = bucket.object(key).restore_object();
+            if let Some(days) = days {
+                restore = restore.set_days(*days);
+            }
+            if let Some(tier) = tier {
+                restore = restore.set_tier(tier.clone());
+            }
+            restore.send().await
+        }
+    }
+}
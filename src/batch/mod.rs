@@ -0,0 +1,5 @@
+//! 批量操作，以受控的并发数对大量文件执行删除、复制、设置ACL、设置标签、解冻等操作，并返回每个操作的执行结果
+
+pub use self::batch_operation::{Batch, BatchOperation, BatchReport};
+
+mod batch_operation;
@@ -0,0 +1,248 @@
+//! OSS命令行工具
+//!
+//! 通过环境变量 OSS_ACCESS_KEY_ID、OSS_ACCESS_KEY_SECRET、OSS_ENDPOINT 配置访问凭证和Endpoint
+//!
+//! ```text
+//! oss ls [oss://bucket/prefix]
+//! oss cp <源路径> <目标路径>
+//! oss rm [-r] oss://bucket/key
+//! oss sync [--delete] [--dry-run] <本地目录> oss://bucket/prefix
+//! oss presign oss://bucket/key [有效秒数]
+//! oss stat oss://bucket/key
+//! ```
+
+use aliyun_oss_rs::{
+    batch::{Batch, BatchOperation},
+    common::Paginator,
+    sync::LocalSync,
+    transfer::TransferManager,
+    Error, OssBucket, OssClient,
+};
+use std::{env, process::ExitCode, time::Duration};
+
+const USAGE: &str = "用法：
+  oss ls [oss://bucket/prefix]                              列举存储空间或文件
+  oss cp <源路径> <目标路径>                                 上传、下载或复制文件
+  oss rm [-r] oss://bucket/key                              删除文件，-r 删除前缀下的所有文件
+  oss sync [--delete] [--dry-run] <本地目录> oss://bucket/prefix  同步本地目录到OSS
+  oss presign oss://bucket/key [有效秒数]                    生成授权url，默认有效期3600秒
+  oss stat oss://bucket/key                                 查询文件元信息
+
+环境变量：OSS_ACCESS_KEY_ID、OSS_ACCESS_KEY_SECRET、OSS_ENDPOINT";
+
+fn main() -> ExitCode {
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    runtime.block_on(run())
+}
+
+async fn run() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let cli = match Cli::from_env() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (flags, args): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+    let has_flag = |names: &[&str]| flags.iter().any(|flag| names.contains(&flag.as_str()));
+    let result = match (command, args.as_slice()) {
+        ("ls", []) => cli.list_buckets().await,
+        ("ls", [path]) => cli.list_objects(path).await,
+        ("cp", [source, target]) => cli.copy(source, target).await,
+        ("rm", [path]) => cli.remove(path, has_flag(&["-r", "--recursive"])).await,
+        ("sync", [local_dir, path]) => {
+            cli.sync(
+                local_dir,
+                path,
+                has_flag(&["--delete"]),
+                has_flag(&["--dry-run"]),
+            )
+            .await
+        }
+        ("presign", [path]) => cli.presign(path, "3600"),
+        ("presign", [path, seconds]) => cli.presign(path, seconds),
+        ("stat", [path]) => cli.stat(path).await,
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct Cli {
+    client: OssClient,
+    endpoint: String,
+}
+impl Cli {
+    fn from_env() -> Result<Self, String> {
+        let var = |key: &str| env::var(key).map_err(|_| format!("缺少环境变量：{}", key));
+        let client = OssClient::new(&var("OSS_ACCESS_KEY_ID")?, &var("OSS_ACCESS_KEY_SECRET")?);
+        let endpoint = env::var("OSS_ENDPOINT").unwrap_or_else(|_| "oss.aliyuncs.com".to_owned());
+        Ok(Cli { client, endpoint })
+    }
+    //解析 oss://bucket/key 格式的路径
+    fn parse(&self, path: &str) -> Option<(OssBucket, String)> {
+        let path = path.strip_prefix("oss://")?;
+        let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+        match bucket.is_empty() {
+            true => None,
+            false => Some((self.client.bucket(bucket, &self.endpoint), key.to_owned())),
+        }
+    }
+    fn parse_object(&self, path: &str) -> Result<(OssBucket, String), Error> {
+        match self.parse(path) {
+            Some((bucket, key)) if !key.is_empty() => Ok((bucket, key)),
+            _ => Err(Error::PathNotSupported),
+        }
+    }
+    async fn list_buckets(&self) -> Result<(), Error> {
+        for bucket in self.client.list_buckets().all().await? {
+            println!("{}\t{}", bucket.name, bucket.extranet_endpoint);
+        }
+        Ok(())
+    }
+    async fn list_objects(&self, path: &str) -> Result<(), Error> {
+        let (bucket, prefix) = self.parse(path).ok_or(Error::PathNotSupported)?;
+        let mut list = bucket.list_objects().set_prefix(&prefix).set_delimiter("/");
+        loop {
+            let result = list.send().await?;
+            for common_prefix in result.common_prefixes.unwrap_or_default() {
+                println!("{:>12}  {:<19}  {}", "DIR", "", common_prefix.prefix);
+            }
+            for file in result.contents.unwrap_or_default() {
+                println!(
+                    "{:>12}  {:<19}  {}",
+                    file.size, file.last_modified, file.key
+                );
+            }
+            match result.next_continuation_token {
+                Some(token) => {
+                    list = bucket
+                        .list_objects()
+                        .set_prefix(&prefix)
+                        .set_delimiter("/")
+                        .set_continuation_token(token)
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+    async fn copy(&self, source: &str, target: &str) -> Result<(), Error> {
+        let manager = TransferManager::new();
+        match (self.parse(source), self.parse(target)) {
+            (None, Some(_)) => {
+                let (bucket, key) = self.parse_object(target)?;
+                manager.upload(source, &bucket.object(&key)).await
+            }
+            (Some(_), None) => {
+                let (bucket, key) = self.parse_object(source)?;
+                manager.download(&bucket.object(&key), target).await
+            }
+            (Some(_), Some(_)) => {
+                let (source_bucket, source_key) = self.parse_object(source)?;
+                let (target_bucket, target_key) = self.parse_object(target)?;
+                manager
+                    .copy(
+                        &source_bucket.object(&source_key),
+                        &target_bucket.object(&target_key),
+                    )
+                    .await
+            }
+            (None, None) => Err(Error::PathNotSupported),
+        }
+    }
+    async fn remove(&self, path: &str, recursive: bool) -> Result<(), Error> {
+        if !recursive {
+            let (bucket, key) = self.parse_object(path)?;
+            return bucket.object(&key).del_object().send().await;
+        }
+        let (bucket, prefix) = self.parse(path).ok_or(Error::PathNotSupported)?;
+        let operations = bucket
+            .list_objects()
+            .set_prefix(&prefix)
+            .all()
+            .await?
+            .into_iter()
+            .map(|file| BatchOperation::Delete { key: file.key });
+        let report = Batch::new(&bucket).add_operations(operations).send().await;
+        println!("已删除 {} 个文件", report.succeeded.len());
+        match report.failed.into_iter().next() {
+            None => Ok(()),
+            Some((_, e)) => Err(e),
+        }
+    }
+    async fn sync(
+        &self,
+        local_dir: &str,
+        path: &str,
+        delete_extra: bool,
+        dry_run: bool,
+    ) -> Result<(), Error> {
+        let (bucket, prefix) = self.parse(path).ok_or(Error::PathNotSupported)?;
+        let mut sync = LocalSync::new(&bucket, local_dir, prefix);
+        if delete_extra {
+            sync = sync.delete_extra();
+        }
+        if dry_run {
+            sync = sync.dry_run();
+        }
+        let report = sync.send().await?;
+        for key in &report.transferred {
+            println!("上传 {}", key);
+        }
+        for key in &report.deleted {
+            println!("删除 {}", key);
+        }
+        println!("跳过 {} 个未变化的文件", report.skipped);
+        Ok(())
+    }
+    fn presign(&self, path: &str, seconds: &str) -> Result<(), Error> {
+        let (bucket, key) = self.parse_object(path)?;
+        let seconds = seconds
+            .parse::<u64>()
+            .map_err(|_| Error::InvalidCharacter)?;
+        let expires = Duration::from_secs(seconds);
+        println!("{}", bucket.object(&key).get_object_url().url(expires)?);
+        Ok(())
+    }
+    async fn stat(&self, path: &str) -> Result<(), Error> {
+        let (bucket, key) = self.parse_object(path)?;
+        let mut headers = bucket
+            .object(&key)
+            .head_object()
+            .send()
+            .await?
+            .into_iter()
+            .collect::<Vec<_>>();
+        headers.sort();
+        for (key, value) in headers {
+            println!("{}: {}", key, value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! 同步调用
+//!
+//! 开启 blocking feature 后，所有操作的异步方法都可以通过 Wait::wait() 同步执行，适用于命令行工具、build.rs 等不需要异步运行时的场景
+//!
+//! 所有同步调用共享一个后台的单线程tokio运行时，首次调用时创建；可以在多个线程中同时调用，请求会依次在运行时中执行
+//!
+//! 不能在异步代码（tokio运行时）中调用，否则会panic；异步代码请直接使用 .await
+//!
+//! ```
+//! use aliyun_oss_rs::blocking::Wait;
+//!
+//! let client = OssClient::new("AccessKey ID", "AccessKey Secret");
+//! let bucket = client.bucket("for-rs-test", "oss-cn-zhangjiakou.aliyuncs.com");
+//! let files = bucket.list_objects().send().wait()?;
+//! bucket.object("rust.png").put_object().send_file("/data/rust.png").wait()?;
+//! ```
+
+use crate::Error;
+use futures_util::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+//获取共享的运行时，不存在时创建
+fn runtime() -> Result<&'static Runtime, Error> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Builder::new_current_thread().enable_all().build()?;
+    //多个线程同时创建时，只保留第一个
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// 同步执行任意的异步任务，返回任务的结果；运行时创建失败时返回错误
+pub fn block_on<F: Future>(future: F) -> Result<F::Output, Error> {
+    Ok(runtime()?.block_on(future))
+}
+
+/// 同步执行OSS操作
+pub trait Wait<T> {
+    /// 阻塞当前线程，直到操作完成
+    fn wait(self) -> Result<T, Error>;
+}
+impl<F, T> Wait<T> for F
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    fn wait(self) -> Result<T, Error> {
+        block_on(self)?
+    }
+}
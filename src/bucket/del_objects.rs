@@ -1,11 +1,11 @@
 use crate::{
+    checksum::Md5,
     error::normal_error,
     request::{Oss, OssRequest},
+    xml::XmlBuilder,
     Error,
 };
-use base64::{engine::general_purpose, Engine};
 use hyper::Method;
-use md5::{Digest, Md5};
 use std::collections::HashSet;
 
 /// 批量删除文件
@@ -16,6 +16,7 @@ use std::collections::HashSet;
 pub struct DelObjects {
     req: OssRequest,
     objects: HashSet<String>,
+    url_encode: bool,
 }
 impl DelObjects {
     pub(super) fn new(oss: Oss, files: Vec<impl ToString>) -> Self {
@@ -26,13 +27,18 @@ impl DelObjects {
             DelObjects {
                 req,
                 objects: HashSet::new(),
+                url_encode: false,
             }
         } else {
             let mut objects = HashSet::with_capacity(len);
             for object in files {
                 objects.insert(object.to_string());
             }
-            DelObjects { req, objects }
+            DelObjects {
+                req,
+                objects,
+                url_encode: false,
+            }
         }
     }
     /// 添加要删除的文件
@@ -49,25 +55,38 @@ impl DelObjects {
             self
         }
     }
+    /// 请求body中的文件路径使用url编码（encoding-type=url）
+    ///
+    /// 文件路径中的 & < > 等字符会自动转义，不需要设置；XML中不能出现控制字符，文件路径包含控制字符时会自动启用
+    pub fn set_encoding_type_url(mut self) -> Self {
+        self.url_encode = true;
+        self
+    }
     /// 发送请求
     ///
     pub async fn send(mut self) -> Result<(), Error> {
         //生成body
-        let body = format!(
-            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Delete><Quiet>true</Quiet>{}</Delete>",
-            self.objects
+        let url_encode = self.url_encode
+            || self
+                .objects
                 .iter()
-                .map(|v| format!("<Object><Key>{}</Key></Object>", v))
-                .collect::<Vec<_>>()
-                .join("")
-        );
+                .any(|v| v.chars().any(|c| c.is_control()));
+        if url_encode {
+            self.req.insert_query("encoding-type", "url");
+        }
+        let mut xml = XmlBuilder::new().set_url_encode(url_encode);
+        xml.start("Delete").element("Quiet", true);
+        for object in &self.objects {
+            xml.start("Object").key("Key", object).end("Object");
+        }
+        xml.end("Delete");
+        let body = xml.finish();
         //计算body长度
         let body_len = body.len();
         //计算body md5值
         let mut hasher = Md5::new();
-        hasher.update(&body);
-        let result = hasher.finalize();
-        let body_md5 = general_purpose::STANDARD.encode(&result);
+        hasher.update(body.as_bytes());
+        let body_md5 = hasher.finalize_base64();
         //插入body内容
         self.req.set_body(body.into());
         //插入header内容
@@ -0,0 +1,53 @@
+use super::{
+    walk::{list_children, merge},
+    DelObjects, ListObjects, WalkEntry,
+};
+use crate::{common::Paginator, request::Oss, Error, OssObject};
+use futures_util::TryStreamExt;
+
+//目录对应的前缀，以 / 结尾
+fn dir_prefix(prefix: &str) -> Result<String, Error> {
+    let prefix = prefix.trim_start_matches('/');
+    match prefix {
+        "" => Err(Error::InvalidKey("目录名称不能为空".to_owned())),
+        prefix if prefix.ends_with('/') => Ok(prefix.to_owned()),
+        prefix => Ok(format!("{}/", prefix)),
+    }
+}
+
+//上传以 / 结尾的空文件作为目录占位
+pub(super) async fn create_dir(oss: Oss, prefix: &str) -> Result<(), Error> {
+    OssObject::new(oss, dir_prefix(prefix)?)
+        .put_object()
+        .set_mime("application/octet-stream")
+        .send_content(Vec::new())
+        .await
+}
+
+//删除目录下的全部文件，包括子目录和目录自身的占位文件
+pub(super) async fn delete_dir(oss: Oss, prefix: &str) -> Result<usize, Error> {
+    let prefix = dir_prefix(prefix)?;
+    let mut objects = ListObjects::new(oss.clone())
+        .set_prefix(&prefix)
+        .into_stream()
+        .try_chunks(1000);
+    let mut deleted = 0;
+    //每次最多删除1000个文件
+    while let Some(chunk) = objects.try_next().await.map_err(|e| e.1)? {
+        deleted += chunk.len();
+        DelObjects::new(oss.clone(), chunk.into_iter().map(|v| v.key).collect())
+            .send()
+            .await?;
+    }
+    Ok(deleted)
+}
+
+//列举目录下的直接子目录和文件
+pub(super) async fn list_dir(oss: Oss, prefix: &str) -> Result<Vec<WalkEntry>, Error> {
+    let prefix = match prefix.trim_start_matches('/') {
+        "" => String::new(),
+        prefix => dir_prefix(prefix)?,
+    };
+    let (dirs, files) = list_children(&oss, &prefix).await?;
+    Ok(merge(dirs, files, 1))
+}
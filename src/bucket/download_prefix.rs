@@ -0,0 +1,139 @@
+use super::ListObjects;
+use crate::{common::Paginator, request::Oss, Error, OssObject};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use std::path::{Component, Path, PathBuf};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::{AsyncWriteExt, BufWriter},
+};
+
+/// 下载指定前缀下的所有文件到本地目录
+///
+/// 文件在本地的路径为 本地目录 + 文件路径去掉前缀后的部分，保留原有的目录层级；以 / 结尾的目录文件只创建目录
+///
+/// 默认会覆盖本地已存在的文件
+///
+/// ```
+/// let downloaded = bucket
+///     .download_prefix("images/2023/", "/data/images")
+///     .skip_existing()
+///     .set_parallel(8)
+///     .send()
+///     .await;
+/// ```
+pub struct DownloadPrefix {
+    oss: Oss,
+    prefix: String,
+    local_dir: PathBuf,
+    parallel: usize,
+    skip_existing: bool,
+}
+impl DownloadPrefix {
+    pub(super) fn new(oss: Oss, prefix: impl ToString, local_dir: impl Into<PathBuf>) -> Self {
+        DownloadPrefix {
+            oss,
+            prefix: prefix.to_string(),
+            local_dir: local_dir.into(),
+            parallel: 4,
+            skip_existing: false,
+        }
+    }
+    /// 设置同时下载的文件数量
+    ///
+    /// 默认值：4
+    pub fn set_parallel(mut self, parallel: usize) -> Self {
+        self.parallel = parallel.max(1);
+        self
+    }
+    /// 跳过本地已存在且大小一致的文件
+    pub fn skip_existing(mut self) -> Self {
+        self.skip_existing = true;
+        self
+    }
+    /// 开始下载
+    ///
+    /// 返回值为实际下载的文件数量，不包含跳过的文件
+    pub async fn send(self) -> Result<usize, Error> {
+        //判断路径
+        if self.local_dir.to_string_lossy().contains("://") {
+            return Err(Error::PathNotSupported);
+        }
+        //列举前缀下的全部文件
+        let files = ListObjects::new(self.oss.clone())
+            .set_prefix(&self.prefix)
+            .all()
+            .await?
+            .into_iter()
+            .map(|object| {
+                let local_path = self.local_path(&object.key)?;
+                Ok((object.key, object.size, local_path))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        //并发下载
+        let downloaded = stream::iter(files)
+            .map(|(key, size, local_path)| {
+                let oss = self.oss.clone();
+                let skip_existing = self.skip_existing;
+                async move {
+                    //目录文件
+                    if key.ends_with('/') {
+                        fs::create_dir_all(&local_path).await?;
+                        return Ok::<_, Error>(false);
+                    }
+                    if skip_existing {
+                        if let Ok(metadata) = fs::metadata(&local_path).await {
+                            if metadata.is_file() && metadata.len() == size {
+                                return Ok(false);
+                            }
+                        }
+                    }
+                    if let Some(dir) = local_path.parent() {
+                        fs::create_dir_all(dir).await?;
+                    }
+                    let buffer_size = oss.buffer_size;
+                    let mut stream = OssObject::new(oss, key)
+                        .get_object()
+                        .download_to_stream()
+                        .await?;
+                    let file = OpenOptions::new()
+                        .write(true)
+                        .create(true)
+                        .truncate(true)
+                        .open(&local_path)
+                        .await?;
+                    let mut writer = BufWriter::with_capacity(buffer_size, file);
+                    while let Some(chunk) = stream.next().await {
+                        writer.write_all(&chunk?).await?;
+                    }
+                    writer.flush().await?;
+                    Ok(true)
+                }
+            })
+            .buffer_unordered(self.parallel)
+            .try_fold(0, |count, downloaded| async move {
+                Ok(count + downloaded as usize)
+            })
+            .await?;
+        Ok(downloaded)
+    }
+    //计算文件在本地的路径，文件路径中包含 .. 等可能跳出本地目录的部分时返回错误
+    fn local_path(&self, key: &str) -> Result<PathBuf, Error> {
+        let relative = key
+            .strip_prefix(&self.prefix)
+            .unwrap_or(key)
+            .trim_start_matches('/');
+        //前缀即为完整的文件路径时，使用文件名
+        let relative = match relative {
+            "" if !key.ends_with('/') => Path::new(key).file_name().map(Path::new),
+            relative => Some(Path::new(relative)),
+        }
+        .ok_or(Error::InvalidCharacter)?;
+        if relative
+            .components()
+            .any(|v| !matches!(v, Component::Normal(_)))
+        {
+            return Err(Error::InvalidCharacter);
+        }
+        Ok(self.local_dir.join(relative))
+    }
+}
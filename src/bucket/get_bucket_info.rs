@@ -1,11 +1,11 @@
 use crate::{
-    common::{Acl, DataRedundancyType, Owner, StorageClass},
+    common::{request_id, Acl, DataRedundancyType, OssDateTime, Owner, StorageClass},
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回内容
 #[derive(Debug, Deserialize)]
@@ -15,7 +15,7 @@ pub(crate) struct BucketList {
 }
 
 /// 存储空间详细信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct BucketInfo {
     /// 访问跟踪状态
@@ -23,7 +23,7 @@ pub struct BucketInfo {
     /// 备注信息
     pub comment: String,
     /// 创建日期
-    pub creation_date: String,
+    pub creation_date: OssDateTime,
     /// 跨区域复制状态
     pub cross_region_replication: String,
     /// 数据容灾类型
@@ -50,10 +50,13 @@ pub struct BucketInfo {
     pub server_side_encryption_rule: ServerSideEncryptionRule,
     /// 日志信息
     pub bucket_policy: BucketPolicy,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// 存储空间的访问权限信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct AccessControlList {
     ///访问权限
@@ -61,7 +64,7 @@ pub struct AccessControlList {
 }
 
 /// 存储空间的服务端加密信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerSideEncryptionRule {
     /// 服务端默认加密方式
     #[serde(rename = "SSEAlgorithm")]
@@ -69,7 +72,7 @@ pub struct ServerSideEncryptionRule {
 }
 
 /// 存储空间的日志信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct BucketPolicy {
     /// 存储日志记录的存储空间名称
@@ -98,11 +101,13 @@ impl GetBucketInfo {
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
-                let bucket_info: BucketList = serde_xml_rs::from_reader(&*response_bytes)
+                let mut bucket_info: BucketList = serde_xml_rs::from_reader(&*response_bytes)
                     .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                bucket_info.bucket.request_id = request_id;
                 Ok(bucket_info.bucket)
             }
             _ => Err(normal_error(response).await),
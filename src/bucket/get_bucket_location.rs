@@ -0,0 +1,48 @@
+use crate::{
+    error::normal_error,
+    request::{Oss, OssRequest},
+    Error,
+};
+use hyper::{body::to_bytes, Method};
+use serde_derive::Deserialize;
+
+// 返回内容
+#[derive(Debug, Deserialize)]
+struct LocationConstraint {
+    #[serde(rename = "$value")]
+    location: String,
+}
+
+/// 查询存储空间所在的地域，返回OSS专用的地域ID，如 oss-cn-hangzhou
+///
+/// 可以通过任意地域的Endpoint查询
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31967.html)
+pub struct GetBucketLocation {
+    req: OssRequest,
+}
+impl GetBucketLocation {
+    pub(super) fn new(oss: Oss) -> Self {
+        let mut req = OssRequest::new(oss, Method::GET);
+        req.insert_query("location", "");
+        GetBucketLocation { req }
+    }
+    /// 发送请求
+    pub async fn send(self) -> Result<String, Error> {
+        //构建http请求
+        let response = self.req.send_to_oss()?.await?;
+        //拆解响应消息
+        let status_code = response.status();
+        match status_code {
+            code if code.is_success() => {
+                let response_bytes = to_bytes(response.into_body())
+                    .await
+                    .map_err(|_| Error::OssInvalidResponse(None))?;
+                let location: LocationConstraint = serde_xml_rs::from_reader(&*response_bytes)
+                    .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                Ok(location.location)
+            }
+            _ => Err(normal_error(response).await),
+        }
+    }
+}
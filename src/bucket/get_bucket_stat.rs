@@ -1,14 +1,15 @@
 use crate::{
+    common::request_id,
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回内容
 /// 存储空间的容量信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct BucketStat {
     /// 总存储容量，单位字节
@@ -53,6 +54,9 @@ pub struct BucketStat {
     pub deep_cold_archive_real_storage: u64,
     /// 深度冷归档存储类型的文件数量
     pub deep_cold_archive_object_count: u64,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// 获取指定存储空间的存储容量以及文件数量
@@ -80,11 +84,13 @@ impl GetBucketStat {
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
-                let bucket_stat: BucketStat = serde_xml_rs::from_reader(&*response_bytes)
+                let mut bucket_stat: BucketStat = serde_xml_rs::from_reader(&*response_bytes)
                     .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                bucket_stat.request_id = request_id;
                 Ok(bucket_stat)
             }
             _ => Err(normal_error(response).await),
@@ -1,13 +1,14 @@
 use crate::{
+    common::{request_id, OssDateTime, PageFuture, Paginator},
     error::{normal_error, Error},
     request::{Oss, OssRequest},
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use std::cmp;
 
 // 返回的内容
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListMultipartUploadsResult {
     pub is_truncated: bool,
@@ -15,23 +16,26 @@ pub struct ListMultipartUploadsResult {
     pub next_upload_id_marker: String,
     pub upload: Option<Vec<Upload>>,
     pub common_prefixes: Option<Vec<CommonPrefixes>>,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// 分组列表
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct CommonPrefixes {
     /// 前缀
     pub prefix: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Upload {
     pub key: String,
     pub upload_id: String,
     pub storage_class: String,
-    pub initiated: String,
+    pub initiated: OssDateTime,
 }
 
 /// 列举所有执行中的Multipart Upload事件，即已经初始化但还未完成（Complete）或者还未中止（Abort）的Multipart Upload事件
@@ -45,6 +49,7 @@ impl ListUploads {
     pub(super) fn new(oss: Oss) -> Self {
         let mut req = OssRequest::new(oss, Method::GET);
         req.insert_query("uploads", "");
+        req.accept_gzip();
         ListUploads { req }
     }
     /// 对Object名字进行分组的字符。所有Object名字包含指定的前缀，第一次出现delimiter字符之间的Object作为一组元素（即CommonPrefixes）
@@ -87,15 +92,36 @@ impl ListUploads {
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
-                let result: ListMultipartUploadsResult =
+                let mut result: ListMultipartUploadsResult =
                     serde_xml_rs::from_reader(&*response_bytes)
                         .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                result.request_id = request_id;
                 Ok(result)
             }
             _ => Err(normal_error(response).await),
         }
     }
 }
+/// 分页列举时只返回分片上传事件，设置了delimiter时，分组信息将被忽略
+impl Paginator for ListUploads {
+    type Item = Upload;
+    fn next_page(self) -> PageFuture<Self> {
+        Box::pin(async move {
+            let mut next = ListUploads {
+                req: self.req.clone_without_body(),
+            };
+            let result = self.send().await?;
+            let next = result.is_truncated.then(|| {
+                next.req.insert_query("key-marker", result.next_key_marker);
+                next.req
+                    .insert_query("upload-id-marker", result.next_upload_id_marker);
+                next
+            });
+            Ok((result.upload.unwrap_or_default(), next))
+        })
+    }
+}
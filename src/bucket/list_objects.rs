@@ -1,33 +1,67 @@
 use crate::{
-    common::{Owner, StorageClass},
+    cache::CacheKey,
+    common::{request_id, OssDateTime, Owner, PageFuture, Paginator, RestoreStatus, StorageClass},
     error::normal_error,
     request::{Oss, OssRequest},
+    xml::XmlSplitter,
     Error,
 };
-use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use hyper::{Body, Method, Response};
+use percent_encoding::percent_decode_str;
+use serde_derive::{Deserialize, Serialize};
 use std::cmp;
 
 // 返回内容
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ObjectsList {
+    /// 存储空间名称
+    #[serde(default)]
+    pub name: String,
+    /// 本次查询使用的前缀
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 本次查询使用的分组字符
+    #[serde(default)]
+    pub delimiter: Option<String>,
+    /// 本次查询使用的起始位置
+    #[serde(default)]
+    pub start_after: Option<String>,
+    /// 本次查询使用的token
+    #[serde(default)]
+    pub continuation_token: Option<String>,
+    /// 本次查询返回的最大数量
+    #[serde(default)]
+    pub max_keys: u32,
+    /// 本次返回的文件和分组的总数
+    #[serde(default)]
+    pub key_count: u32,
+    /// 是否还有未返回的文件，为true时可以使用 next_continuation_token 继续查询
+    #[serde(default)]
+    pub is_truncated: bool,
     // 列表继续请求的token
     pub next_continuation_token: Option<String>,
     // 文件列表
     pub contents: Option<Vec<ObjectInfo>>,
     // 分组列表
     pub common_prefixes: Option<Vec<CommonPrefixes>>,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// Object文件信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ObjectInfo {
     /// Object路径
     pub key: String,
     /// Object最后修改时间
-    pub last_modified: String,
+    pub last_modified: OssDateTime,
     /// ETag在每个Object生成时创建，用于标识一个Object的内容，ETag值可以用于检查Object内容是否发生变化，不建议使用ETag值作为Object内容的MD5校验数据完整性的依据。
     pub e_tag: String,
     #[serde(rename = "Type")]
@@ -36,14 +70,15 @@ pub struct ObjectInfo {
     pub size: u64,
     /// Object的存储类型
     pub storage_class: StorageClass,
-    /// Object的解冻状态
-    pub restore_info: Option<String>,
+    /// 归档、冷归档文件的解冻状态，未提交解冻请求时为None
+    #[serde(default)]
+    pub restore_info: Option<RestoreStatus>,
     /// Bucket拥有者信息
     pub owner: Option<Owner>,
 }
 
 /// 分组列表
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct CommonPrefixes {
     /// 前缀
@@ -54,6 +89,9 @@ pub struct CommonPrefixes {
 ///
 /// 默认获取前1000条文件信息
 ///
+/// 请求时携带 encoding-type=url ，OSS对文件路径、前缀等字段进行url编码，返回的结构体中已经解码；
+/// 包含控制字符等XML不支持的字符的文件路径也能正常解析
+///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/187544.html)
 pub struct ListObjects {
     req: OssRequest,
@@ -64,6 +102,8 @@ impl ListObjects {
         let mut req = OssRequest::new(oss, Method::GET);
         req.insert_query("list-type", "2");
         req.insert_query("max-keys", "1000");
+        req.insert_query("encoding-type", "url");
+        req.accept_gzip();
         ListObjects { req }
     }
     /// 对Object名字进行分组的字符。所有Object名字包含指定的前缀，第一次出现delimiter字符之间的Object作为一组元素（即CommonPrefixes）
@@ -111,20 +151,183 @@ impl ListObjects {
     /// 发送请求
     ///
     pub async fn send(self) -> Result<ObjectsList, Error> {
-        //构建http请求
+        //读取缓存
+        let cache = self.req.oss.cache.clone();
+        let cache_key = cache.as_ref().map(|_| {
+            let mut querys = self.req.querys.iter().collect::<Vec<_>>();
+            querys.sort();
+            CacheKey::new("list", &self.req.oss, format!("{:?}", querys))
+        });
+        if let (Some(cache), Some(key)) = (&cache, &cache_key) {
+            if let Some(mut object_list) = cache.get::<ObjectsList>(key) {
+                object_list.request_id = None;
+                return Ok(object_list);
+            }
+        }
+        //发送请求，边接收边解析
+        let response = self.response().await?;
+        let request_id = request_id(response.headers());
+        let mut body = response.into_body();
+        let mut parser = ListParser::default();
+        let mut contents = Vec::new();
+        loop {
+            while let Some(object) = parser.next_object()? {
+                contents.push(object);
+            }
+            match body.next().await {
+                Some(chunk) => parser.push(&chunk?),
+                None => break,
+            }
+        }
+        let mut object_list = parser.finish()?;
+        object_list.contents = (!contents.is_empty()).then_some(contents);
+        object_list.request_id = request_id;
+        if let (Some(cache), Some(key)) = (cache, cache_key) {
+            cache.insert(key, object_list.clone());
+        }
+        Ok(object_list)
+    }
+    //发送请求，返回成功的响应
+    async fn response(self) -> Result<Response<Body>, Error> {
         let response = self.req.send_to_oss()?.await?;
-        //拆解响应消息
-        let status_code = response.status();
-        match status_code {
-            code if code.is_success() => {
-                let response_bytes = to_bytes(response.into_body())
-                    .await
-                    .map_err(|_| Error::OssInvalidResponse(None))?;
-                let object_list: ObjectsList = serde_xml_rs::from_reader(&*response_bytes)
-                    .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
-                Ok(object_list)
+        match response.status() {
+            code if code.is_success() => Ok(response),
+            _ => Err(normal_error(response).await),
+        }
+    }
+}
+/// 分页列举时只返回文件信息，设置了delimiter时，分组信息将被忽略
+impl Paginator for ListObjects {
+    type Item = ObjectInfo;
+    fn next_page(self) -> PageFuture<Self> {
+        Box::pin(async move {
+            let mut next = ListObjects {
+                req: self.req.clone_without_body(),
+            };
+            let result = self.send().await?;
+            let next = result.next_continuation_token.map(|token| {
+                next.req.insert_query("continuation-token", token);
+                next
+            });
+            Ok((result.contents.unwrap_or_default(), next))
+        })
+    }
+    /// 文件信息在响应中解析完成后立即返回，不需要等待整页数据接收完毕
+    fn into_stream(self) -> BoxStream<'static, Result<ObjectInfo, Error>> {
+        stream::unfold(StreamState::Request(self), |mut state| async move {
+            loop {
+                state = match state {
+                    StreamState::Request(list) => {
+                        let next = ListObjects {
+                            req: list.req.clone_without_body(),
+                        };
+                        match list.response().await {
+                            Ok(response) => {
+                                StreamState::Reading(response.into_body(), Box::default(), next)
+                            }
+                            Err(e) => return Some((Err(e), StreamState::Done)),
+                        }
+                    }
+                    StreamState::Reading(mut body, mut parser, mut next) => {
+                        match parser.next_object() {
+                            Ok(Some(object)) => {
+                                return Some((Ok(object), StreamState::Reading(body, parser, next)))
+                            }
+                            Ok(None) => {}
+                            Err(e) => return Some((Err(e), StreamState::Done)),
+                        }
+                        match body.next().await {
+                            Some(Ok(chunk)) => {
+                                parser.push(&chunk);
+                                StreamState::Reading(body, parser, next)
+                            }
+                            Some(Err(e)) => return Some((Err(e.into()), StreamState::Done)),
+                            None => match parser.finish() {
+                                Ok(ObjectsList {
+                                    next_continuation_token: Some(token),
+                                    ..
+                                }) => {
+                                    next.req.insert_query("continuation-token", token);
+                                    StreamState::Request(next)
+                                }
+                                Ok(_) => StreamState::Done,
+                                Err(e) => return Some((Err(e), StreamState::Done)),
+                            },
+                        }
+                    }
+                    StreamState::Done => return None,
+                };
             }
-            _ => return Err(normal_error(response).await),
+        })
+        .boxed()
+    }
+}
+
+//流式列举的状态
+enum StreamState {
+    Request(ListObjects),
+    Reading(Body, Box<ListParser>, ListObjects),
+    Done,
+}
+
+//增量解析ListObjectsV2的响应，文件信息逐条返回，其余字段填充到ObjectsList
+//
+//请求携带了 encoding-type=url ，Key、Prefix、Delimiter、StartAfter、NextContinuationToken 需要解码
+#[derive(Default)]
+struct ListParser {
+    splitter: XmlSplitter,
+    list: ObjectsList,
+}
+impl ListParser {
+    fn push(&mut self, chunk: &[u8]) {
+        self.splitter.push(chunk);
+    }
+    //返回下一条已经完整接收的文件信息
+    fn next_object(&mut self) -> Result<Option<ObjectInfo>, Error> {
+        while let Some(element) = self.splitter.next_element()? {
+            let list = &mut self.list;
+            match element.name.as_str() {
+                "Contents" => {
+                    let mut object: ObjectInfo = element.parse()?;
+                    object.key = url_decode(&object.key);
+                    return Ok(Some(object));
+                }
+                "CommonPrefixes" => {
+                    let mut common_prefix: CommonPrefixes = element.parse()?;
+                    common_prefix.prefix = url_decode(&common_prefix.prefix);
+                    list.common_prefixes
+                        .get_or_insert_with(Vec::new)
+                        .push(common_prefix)
+                }
+                "Name" => list.name = element.text(),
+                "Prefix" => list.prefix = Some(url_decode(&element.text())),
+                "Delimiter" => list.delimiter = Some(url_decode(&element.text())),
+                "StartAfter" => list.start_after = Some(url_decode(&element.text())),
+                "ContinuationToken" => list.continuation_token = Some(element.text()),
+                "NextContinuationToken" => {
+                    list.next_continuation_token = Some(url_decode(&element.text()))
+                }
+                "MaxKeys" => list.max_keys = element.text().parse().unwrap_or_default(),
+                "KeyCount" => list.key_count = element.text().parse().unwrap_or_default(),
+                "IsTruncated" => list.is_truncated = element.text() == "true",
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+    //响应接收完毕，返回除文件信息以外的字段
+    fn finish(mut self) -> Result<ObjectsList, Error> {
+        if let Some(object) = self.next_object()? {
+            self.list.contents.get_or_insert_with(Vec::new).push(object);
+        }
+        match self.splitter.is_finished() {
+            true => Ok(self.list),
+            false => Err(Error::OssInvalidResponse(None)),
         }
     }
 }
+
+//解码 encoding-type=url 编码的字段
+fn url_decode(value: &str) -> String {
+    percent_decode_str(value).decode_utf8_lossy().into_owned()
+}
@@ -3,16 +3,31 @@
 #[doc(hidden)]
 pub use self::oss_bucket::OssBucket;
 pub use self::{
-    del_bucket::DelBucket, del_objects::DelObjects, get_bucket_info::GetBucketInfo,
-    get_bucket_stat::GetBucketStat, list_multipart_uploads::ListUploads, list_objects::ListObjects,
+    del_bucket::DelBucket,
+    del_objects::DelObjects,
+    download_prefix::DownloadPrefix,
+    get_bucket_info::GetBucketInfo,
+    get_bucket_location::GetBucketLocation,
+    get_bucket_stat::GetBucketStat,
+    list_multipart_uploads::ListUploads,
+    list_objects::{ListObjects, ObjectInfo},
+    presign_urls::{PresignUrls, PresignedUrl},
     put_bucket::PutBucket,
+    walk::{Walk, WalkEntry, WalkOrder},
+    watch::WatchEvent,
 };
 
 mod del_bucket;
 mod del_objects;
+mod dir;
+mod download_prefix;
 mod get_bucket_info;
+mod get_bucket_location;
 mod get_bucket_stat;
 mod list_multipart_uploads;
 mod list_objects;
 mod oss_bucket;
+mod presign_urls;
 mod put_bucket;
+mod walk;
+mod watch;
@@ -1,7 +1,16 @@
 use super::{
-    DelBucket, DelObjects, GetBucketInfo, GetBucketStat, ListObjects, ListUploads, PutBucket,
+    dir, watch::watch, DelBucket, DelObjects, DownloadPrefix, GetBucketInfo, GetBucketLocation,
+    GetBucketStat, ListObjects, ListUploads, PresignUrls, PutBucket, Walk, WalkEntry, WatchEvent,
 };
-use crate::{request::Oss, OssObject};
+use crate::{
+    client::{ACCELERATE_ENDPOINT, ACCELERATE_OVERSEAS_ENDPOINT},
+    common::RequestRecorder,
+    request::Oss,
+    signer, Error, OssObject,
+};
+use futures_util::stream::BoxStream;
+use hyper::Request;
+use std::{path::PathBuf, time::Duration};
 
 /// OSS存储空间，实现了新建存储空间、获取存储空间信息、文件列表等API
 #[derive(Debug, Clone)]
@@ -12,9 +21,98 @@ pub struct OssBucket {
 impl OssBucket {
     pub(crate) fn new(mut oss: Oss, bucket: impl ToString, endpoint: impl ToString) -> Self {
         oss.set_bucket(bucket);
-        oss.set_endpoint(endpoint);
+        let endpoint = endpoint.to_string();
+        if !endpoint.is_empty() {
+            oss.set_endpoint(endpoint);
+        }
         OssBucket { oss }
     }
+    /// 设置单次请求的超时时间，覆盖OssClient的配置，从发起连接开始到收到响应头为止
+    ///
+    /// 只影响通过此OssBucket发起的请求，超时后返回 Error::Timeout
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.oss.timeout = Some(timeout);
+        self
+    }
+    /// 设置读取响应body的超时时间，覆盖OssClient的配置，两次收到数据的间隔超过此时间时中断读取
+    pub fn set_read_timeout(mut self, timeout: Duration) -> Self {
+        self.oss.read_timeout = Some(timeout);
+        self
+    }
+    /// 记录通过此OssBucket发起的每个请求的Request ID，包括成功的请求
+    pub fn set_request_recorder(mut self, recorder: &RequestRecorder) -> Self {
+        self.oss.request_recorder = Some(recorder.clone());
+        self
+    }
+    /// 声明由请求者支付请求和流量费用，用于访问开启了请求者付费模式的存储空间
+    ///
+    /// 通过此OssBucket发起的所有请求都会携带 x-oss-request-payer: requester 请求头
+    pub fn set_request_payer(mut self) -> Self {
+        self.oss.request_payer = true;
+        self
+    }
+    /// 设置STS临时凭证的安全令牌，覆盖OssClient的凭证中的令牌
+    ///
+    /// 只影响通过此OssBucket发起的请求；AccessKey仍然使用OssClient的共享凭证，update_credentials 和凭证提供者的刷新对此OssBucket同样生效
+    pub fn set_security_token(mut self, security_token: impl ToString) -> Self {
+        self.oss.set_security_token(security_token);
+        self
+    }
+    /// 设置读写本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置上传时每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.oss.chunk_size = chunk_size.max(1024);
+        self
+    }
+    /// 设置是否启用https
+    ///
+    /// 默认与OssClient保持一致，endpoint中带有 http:// 或 https:// 时以endpoint中的协议为准
+    pub fn set_https(mut self, enable_https: bool) -> Self {
+        self.oss.set_https(enable_https);
+        self
+    }
+    /// 为自行构建的请求签名，用于调用本库尚未实现的API
+    ///
+    /// 请求地址需要指向此存储空间，文件路径和查询参数从请求地址中读取；签名时添加的Date、Authorization等请求头会写入请求中，
+    /// 覆盖已存在的同名请求头
+    pub fn sign_request<B>(&self, request: &mut Request<B>) -> Result<(), Error> {
+        signer::sign_request(self.oss.clone(), request)
+    }
+    /// 设置V4签名使用的地域，如 cn-hangzhou
+    ///
+    /// 默认从Endpoint推断；使用自定义域名、传输加速域名时需要设置
+    pub fn set_region(mut self, region: impl ToString) -> Self {
+        self.oss.region = Some(region.to_string().into());
+        self
+    }
+    /// 设置云盒ID，访问云盒上的存储空间时使用，设置后始终使用V4签名
+    ///
+    /// 默认从Endpoint推断，如 cb-xxx.cn-hangzhou.oss-cloudbox.aliyuncs.com 的云盒ID为 cb-xxx
+    pub fn set_cloudbox_id(mut self, cloudbox_id: impl ToString) -> Self {
+        self.oss.cloudbox_id = Some(cloudbox_id.to_string().into());
+        self
+    }
+    /// 使用全球传输加速Endpoint（oss-accelerate.aliyuncs.com）访问，需要存储空间已开启传输加速
+    ///
+    /// 适用于跨地域、跨洲的上传和下载；只影响此OssBucket及其创建的OssObject，原Endpoint的地域会保留用于V4签名
+    pub fn enable_transfer_acceleration(self) -> Self {
+        self.set_accelerate_endpoint(ACCELERATE_ENDPOINT)
+    }
+    /// 使用中国内地以外的传输加速Endpoint（oss-accelerate-overseas.aliyuncs.com）访问，需要存储空间已开启传输加速
+    pub fn enable_transfer_acceleration_overseas(self) -> Self {
+        self.set_accelerate_endpoint(ACCELERATE_OVERSEAS_ENDPOINT)
+    }
+    fn set_accelerate_endpoint(mut self, endpoint: &str) -> Self {
+        if self.oss.region.is_none() {
+            self.oss.region = self.oss.region().ok().map(|v| v.into());
+        }
+        self.oss.set_endpoint(endpoint);
+        self
+    }
     /// 设置自定义域名
     ///
     pub fn set_custom_domain(mut self, custom_domain: impl ToString, enable_https: bool) -> Self {
@@ -26,6 +124,14 @@ impl OssBucket {
     pub fn object(&self, object: impl ToString) -> OssObject {
         OssObject::new(self.oss.clone(), object)
     }
+    /// 使用已编码的文件路径初始化OssObject
+    ///
+    /// 文件路径会原样用于请求url，不再进行编码，签名时使用解码后的路径；仅在默认的编码方式不满足需求时使用
+    pub fn object_encoded(&self, encoded_object: impl ToString) -> OssObject {
+        let mut oss = self.oss.clone();
+        oss.set_encoded_object(encoded_object);
+        OssObject { oss }
+    }
     /// 创建存储空间
     pub fn put_bucket(&self) -> PutBucket {
         PutBucket::new(self.oss.clone())
@@ -42,6 +148,10 @@ impl OssBucket {
     pub fn get_bucket_info(&self) -> GetBucketInfo {
         GetBucketInfo::new(self.oss.clone())
     }
+    /// 查询存储空间所在的地域
+    pub fn get_bucket_location(&self) -> GetBucketLocation {
+        GetBucketLocation::new(self.oss.clone())
+    }
     /// 查询存储空间的存储容量和文件数量
     pub fn get_bucket_stat(&self) -> GetBucketStat {
         GetBucketStat::new(self.oss.clone())
@@ -54,4 +164,70 @@ impl OssBucket {
     pub fn multipart_list_uploads(&self) -> ListUploads {
         ListUploads::new(self.oss.clone())
     }
+    /// 批量生成文件的授权url
+    pub fn presign_urls(&self, keys: Vec<impl ToString>) -> PresignUrls {
+        PresignUrls::new(self.oss.clone(), keys)
+    }
+    /// 下载指定前缀下的所有文件到本地目录，保留目录层级
+    pub fn download_prefix(
+        &self,
+        prefix: impl ToString,
+        local_dir: impl Into<PathBuf>,
+    ) -> DownloadPrefix {
+        DownloadPrefix::new(self.oss.clone(), prefix, local_dir)
+    }
+    /// 创建目录，与OSS控制台的行为一致，上传一个以 / 结尾的空文件作为目录
+    ///
+    /// prefix不以 / 结尾时自动补全，如 images/2023 对应的文件为 images/2023/
+    pub async fn create_dir(&self, prefix: impl AsRef<str>) -> Result<(), Error> {
+        dir::create_dir(self.oss.clone(), prefix.as_ref()).await
+    }
+    /// 删除目录，包括目录下的全部文件、子目录以及目录自身
+    ///
+    /// 返回删除的文件数量；删除过程中出错时，已经删除的文件不会恢复
+    pub async fn delete_dir(&self, prefix: impl AsRef<str>) -> Result<usize, Error> {
+        dir::delete_dir(self.oss.clone(), prefix.as_ref()).await
+    }
+    /// 列举目录下的直接子目录和文件，按路径排序，不包含目录自身的占位文件
+    ///
+    /// prefix为空字符串时列举存储空间的根目录
+    ///
+    /// ```
+    /// for entry in bucket.list_dir("images/").await? {
+    ///     match entry {
+    ///         WalkEntry::Directory { prefix, .. } => println!("目录 {}", prefix),
+    ///         WalkEntry::File { object, .. } => println!("文件 {} {}", object.key, object.size),
+    ///     }
+    /// }
+    /// ```
+    pub async fn list_dir(&self, prefix: impl AsRef<str>) -> Result<Vec<WalkEntry>, Error> {
+        dir::list_dir(self.oss.clone(), prefix.as_ref()).await
+    }
+    /// 按 / 分隔的目录结构遍历指定前缀下的文件和目录
+    pub fn walk(&self, prefix: impl ToString) -> Walk {
+        Walk::new(self.oss.clone(), prefix)
+    }
+    /// 监听指定前缀下文件的变更
+    ///
+    /// 每隔interval列举一次文件，与上一次的结果对比，按ETag和大小判断文件是否发生变化；首次列举的结果作为基准，不产生事件
+    ///
+    /// 列举失败时返回错误，继续轮询下一个Stream项会在下一个周期重新列举；文件数量较多时，每次轮询都会产生多次ListObjects请求，请合理设置interval
+    ///
+    /// ```
+    /// let mut events = bucket.watch("inbox/", Duration::from_secs(30));
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         WatchEvent::Created(file) => println!("新增 {}", file.key),
+    ///         WatchEvent::Modified(file) => println!("修改 {}", file.key),
+    ///         WatchEvent::Deleted(key) => println!("删除 {}", key),
+    ///     }
+    /// }
+    /// ```
+    pub fn watch(
+        &self,
+        prefix: impl ToString,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<WatchEvent, Error>> {
+        watch(self.oss.clone(), prefix, interval)
+    }
 }
@@ -0,0 +1,105 @@
+use crate::{
+    common::Expires,
+    request::{Oss, OssRequest},
+    Error,
+};
+use chrono::{DateTime, Duration, Utc};
+use hyper::Method;
+
+/// 批量生成的授权url
+#[derive(Debug, Clone)]
+pub struct PresignedUrl {
+    /// 文件路径
+    pub key: String,
+    /// 授权url
+    pub url: String,
+    /// 过期时间
+    pub expires: DateTime<Utc>,
+}
+
+/// 批量生成文件的授权url
+///
+/// 所有文件共用同一份请求参数、访问凭证和过期时间，只在本地计算签名，不发起网络请求，适合一次生成大量url
+///
+/// ```
+/// use chrono::Duration;
+///
+/// let urls = bucket
+///     .presign_urls(vec!["img/1.png", "img/2.png"])
+///     .set_process("image/resize,w_100")
+///     .urls(Duration::hours(1))?;
+/// for url in urls {
+///     println!("{} {} {}", url.key, url.url, url.expires);
+/// }
+/// ```
+pub struct PresignUrls {
+    req: OssRequest,
+    keys: Vec<String>,
+}
+impl PresignUrls {
+    pub(super) fn new(oss: Oss, keys: Vec<impl ToString>) -> Self {
+        PresignUrls {
+            req: OssRequest::new(oss, Method::GET),
+            keys: keys.into_iter().map(|v| v.to_string()).collect(),
+        }
+    }
+    /// 设置url对应的请求方法
+    ///
+    /// 默认为GET，使用url发起请求时，必须使用相同的方法
+    pub fn set_method(mut self, method: Method) -> Self {
+        self.req.method = method;
+        self
+    }
+    /// 设置图片处理、视频截帧等数据处理参数，即 x-oss-process
+    pub fn set_process(mut self, process: impl ToString) -> Self {
+        self.req.insert_query("x-oss-process", process);
+        self
+    }
+    /// 设置其他查询参数
+    ///
+    /// OSS要求参与签名的参数（如 response-* 、x-oss-process 等）会自动加入签名，其他参数仅附加在url中
+    pub fn set_query(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.req.insert_query(key, value);
+        self
+    }
+    /// 设置自定义域名
+    ///
+    /// 自定义域名（CNAME）已经绑定到存储空间，生成的url中不再包含bucket
+    pub fn set_custom_domain(mut self, custom_domain: impl ToString, enable_https: bool) -> Self {
+        self.req.oss.set_custom_domain(custom_domain);
+        self.req.set_https(enable_https);
+        self
+    }
+    /// 设置生成的url是否使用https
+    ///
+    /// 默认与OssBucket保持一致
+    pub fn set_https(mut self, enable_https: bool) -> Self {
+        self.req.set_https(enable_https);
+        self
+    }
+    /// 生成url，结果与传入的文件顺序一致
+    ///
+    /// expires可以是过期时间（DateTime<Utc>），也可以是从当前时间开始的有效时长（Duration），所有url的过期时间相同
+    pub fn urls(self, expires: impl Into<Expires>) -> Result<Vec<PresignedUrl>, Error> {
+        let expires = match expires.into() {
+            Expires::At(time) => time,
+            Expires::In(duration) => {
+                self.req.oss.now() + Duration::seconds(duration.as_secs() as i64)
+            }
+        };
+        let timestamp = expires.timestamp();
+        let mut urls = Vec::with_capacity(self.keys.len());
+        for key in self.keys {
+            //每个文件只替换路径，其他参数沿用同一个请求
+            let mut req = self.req.clone_without_body();
+            req.oss.set_object(&key);
+            req.query_sign(timestamp)?;
+            urls.push(PresignedUrl {
+                url: req.uri(),
+                key,
+                expires,
+            });
+        }
+        Ok(urls)
+    }
+}
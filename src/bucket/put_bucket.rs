@@ -52,7 +52,7 @@ impl PutBucket {
         let body_str = format!(
             "<?xml version=\"1.0\" encoding=\"UTF-8\"?><CreateBucketConfiguration>{}{}</CreateBucketConfiguration>",
             storage_class.to_string(),
-            self.data_redundancy_type.map_or(String::new(),|v|format!("<DataRedundancyType>{}</DataRedundancyType>",v.to_string()))
+            self.data_redundancy_type.as_ref().map_or(String::new(),|v|format!("<DataRedundancyType>{}</DataRedundancyType>",v.to_string()))
         );
         self.storage_class = Some(storage_class);
         self.req.set_body(body_str.into());
@@ -62,7 +62,7 @@ impl PutBucket {
     pub fn set_redundancy_type(mut self, redundancy_type: DataRedundancyType) -> Self {
         let body_str = format!(
             "<?xml version=\"1.0\" encoding=\"UTF-8\"?><CreateBucketConfiguration>{}{}</CreateBucketConfiguration>",
-            self.storage_class.map(|v|format!("<StorageClass>{}</StorageClass>",v.to_string())).unwrap_or_else(||String::new()),
+            self.storage_class.as_ref().map(|v|format!("<StorageClass>{}</StorageClass>",v.to_string())).unwrap_or_else(||String::new()),
             redundancy_type.to_string()
         );
         self.req.set_body(body_str.into());
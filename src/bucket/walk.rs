@@ -0,0 +1,195 @@
+use super::{list_objects::ObjectInfo, ListObjects};
+use crate::{request::Oss, Error};
+use futures_util::{stream::BoxStream, StreamExt};
+use serde_derive::Serialize;
+use std::collections::VecDeque;
+
+/// 目录树中的节点
+#[derive(Debug, Clone, Serialize)]
+pub enum WalkEntry {
+    /// 目录，即以 / 结尾的前缀
+    Directory {
+        /// 完整的前缀，如 images/2023/
+        prefix: String,
+        /// 相对于起始前缀的层级，起始前缀下的直接子节点为1
+        depth: usize,
+    },
+    /// 文件
+    File {
+        /// 文件信息
+        object: ObjectInfo,
+        /// 相对于起始前缀的层级，起始前缀下的直接子节点为1
+        depth: usize,
+    },
+}
+impl WalkEntry {
+    /// 目录的前缀或文件的路径
+    pub fn key(&self) -> &str {
+        match self {
+            WalkEntry::Directory { prefix, .. } => prefix,
+            WalkEntry::File { object, .. } => &object.key,
+        }
+    }
+    /// 相对于起始前缀的层级
+    pub fn depth(&self) -> usize {
+        match self {
+            WalkEntry::Directory { depth, .. } | WalkEntry::File { depth, .. } => *depth,
+        }
+    }
+}
+
+/// 遍历顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalkOrder {
+    /// 广度优先，逐层返回
+    #[default]
+    BreadthFirst,
+    /// 深度优先，目录之后紧接着返回目录下的全部节点
+    DepthFirst,
+}
+
+/// 按 / 分隔的目录结构遍历指定前缀下的文件和目录
+///
+/// 每个目录使用 delimiter=/ 列举一次，同一目录下的节点按路径排序返回；目录下以 / 结尾的占位文件不作为文件返回
+///
+/// ```
+/// let mut entries = bucket
+///     .walk("images/")
+///     .set_order(WalkOrder::DepthFirst)
+///     .set_max_depth(2)
+///     .into_stream();
+/// while let Some(entry) = entries.next().await {
+///     match entry? {
+///         WalkEntry::Directory { prefix, depth } => println!("{}{}", "  ".repeat(depth), prefix),
+///         WalkEntry::File { object, depth } => println!("{}{}", "  ".repeat(depth), object.key),
+///     }
+/// }
+/// ```
+pub struct Walk {
+    oss: Oss,
+    prefix: String,
+    order: WalkOrder,
+    max_depth: Option<usize>,
+}
+impl Walk {
+    pub(super) fn new(oss: Oss, prefix: impl ToString) -> Self {
+        Walk {
+            oss,
+            prefix: prefix.to_string(),
+            order: WalkOrder::default(),
+            max_depth: None,
+        }
+    }
+    /// 设置遍历顺序
+    ///
+    /// 默认值：WalkOrder::BreadthFirst
+    pub fn set_order(mut self, order: WalkOrder) -> Self {
+        self.order = order;
+        self
+    }
+    /// 设置遍历的最大层级，超过此层级的目录会返回，但不再列举其中的内容
+    ///
+    /// 默认不限制
+    pub fn set_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth.max(1));
+        self
+    }
+    /// 开始遍历
+    ///
+    /// 某个目录列举失败时返回错误，跳过此目录继续遍历其他目录
+    pub fn into_stream(self) -> BoxStream<'static, Result<WalkEntry, Error>> {
+        let state = WalkState {
+            expand: Some((self.prefix.clone(), 0)),
+            walk: self,
+            pending: VecDeque::new(),
+        };
+        futures_util::stream::unfold(state, |mut state| async move {
+            //列举上一次返回的目录
+            if let Some((prefix, depth)) = state.expand.take() {
+                match list_children(&state.walk.oss, &prefix).await {
+                    Ok((dirs, files)) => state.push(merge(dirs, files, depth + 1)),
+                    Err(e) => return Some((Err(e), state)),
+                }
+            }
+            let entry = state.pending.pop_front()?;
+            if let WalkEntry::Directory { prefix, depth } = &entry {
+                if state.walk.max_depth.is_none_or(|max| *depth < max) {
+                    state.expand = Some((prefix.clone(), *depth));
+                }
+            }
+            Some((Ok(entry), state))
+        })
+        .boxed()
+    }
+}
+
+struct WalkState {
+    walk: Walk,
+    pending: VecDeque<WalkEntry>,
+    expand: Option<(String, usize)>,
+}
+impl WalkState {
+    //深度优先时子节点排在队列最前，广度优先时排在最后
+    fn push(&mut self, entries: Vec<WalkEntry>) {
+        match self.walk.order {
+            WalkOrder::BreadthFirst => self.pending.extend(entries),
+            WalkOrder::DepthFirst => {
+                for entry in entries.into_iter().rev() {
+                    self.pending.push_front(entry);
+                }
+            }
+        }
+    }
+}
+
+//列举目录下的全部子目录和文件，不包含目录自身的占位文件
+pub(super) async fn list_children(
+    oss: &Oss,
+    prefix: &str,
+) -> Result<(Vec<String>, Vec<ObjectInfo>), Error> {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    let mut token = None;
+    loop {
+        let mut list = ListObjects::new(oss.clone())
+            .set_prefix(prefix)
+            .set_delimiter("/");
+        if let Some(token) = token {
+            list = list.set_continuation_token(token);
+        }
+        let result = list.send().await?;
+        dirs.extend(
+            result
+                .common_prefixes
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.prefix),
+        );
+        files.extend(
+            result
+                .contents
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|v| v.key != prefix),
+        );
+        token = result.next_continuation_token;
+        if token.is_none() {
+            return Ok((dirs, files));
+        }
+    }
+}
+
+//目录和文件按路径合并排序
+pub(super) fn merge(dirs: Vec<String>, files: Vec<ObjectInfo>, depth: usize) -> Vec<WalkEntry> {
+    let mut entries = dirs
+        .into_iter()
+        .map(|prefix| WalkEntry::Directory { prefix, depth })
+        .chain(
+            files
+                .into_iter()
+                .map(|object| WalkEntry::File { object, depth }),
+        )
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.key().cmp(b.key()));
+    entries
+}
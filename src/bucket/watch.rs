@@ -0,0 +1,91 @@
+use super::{list_objects::ObjectInfo, ListObjects};
+use crate::{common::Paginator, request::Oss, Error};
+use futures_util::{stream::BoxStream, StreamExt};
+use serde_derive::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+/// 文件变更事件
+#[derive(Debug, Clone, Serialize)]
+pub enum WatchEvent {
+    /// 新增的文件
+    Created(ObjectInfo),
+    /// ETag或大小发生变化的文件
+    Modified(ObjectInfo),
+    /// 已删除的文件路径
+    Deleted(String),
+}
+
+struct WatchState {
+    oss: Oss,
+    prefix: String,
+    interval: Duration,
+    started: bool,
+    snapshot: Option<HashMap<String, ObjectInfo>>,
+    events: VecDeque<WatchEvent>,
+}
+
+//定时列举文件，对比前后两次的结果生成变更事件
+pub(super) fn watch(
+    mut oss: Oss,
+    prefix: impl ToString,
+    interval: Duration,
+) -> BoxStream<'static, Result<WatchEvent, Error>> {
+    //列举结果不能使用缓存
+    oss.cache = None;
+    let state = WatchState {
+        oss,
+        prefix: prefix.to_string(),
+        interval,
+        started: false,
+        snapshot: None,
+        events: VecDeque::new(),
+    };
+    futures_util::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                return Some((Ok(event), state));
+            }
+            if state.started {
+                tokio::time::sleep(state.interval).await;
+            }
+            state.started = true;
+            let files = match ListObjects::new(state.oss.clone())
+                .set_prefix(&state.prefix)
+                .all()
+                .await
+            {
+                Ok(files) => files,
+                Err(e) => return Some((Err(e), state)),
+            };
+            let current = files
+                .into_iter()
+                .map(|file| (file.key.clone(), file))
+                .collect::<HashMap<_, _>>();
+            //首次列举成功的结果作为基准，不生成事件
+            if let Some(mut previous) = state.snapshot.take() {
+                let mut keys = current.keys().collect::<Vec<_>>();
+                keys.sort();
+                for key in keys {
+                    let file = &current[key];
+                    match previous.remove(key) {
+                        None => state.events.push_back(WatchEvent::Created(file.clone())),
+                        Some(old) if old.e_tag != file.e_tag || old.size != file.size => {
+                            state.events.push_back(WatchEvent::Modified(file.clone()))
+                        }
+                        _ => {}
+                    }
+                }
+                let mut deleted = previous.into_keys().collect::<Vec<_>>();
+                deleted.sort();
+                state
+                    .events
+                    .extend(deleted.into_iter().map(WatchEvent::Deleted));
+            }
+            state.snapshot = Some(current);
+        }
+    })
+    .boxed()
+}
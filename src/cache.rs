@@ -0,0 +1,86 @@
+//! 元信息缓存
+//!
+//! 缓存HeadObject、GetObjectMeta、ListObjects的结果，通过同一个OssClient发起写请求时，自动清除相关的缓存
+
+use crate::request::Oss;
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// 缓存数量超过此值时，清理已过期的缓存
+const PURGE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    kind: &'static str,
+    bucket: String,
+    object: String,
+    query: String,
+}
+impl CacheKey {
+    pub fn new(kind: &'static str, oss: &Oss, query: impl ToString) -> Self {
+        CacheKey {
+            kind,
+            bucket: oss.bucket.clone().unwrap_or_default().to_string(),
+            object: oss.object.clone().unwrap_or_default().to_string(),
+            query: query.to_string(),
+        }
+    }
+}
+
+type CacheEntry = (Instant, Arc<dyn Any + Send + Sync>);
+
+#[derive(Debug)]
+pub(crate) struct MetaCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+impl MetaCache {
+    pub fn new(ttl: Duration) -> Self {
+        MetaCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+    //读取缓存，已过期的缓存会被删除
+    pub fn get<T: Clone + 'static>(&self, key: &CacheKey) -> Option<T> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((created, value)) if created.elapsed() < self.ttl => {
+                value.downcast_ref::<T>().cloned()
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+    //写入缓存
+    pub fn insert<T: Send + Sync + 'static>(&self, key: CacheKey, value: T) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= PURGE_THRESHOLD {
+                let ttl = self.ttl;
+                entries.retain(|_, (created, _)| created.elapsed() < ttl);
+            }
+            entries.insert(key, (Instant::now(), Arc::new(value)));
+        }
+    }
+    //清除写请求影响的缓存：文件的元信息，以及所在存储空间的所有列表；未指定文件时清除存储空间的所有缓存
+    pub fn invalidate(&self, oss: &Oss) {
+        let bucket = oss.bucket.as_deref().unwrap_or_default();
+        let object = oss.object.as_deref();
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|key, _| {
+                key.bucket != bucket
+                    || match object {
+                        Some(object) => key.kind != "list" && key.object != object,
+                        None => false,
+                    }
+            });
+        }
+    }
+}
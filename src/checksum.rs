@@ -0,0 +1,156 @@
+//! 数据校验
+//!
+//! 提供与OSS算法一致的CRC64、MD5、SHA-256流式计算，可用于对比本地文件与OSS文件是否一致
+//!
+//! ```
+//! use aliyun_oss_rs::checksum;
+//!
+//! let local_crc64 = checksum::file_crc64("/data/rust.mp4").await?;
+//! let headers = object.head_object().send().await?;
+//! let same = headers.get("x-oss-hash-crc64ecma") == Some(&local_crc64.to_string());
+//! ```
+
+use crate::{backend, Error};
+use base64::{engine::general_purpose, Engine};
+use std::path::Path;
+use tokio::{fs::File, io::AsyncReadExt};
+
+// CRC-64/ECMA-182 查找表，与OSS返回的 x-oss-hash-crc64ecma 算法一致
+const CRC64_TABLE: [u64; 256] = crc64_table();
+const fn crc64_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xC96C_5795_D787_0F42
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// 流式计算CRC64，结果与OSS返回的 x-oss-hash-crc64ecma 一致
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc64 {
+    value: u64,
+}
+impl Crc64 {
+    pub fn new() -> Self {
+        Crc64 { value: 0 }
+    }
+    /// 追加数据
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = !self.value;
+        for byte in data {
+            crc = CRC64_TABLE[((crc ^ *byte as u64) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.value = !crc;
+    }
+    /// 获取当前已追加数据的CRC64
+    pub fn finalize(&self) -> u64 {
+        self.value
+    }
+}
+
+/// 流式计算MD5
+#[derive(Debug, Clone, Default)]
+pub struct Md5 {
+    hasher: backend::Md5,
+}
+impl Md5 {
+    pub fn new() -> Self {
+        Md5 {
+            hasher: backend::Md5::new(),
+        }
+    }
+    /// 追加数据
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+    /// 获取当前已追加数据的MD5
+    pub fn finalize(&self) -> [u8; 16] {
+        self.hasher.finish()
+    }
+    /// 获取当前已追加数据的MD5，并进行base64编码，结果可以直接作为 Content-MD5 使用
+    pub fn finalize_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.finalize())
+    }
+}
+
+/// 流式计算SHA-256
+#[derive(Clone)]
+pub struct Sha256 {
+    context: backend::Sha256,
+}
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            context: backend::Sha256::new(),
+        }
+    }
+    /// 追加数据
+    pub fn update(&mut self, data: &[u8]) {
+        self.context.update(data);
+    }
+    /// 获取当前已追加数据的SHA-256
+    pub fn finalize(&self) -> [u8; 32] {
+        self.context.finish()
+    }
+    /// 获取当前已追加数据的SHA-256，并转换为小写十六进制字符串
+    pub fn finalize_hex(&self) -> String {
+        self.finalize()
+            .iter()
+            .map(|v| format!("{:02x}", v))
+            .collect()
+    }
+}
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 计算本地文件的CRC64，结果与OSS返回的 x-oss-hash-crc64ecma 一致
+pub async fn file_crc64(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let mut crc64 = Crc64::new();
+    read_file(path, |data| crc64.update(data)).await?;
+    Ok(crc64.finalize())
+}
+
+/// 计算本地文件的MD5，并进行base64编码，结果与上传时的 Content-MD5 一致
+pub async fn file_md5(path: impl AsRef<Path>) -> Result<String, Error> {
+    let mut md5 = Md5::new();
+    read_file(path, |data| md5.update(data)).await?;
+    Ok(md5.finalize_base64())
+}
+
+/// 计算本地文件的SHA-256，结果为小写十六进制字符串
+pub async fn file_sha256(path: impl AsRef<Path>) -> Result<String, Error> {
+    let mut sha256 = Sha256::new();
+    read_file(path, |data| sha256.update(data)).await?;
+    Ok(sha256.finalize_hex())
+}
+
+//分块读取文件内容
+pub(crate) async fn read_file(
+    path: impl AsRef<Path>,
+    mut f: impl FnMut(&[u8]),
+) -> Result<(), Error> {
+    let mut file = File::open(path).await?;
+    let mut buf = vec![0; 131072];
+    loop {
+        let len = file.read(&mut buf).await?;
+        if len == 0 {
+            return Ok(());
+        }
+        f(&buf[..len]);
+    }
+}
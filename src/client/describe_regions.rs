@@ -4,11 +4,11 @@ use crate::{
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回内容
 /// Region基础信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct RegionInfo {
     /// 地域ID
@@ -0,0 +1,129 @@
+use super::{Region, ACCELERATE_ENDPOINT, ACCELERATE_OVERSEAS_ENDPOINT};
+use std::fmt;
+
+/// OSS访问域名
+///
+/// 根据地域生成外网、内网、传输加速、双栈（IPv4/IPv6）的Endpoint，OssClient::bucket 同时接受 Endpoint 和字符串
+///
+/// 通过地域生成的Endpoint同时携带地域信息，使用V4签名时无需再设置地域
+///
+/// ```
+/// use aliyun_oss_rs::client::{Endpoint, Region};
+///
+/// let endpoint = if cfg!(debug_assertions) {
+///     Endpoint::public(Region::CnHangzhou)
+/// } else {
+///     Endpoint::internal(Region::CnHangzhou)
+/// };
+/// let bucket = client.bucket("for-rs-test", endpoint);
+/// let bucket = client.bucket("for-rs-test", "oss-cn-hangzhou.aliyuncs.com");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    host: String,
+    region: Option<Region>,
+}
+impl Endpoint {
+    /// 外网Endpoint，如 oss-cn-hangzhou.aliyuncs.com
+    pub fn public(region: Region) -> Self {
+        Endpoint {
+            host: region.endpoint(),
+            region: Some(region),
+        }
+    }
+    /// 内网Endpoint，如 oss-cn-hangzhou-internal.aliyuncs.com ，仅同地域的ECS等云产品可以访问
+    pub fn internal(region: Region) -> Self {
+        Endpoint {
+            host: region.internal_endpoint(),
+            region: Some(region),
+        }
+    }
+    /// 全球传输加速Endpoint，需要存储空间已开启传输加速；region为存储空间所在的地域
+    pub fn accelerate(region: Region) -> Self {
+        Endpoint {
+            host: ACCELERATE_ENDPOINT.to_owned(),
+            region: Some(region),
+        }
+    }
+    /// 中国内地以外的传输加速Endpoint，需要存储空间已开启传输加速；region为存储空间所在的地域
+    pub fn accelerate_overseas(region: Region) -> Self {
+        Endpoint {
+            host: ACCELERATE_OVERSEAS_ENDPOINT.to_owned(),
+            region: Some(region),
+        }
+    }
+    /// 双栈Endpoint，同时支持IPv4和IPv6访问，如 cn-hangzhou.oss.aliyuncs.com
+    pub fn dualstack(region: Region) -> Self {
+        Endpoint {
+            host: format!("{}.oss.aliyuncs.com", region.id()),
+            region: Some(region),
+        }
+    }
+    /// 云盒Endpoint，如 cb-xxx.cn-hangzhou.oss-cloudbox.aliyuncs.com ，签名时使用云盒ID
+    pub fn cloudbox(cloudbox_id: impl ToString, region: Region) -> Self {
+        Endpoint {
+            host: format!(
+                "{}.{}.oss-cloudbox.aliyuncs.com",
+                cloudbox_id.to_string(),
+                region.id()
+            ),
+            region: Some(region),
+        }
+    }
+    /// 云盒管控Endpoint，用于创建、删除存储空间等管控操作，如 cb-xxx.cn-hangzhou.oss-cloudbox-control.aliyuncs.com
+    pub fn cloudbox_control(cloudbox_id: impl ToString, region: Region) -> Self {
+        Endpoint {
+            host: format!(
+                "{}.{}.oss-cloudbox-control.aliyuncs.com",
+                cloudbox_id.to_string(),
+                region.id()
+            ),
+            region: Some(region),
+        }
+    }
+    /// 自定义的Endpoint，可以带有协议，如 http://192.168.1.10:9000
+    pub fn custom(host: impl ToString) -> Self {
+        Endpoint {
+            host: host.to_string(),
+            region: None,
+        }
+    }
+    /// 域名
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+    /// 地域，自定义的Endpoint为None
+    pub fn region(&self) -> Option<&Region> {
+        self.region.as_ref()
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.host)
+    }
+}
+
+impl From<Region> for Endpoint {
+    fn from(region: Region) -> Self {
+        Endpoint::public(region)
+    }
+}
+
+impl From<&str> for Endpoint {
+    fn from(host: &str) -> Self {
+        Endpoint::custom(host)
+    }
+}
+
+impl From<String> for Endpoint {
+    fn from(host: String) -> Self {
+        Endpoint::custom(host)
+    }
+}
+
+impl From<&String> for Endpoint {
+    fn from(host: &String) -> Self {
+        Endpoint::custom(host)
+    }
+}
@@ -1,15 +1,15 @@
 use crate::{
-    common::StorageClass,
+    common::{request_id, OssDateTime, Owner, PageFuture, Paginator, StorageClass},
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 //返回值
 /// Bucket基础信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct BucketBase {
     /// Bucket名称
@@ -24,6 +24,15 @@ pub struct BucketBase {
     pub intranet_endpoint: String,
     /// 存储类型    
     pub storage_class: StorageClass,
+    /// 创建时间
+    #[serde(default)]
+    pub creation_date: OssDateTime,
+    /// 所属资源组ID
+    #[serde(default)]
+    pub resource_group_id: Option<String>,
+    /// 备注
+    #[serde(default)]
+    pub comment: Option<String>,
 }
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -35,6 +44,12 @@ pub(crate) struct Buckets {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub(crate) struct ListAllMyBucketsResult {
+    pub owner: Option<Owner>,
+    pub prefix: Option<String>,
+    pub marker: Option<String>,
+    pub max_keys: Option<u32>,
+    #[serde(default)]
+    pub is_truncated: bool,
     /// 如果一次查询未穷尽所有存储空间，next_marker则可用于下一次继续查询
     pub next_marker: Option<String>,
     /// 存储空间列表
@@ -42,13 +57,26 @@ pub(crate) struct ListAllMyBucketsResult {
 }
 
 /// 查询存储空间列表的结果集合
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListAllMyBuckets {
+    /// 存储空间拥有者
+    pub owner: Option<Owner>,
+    /// 本次查询使用的前缀，未设置时为空
+    pub prefix: Option<String>,
+    /// 本次查询使用的起始位置，未设置时为空
+    pub marker: Option<String>,
+    /// 本次查询返回的最大个数，未设置时为空
+    pub max_keys: Option<u32>,
+    /// 是否还有未返回的存储空间
+    pub is_truncated: bool,
     /// 如果一次查询未穷尽所有存储空间，next_marker则可用于下一次继续查询
     pub next_marker: Option<String>,
     /// 存储空间列表
     pub buckets: Option<Vec<BucketBase>>,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// 查询存储空间列表
@@ -67,9 +95,9 @@ pub struct ListBuckets {
 
 impl ListBuckets {
     pub(super) fn new(oss: Oss) -> Self {
-        ListBuckets {
-            req: OssRequest::new(oss, Method::GET),
-        }
+        let mut req = OssRequest::new(oss, Method::GET);
+        req.accept_gzip();
+        ListBuckets { req }
     }
 
     /// 限定返回的Bucket名称必须以prefix作为前缀。如果不设定，则不过滤前缀信息。
@@ -112,17 +140,40 @@ impl ListBuckets {
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
                 let result: ListAllMyBucketsResult = serde_xml_rs::from_reader(&*response_bytes)
                     .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
                 Ok(ListAllMyBuckets {
+                    owner: result.owner,
+                    prefix: result.prefix.filter(|v| !v.is_empty()),
+                    marker: result.marker.filter(|v| !v.is_empty()),
+                    max_keys: result.max_keys,
+                    is_truncated: result.is_truncated,
                     next_marker: result.next_marker,
                     buckets: result.buckets.bucket,
+                    request_id,
                 })
             }
             _ => Err(normal_error(response).await),
         }
     }
 }
+impl Paginator for ListBuckets {
+    type Item = BucketBase;
+    fn next_page(self) -> PageFuture<Self> {
+        Box::pin(async move {
+            let mut next = ListBuckets {
+                req: self.req.clone_without_body(),
+            };
+            let result = self.send().await?;
+            let next = result.next_marker.map(|marker| {
+                next.req.insert_query("marker", marker);
+                next
+            });
+            Ok((result.buckets.unwrap_or_default(), next))
+        })
+    }
+}
@@ -1,9 +1,15 @@
 //! 包括AccessKey和EndPoint信息的基础服务
 
-pub use self::describe_regions::DescribeRegions;
+pub use self::describe_regions::{DescribeRegions, RegionInfo};
+pub use self::endpoint::Endpoint;
 pub use self::list_buckets::ListBuckets;
 pub use self::oss_client::OssClient;
+pub use self::oss_client_builder::OssClientBuilder;
+pub use self::region::{Region, RegionCache, ACCELERATE_ENDPOINT, ACCELERATE_OVERSEAS_ENDPOINT};
 
 mod describe_regions;
+mod endpoint;
 mod list_buckets;
 mod oss_client;
+mod oss_client_builder;
+mod region;
@@ -1,5 +1,18 @@
-use super::{DescribeRegions, ListBuckets};
-use crate::{request::Oss, OssBucket};
+use super::{DescribeRegions, Endpoint, ListBuckets, OssClientBuilder, Region, RegionCache};
+use crate::{
+    cache::MetaCache,
+    credentials::{self, CredentialsCache, CredentialsProvider, EcsRamRoleProvider, Profile},
+    request::{Credentials, Oss},
+    signer::{self, HmacSigner, Signer},
+    Error, OssBucket,
+};
+use hyper::Request;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, MutexGuard},
+    time::Duration,
+};
 
 /// OSS容器入口，实现了查询OSS开服地域信息和查询存储空间列表两个API
 #[derive(Debug, Clone)]
@@ -18,21 +31,247 @@ impl OssClient {
             oss: Oss::new(ak_id, ak_secret),
         }
     }
+    /// 使用自定义的签名器初始化OssClient，AccessKey Secret不需要保存在内存中，详见 [signer](crate::signer) 模块
+    pub fn from_signer(signer: impl Signer + 'static) -> Self {
+        OssClient {
+            oss: Oss::with_signer(Arc::new(signer)),
+        }
+    }
+    /// 使用STS临时凭证初始化OssClient
+    ///
+    /// - ak_id ： STS临时凭证的AccessKey ID
+    /// - ak_secret：STS临时凭证的AccessKey Secret
+    /// - security_token：STS临时凭证的安全令牌
+    ///
+    /// 安全令牌会参与所有请求的签名，包括生成的授权url；临时凭证过期前通过 update_credentials 更新
+    pub fn with_security_token(ak_id: &str, ak_secret: &str, security_token: &str) -> Self {
+        OssClient::new(ak_id, ak_secret).set_security_token(security_token)
+    }
+    /// 初始化匿名访问的OssClient，请求不携带签名，只能访问公共读或公共读写的存储空间
+    ///
+    /// 生成的文件url不包含签名参数，适用于只需要下载公开文件、不应持有访问凭证的工具
+    pub fn anonymous() -> Self {
+        let mut oss = Oss::new("", "");
+        oss.anonymous = true;
+        OssClient { oss }
+    }
+    /// 通过凭证提供者初始化OssClient，发送请求前自动获取和刷新访问凭证，详见 [credentials](crate::credentials) 模块
+    pub fn from_provider(provider: impl CredentialsProvider + 'static) -> Self {
+        let mut oss = Oss::new("", "");
+        oss.credentials_provider = Some(Arc::new(CredentialsCache::new(provider)));
+        OssClient { oss }
+    }
+    /// 从环境变量中读取访问凭证并初始化OssClient，缺少AccessKey时返回错误
+    ///
+    /// 访问凭证的读取顺序见 Credentials::from_env ；设置了 OSS_ENDPOINT 时使用其作为默认Endpoint，
+    /// 否则设置了 OSS_REGION 或 ALIBABA_CLOUD_REGION_ID 时使用对应地域的Endpoint
+    pub fn from_env() -> Result<Self, Error> {
+        let credentials = credentials::Credentials::from_env().ok_or_else(|| {
+            Error::CredentialsError(
+                "缺少环境变量OSS_ACCESS_KEY_ID、OSS_ACCESS_KEY_SECRET".to_owned(),
+            )
+        })?;
+        let mut client = OssClient::from_credentials(credentials);
+        let var = |key: &str| env::var(key).ok().filter(|v| !v.is_empty());
+        if let Some(endpoint) = var("OSS_ENDPOINT") {
+            client.oss.set_endpoint(endpoint);
+        } else if let Some(region) = var("OSS_REGION").or_else(|| var("ALIBABA_CLOUD_REGION_ID")) {
+            client
+                .oss
+                .set_endpoint(format!("oss-{}.aliyuncs.com", region));
+        }
+        Ok(client)
+    }
+    /// 读取aliyun命令行工具的配置文件并初始化OssClient，name为None时使用当前配置，详见 Profile
+    ///
+    /// 配置中设置了地域时，使用对应地域的Endpoint作为默认Endpoint；EcsRamRole模式通过 EcsRamRoleProvider 获取凭证
+    pub fn from_profile(name: Option<&str>) -> Result<Self, Error> {
+        let profile = Profile::load(name)?;
+        let mut client = match &profile.credentials {
+            Some(credentials) => OssClient::from_credentials(credentials.clone()),
+            None => {
+                let mut provider = EcsRamRoleProvider::new();
+                if let Some(ram_role_name) = &profile.ram_role_name {
+                    provider = provider.set_role_name(ram_role_name);
+                }
+                OssClient::from_provider(provider)
+            }
+        };
+        if let Some(region) = profile.region_id() {
+            client
+                .oss
+                .set_endpoint(format!("oss-{}.aliyuncs.com", region));
+        }
+        Ok(client)
+    }
+    fn from_credentials(credentials: credentials::Credentials) -> Self {
+        let client = OssClient::new(&credentials.access_key_id, &credentials.access_key_secret);
+        match credentials.security_token {
+            Some(security_token) => client.set_security_token(security_token),
+            None => client,
+        }
+    }
+    /// 初始化OssClient构建器，用于设置超时、代理、重试等配置
+    ///
+    /// - ak_id ： 阿里云AccessKey ID
+    /// - ak_secret：阿里云AccessKey Secret
+    ///
+    pub fn builder(ak_id: &str, ak_secret: &str) -> OssClientBuilder {
+        OssClientBuilder::new(ak_id, ak_secret)
+    }
+    /// 更新访问凭证，用于定期轮换AccessKey或刷新STS临时凭证
+    ///
+    /// 此OssClient及其创建的OssBucket、OssObject共享同一份凭证，更新后的请求立即使用新的凭证，无需重新创建
+    ///
+    /// 使用凭证提供者时，更新的凭证会在下次刷新时被提供者返回的凭证覆盖
+    ///
+    /// - ak_id ： 阿里云AccessKey ID
+    /// - ak_secret：阿里云AccessKey Secret
+    /// - security_token：STS临时凭证的安全令牌，使用长期AccessKey时传入None
+    ///
+    /// ```
+    /// let client = OssClient::new("STS AccessKey ID", "STS AccessKey Secret");
+    /// let bucket = client.bucket("for-rs-test", "oss-cn-zhangjiakou.aliyuncs.com");
+    /// // 临时凭证过期前
+    /// client.update_credentials("New AccessKey ID", "New AccessKey Secret", Some("New SecurityToken"));
+    /// // bucket 使用新的凭证发起请求
+    /// bucket.list_objects().send().await?;
+    /// ```
+    pub fn update_credentials(&self, ak_id: &str, ak_secret: &str, security_token: Option<&str>) {
+        self.oss.update_credentials(Credentials {
+            signer: Arc::new(HmacSigner::new(ak_id, ak_secret)),
+            security_token: security_token.map(|v| v.to_owned().into()),
+        });
+    }
+    /// 更换签名器，同 update_credentials ，此OssClient及其创建的OssBucket、OssObject立即生效
+    ///
+    /// - security_token：STS临时凭证的安全令牌，使用长期AccessKey时传入None
+    pub fn update_signer(&self, signer: impl Signer + 'static, security_token: Option<&str>) {
+        self.oss.update_credentials(Credentials {
+            signer: Arc::new(signer),
+            security_token: security_token.map(|v| v.to_owned().into()),
+        });
+    }
+    /// 设置STS临时凭证的安全令牌
+    ///
+    /// 令牌写入共享的访问凭证，此OssClient及其创建的OssBucket、OssObject立即生效；使用凭证提供者时，刷新后的凭证会替换此令牌
+    pub fn set_security_token(self, security_token: impl ToString) -> Self {
+        self.oss.update_security_token(security_token);
+        self
+    }
+    /// 立即通过凭证提供者刷新访问凭证，未使用凭证提供者时直接返回
+    ///
+    /// 生成授权url、sign_request 等同步签名的方法不会自动刷新凭证，可以在调用前使用此方法
+    pub async fn refresh_credentials(&self) -> Result<(), Error> {
+        match &self.oss.credentials_provider {
+            Some(credentials_provider) => credentials_provider.refresh(&self.oss, true).await,
+            None => Ok(()),
+        }
+    }
     /// 禁用https
     pub fn disable_https(mut self) -> Self {
         self.oss.set_https(false);
         self
     }
+    /// 设置是否启用https，默认启用
+    ///
+    /// 初始化OssBucket时，如果endpoint中带有 http:// 或 https:// ，则以endpoint中的协议为准
+    pub fn set_https(mut self, enable_https: bool) -> Self {
+        self.oss.set_https(enable_https);
+        self
+    }
+    /// 启用元信息缓存
+    ///
+    /// 缓存HeadObject、GetObjectMeta、ListObjects的结果，在有效期内重复查询时直接返回缓存，不再请求OSS
+    ///
+    /// 通过此OssClient（及其创建的OssBucket、OssObject）发起的写请求会自动清除相关的缓存，其他客户端的修改需等待缓存过期
+    ///
+    /// 等待解冻、断点续传下载、TransferManager 等内部的元信息查询不使用缓存
+    ///
+    /// - ttl：缓存有效期
+    pub fn enable_cache(mut self, ttl: Duration) -> Self {
+        self.oss.cache = Some(Arc::new(MetaCache::new(ttl)));
+        self
+    }
+    /// 清空DNS缓存，Endpoint的解析结果变化后使用，仅在通过 OssClientBuilder::enable_dns_cache 开启缓存时有效
+    ///
+    /// 已经建立的连接不受影响
+    pub fn clear_dns_cache(&self) {
+        if let Some(dns_cache) = &self.oss.dns_cache {
+            dns_cache.clear();
+        }
+    }
+    /// 开启严格的参数校验
+    ///
+    /// 发送请求前校验存储空间名称、文件路径、自定义元信息，不符合要求时返回本地错误，详见 [validate](crate::validate) 模块
+    pub fn enable_strict_validation(mut self) -> Self {
+        self.oss.strict = true;
+        self
+    }
     /// 初始化OssBucket
-    pub fn bucket(&self, bucket: &str, endpoint: &str) -> OssBucket {
-        OssBucket::new(self.oss.clone(), bucket, endpoint)
+    ///
+    /// endpoint可以带有协议，如 http://192.168.1.10:9000 ，用于仅支持http的内网地址或测试服务
+    ///
+    /// endpoint为空字符串时，使用OssClientBuilder中设置的默认Endpoint
+    ///
+    /// endpoint也可以是通过地域生成的 Endpoint ，此时V4签名使用其中的地域
+    pub fn bucket(&self, bucket: &str, endpoint: impl Into<Endpoint>) -> OssBucket {
+        let endpoint = endpoint.into();
+        let mut bucket = OssBucket::new(self.oss.clone(), bucket, endpoint.host());
+        if let Some(region) = endpoint.region() {
+            bucket.oss.region = Some(region.id().to_owned().into());
+        }
+        bucket
+    }
+    /// 初始化OssBucket，自动查询存储空间所在的地域，使用对应地域的外网Endpoint
+    ///
+    /// 每个存储空间只查询一次，结果缓存在此OssClient（及其克隆）中；查询通过OssClientBuilder中设置的默认Endpoint发送
+    ///
+    /// ```
+    /// let bucket = client.bucket_auto("for-rs-test").await?;
+    /// ```
+    pub async fn bucket_auto(&self, bucket: &str) -> Result<OssBucket, Error> {
+        let cached = self.lock_bucket_regions().get(bucket).cloned();
+        let region = match cached {
+            Some(region) => region,
+            None => {
+                let mut lookup = self.bucket(bucket, "");
+                //默认Endpoint位于杭州，V4签名时无法从中推断地域
+                if lookup.oss.region.is_none() && lookup.oss.endpoint == "oss.aliyuncs.com" {
+                    lookup.oss.region = Some("cn-hangzhou".into());
+                }
+                let region: Region = lookup.get_bucket_location().send().await?.parse()?;
+                self.lock_bucket_regions()
+                    .insert(bucket.to_owned(), region.clone());
+                region
+            }
+        };
+        Ok(self.bucket(bucket, Endpoint::public(region)))
+    }
+    fn lock_bucket_regions(&self) -> MutexGuard<'_, HashMap<String, Region>> {
+        match self.oss.bucket_regions.lock() {
+            Ok(bucket_regions) => bucket_regions,
+            Err(poisoned) => poisoned.into_inner(),
+        }
     }
     /// 查询所有地域的Endpoint信息
     pub fn describe_regions(&self) -> DescribeRegions {
         DescribeRegions::new(self.oss.clone())
     }
+    /// 初始化带缓存的地域信息查询
+    ///
+    /// - ttl：缓存有效期
+    pub fn region_cache(&self, ttl: Duration) -> RegionCache {
+        RegionCache::new(self.oss.clone(), ttl)
+    }
     /// 查询已创建的所有存储空间
     pub fn list_buckets(&self) -> ListBuckets {
         ListBuckets::new(self.oss.clone())
     }
+    /// 为自行构建的、不属于任何存储空间的请求签名，用于调用本库尚未实现的API；存储空间的请求请使用 OssBucket::sign_request
+    ///
+    /// 签名时添加的Date、Authorization等请求头会写入请求中，覆盖已存在的同名请求头
+    pub fn sign_request<B>(&self, request: &mut Request<B>) -> Result<(), Error> {
+        signer::sign_request(self.oss.clone(), request)
+    }
 }
@@ -0,0 +1,228 @@
+use super::{describe_regions::RegionInfo, DescribeRegions};
+use crate::{request::Oss, Error};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// 传输加速Endpoint，全球通用
+pub const ACCELERATE_ENDPOINT: &str = "oss-accelerate.aliyuncs.com";
+
+/// 传输加速Endpoint，仅中国内地以外的地域可用
+pub const ACCELERATE_OVERSEAS_ENDPOINT: &str = "oss-accelerate-overseas.aliyuncs.com";
+
+/// OSS地域
+///
+/// 用于生成地域对应的Endpoint，未列出的地域可以使用 Region::Custom
+///
+/// ```
+/// let bucket = client.bucket("for-rs-test", &Region::CnZhangjiakou.endpoint());
+/// let region: Region = "oss-cn-hangzhou".parse()?;
+/// assert_eq!(region.internal_endpoint(), "oss-cn-hangzhou-internal.aliyuncs.com");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// 华东1（杭州）
+    CnHangzhou,
+    /// 华东2（上海）
+    CnShanghai,
+    /// 华东5（南京-本地地域）
+    CnNanjing,
+    /// 华东6（福州-本地地域）
+    CnFuzhou,
+    /// 华北1（青岛）
+    CnQingdao,
+    /// 华北2（北京）
+    CnBeijing,
+    /// 华北3（张家口）
+    CnZhangjiakou,
+    /// 华北5（呼和浩特）
+    CnHuhehaote,
+    /// 华北6（乌兰察布）
+    CnWulanchabu,
+    /// 华南1（深圳）
+    CnShenzhen,
+    /// 华南2（河源）
+    CnHeyuan,
+    /// 华南3（广州）
+    CnGuangzhou,
+    /// 西南1（成都）
+    CnChengdu,
+    /// 中国香港
+    CnHongkong,
+    /// 日本（东京）
+    ApNortheast1,
+    /// 韩国（首尔）
+    ApNortheast2,
+    /// 新加坡
+    ApSoutheast1,
+    /// 马来西亚（吉隆坡）
+    ApSoutheast3,
+    /// 印度尼西亚（雅加达）
+    ApSoutheast5,
+    /// 菲律宾（马尼拉）
+    ApSoutheast6,
+    /// 泰国（曼谷）
+    ApSoutheast7,
+    /// 德国（法兰克福）
+    EuCentral1,
+    /// 英国（伦敦）
+    EuWest1,
+    /// 美国（硅谷）
+    UsWest1,
+    /// 美国（弗吉尼亚）
+    UsEast1,
+    /// 阿联酋（迪拜）
+    MeEast1,
+    /// 其他地域，传入不带 oss- 前缀的地域ID，如 cn-wuhan-lr
+    Custom(String),
+}
+
+const KNOWN_REGIONS: [(Region, &str); 26] = [
+    (Region::CnHangzhou, "cn-hangzhou"),
+    (Region::CnShanghai, "cn-shanghai"),
+    (Region::CnNanjing, "cn-nanjing"),
+    (Region::CnFuzhou, "cn-fuzhou"),
+    (Region::CnQingdao, "cn-qingdao"),
+    (Region::CnBeijing, "cn-beijing"),
+    (Region::CnZhangjiakou, "cn-zhangjiakou"),
+    (Region::CnHuhehaote, "cn-huhehaote"),
+    (Region::CnWulanchabu, "cn-wulanchabu"),
+    (Region::CnShenzhen, "cn-shenzhen"),
+    (Region::CnHeyuan, "cn-heyuan"),
+    (Region::CnGuangzhou, "cn-guangzhou"),
+    (Region::CnChengdu, "cn-chengdu"),
+    (Region::CnHongkong, "cn-hongkong"),
+    (Region::ApNortheast1, "ap-northeast-1"),
+    (Region::ApNortheast2, "ap-northeast-2"),
+    (Region::ApSoutheast1, "ap-southeast-1"),
+    (Region::ApSoutheast3, "ap-southeast-3"),
+    (Region::ApSoutheast5, "ap-southeast-5"),
+    (Region::ApSoutheast6, "ap-southeast-6"),
+    (Region::ApSoutheast7, "ap-southeast-7"),
+    (Region::EuCentral1, "eu-central-1"),
+    (Region::EuWest1, "eu-west-1"),
+    (Region::UsWest1, "us-west-1"),
+    (Region::UsEast1, "us-east-1"),
+    (Region::MeEast1, "me-east-1"),
+];
+
+impl Region {
+    /// 地域ID，如 cn-hangzhou
+    pub fn id(&self) -> &str {
+        match self {
+            Region::Custom(id) => id.strip_prefix("oss-").unwrap_or(id),
+            region => KNOWN_REGIONS
+                .iter()
+                .find(|(known, _)| known == region)
+                .map(|(_, id)| *id)
+                .unwrap_or_default(),
+        }
+    }
+    /// OSS专用的地域ID，如 oss-cn-hangzhou ，与DescribeRegions返回的Region字段一致
+    pub fn oss_region(&self) -> String {
+        format!("oss-{}", self.id())
+    }
+    /// 外网Endpoint，如 oss-cn-hangzhou.aliyuncs.com
+    pub fn endpoint(&self) -> String {
+        format!("oss-{}.aliyuncs.com", self.id())
+    }
+    /// 内网Endpoint，如 oss-cn-hangzhou-internal.aliyuncs.com ，仅同地域的ECS等云产品可以访问
+    pub fn internal_endpoint(&self) -> String {
+        format!("oss-{}-internal.aliyuncs.com", self.id())
+    }
+    /// 传输加速Endpoint，需要存储空间已开启传输加速
+    pub fn accelerate_endpoint(&self) -> &'static str {
+        ACCELERATE_ENDPOINT
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Region {
+    type Err = Error;
+    /// 解析地域ID，支持 cn-hangzhou 和 oss-cn-hangzhou 两种格式，未知的地域解析为 Region::Custom
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s.trim().strip_prefix("oss-").unwrap_or(s.trim());
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(Error::InvalidCharacter);
+        }
+        Ok(KNOWN_REGIONS
+            .iter()
+            .find(|(_, known)| *known == id)
+            .map(|(region, _)| region.clone())
+            .unwrap_or_else(|| Region::Custom(id.to_owned())))
+    }
+}
+
+/// 带缓存的地域信息查询
+///
+/// 地域信息很少变化，首次查询后在有效期内直接返回缓存的结果，多个任务同时查询时只会请求一次OSS
+///
+/// ```
+/// let regions = client.region_cache(Duration::from_secs(86400));
+/// let hangzhou = regions.region("oss-cn-hangzhou").await?;
+/// ```
+pub struct RegionCache {
+    oss: Oss,
+    ttl: Duration,
+    endpoint: Option<String>,
+    regions: Mutex<Option<(Instant, Arc<Vec<RegionInfo>>)>>,
+}
+
+impl RegionCache {
+    pub(super) fn new(oss: Oss, ttl: Duration) -> Self {
+        RegionCache {
+            oss,
+            ttl,
+            endpoint: None,
+            regions: Mutex::new(None),
+        }
+    }
+    /// 指定从哪个EndPoint发起查询，同 DescribeRegions::set_endpoint
+    pub fn set_endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+    /// 查询所有地域的Endpoint信息，缓存有效时直接返回缓存
+    pub async fn regions(&self) -> Result<Arc<Vec<RegionInfo>>, Error> {
+        let mut regions = self.regions.lock().await;
+        if let Some((created, cached)) = &*regions {
+            if created.elapsed() < self.ttl {
+                return Ok(cached.clone());
+            }
+        }
+        let mut describe = DescribeRegions::new(self.oss.clone());
+        if let Some(endpoint) = &self.endpoint {
+            describe = describe.set_endpoint(endpoint);
+        }
+        let fetched = Arc::new(describe.send().await?);
+        *regions = Some((Instant::now(), fetched.clone()));
+        Ok(fetched)
+    }
+    /// 查询单个地域的Endpoint信息，支持 cn-hangzhou 和 oss-cn-hangzhou 两种格式，地域不存在时返回None
+    pub async fn region(&self, region: impl ToString) -> Result<Option<RegionInfo>, Error> {
+        let region = region.to_string();
+        let region = region.strip_prefix("oss-").unwrap_or(&region);
+        let regions = self.regions().await?;
+        Ok(regions
+            .iter()
+            .find(|info| info.region.strip_prefix("oss-").unwrap_or(&info.region) == region)
+            .cloned())
+    }
+    /// 清除缓存，下次查询时重新请求OSS
+    pub async fn clear(&self) {
+        *self.regions.lock().await = None;
+    }
+}
@@ -1,9 +1,26 @@
 //! 公共数据定义
 //!
 //!
+use crate::{request::OssRequest, validate, Error};
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Utc};
+use futures_util::{
+    future::{self, BoxFuture, Either},
+    pin_mut,
+    stream::{self, BoxStream},
+    Future, FutureExt, StreamExt, TryStreamExt,
+};
+use hyper::{HeaderMap, Method};
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserializer, Serializer};
 use serde_derive::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
 
 // -------------------------- 公共方法 --------------------------
 //编码查询参数值
@@ -12,6 +29,36 @@ pub(crate) fn url_encode(input: &str) -> String {
     utf8_percent_encode(input, URL_ENCODE).to_string()
 }
 
+//编码url中的文件路径和查询参数，只保留 A-Z a-z 0-9 - _ . ~
+const URI_ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+pub(crate) fn uri_encode(input: &str) -> String {
+    utf8_percent_encode(input, URI_ENCODE).to_string()
+}
+
+//编码文件路径，按 / 拆分后分别编码每一段；. 和 .. 也需要编码，避免被当作相对路径处理
+pub(crate) fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| match segment {
+            "." => "%2E".to_owned(),
+            ".." => "%2E%2E".to_owned(),
+            segment => uri_encode(segment),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+//响应头中的 x-oss-request-id
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-oss-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_owned())
+}
+
 //检测metadata中key是否合规
 pub(crate) fn invalid_metadata_key(input: &str) -> bool {
     for c in input.chars() {
@@ -22,23 +69,168 @@ pub(crate) fn invalid_metadata_key(input: &str) -> bool {
     false
 }
 
+//转义JSON字符串
+pub(crate) fn json_escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    for c in input.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+//检测标签是否合规
+//
+//最多10个标签，key长度1-128个字符，value长度不超过256个字符，只允许英文字母、数字、空格和 + - = . _ : /
+pub(crate) fn check_tags<'a>(
+    tags: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> Result<(), Error> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || " +-=._:/".contains(c);
+    let mut keys = HashSet::new();
+    for (key, value) in tags {
+        if keys.len() >= 10 {
+            return Err(Error::InvalidTag("标签数量不能超过10个".to_owned()));
+        }
+        let key_len = key.chars().count();
+        if key_len == 0 || key_len > 128 {
+            return Err(Error::InvalidTag(format!(
+                "key长度需在1-128个字符之间：{}",
+                key
+            )));
+        }
+        if value.chars().count() > 256 {
+            return Err(Error::InvalidTag(format!(
+                "value长度不能超过256个字符：{}",
+                key
+            )));
+        }
+        if !key.chars().all(valid_char) || !value.chars().all(valid_char) {
+            return Err(Error::InvalidTag(format!("存在不支持的字符：{}", key)));
+        }
+        if !keys.insert(key) {
+            return Err(Error::InvalidTag(format!("key重复：{}", key)));
+        }
+    }
+    Ok(())
+}
+
+//等待任务完成，取消令牌被触发时立即返回 Error::Cancelled ，未完成的任务会被丢弃
+pub(crate) async fn cancellable<F: Future>(
+    token: Option<&CancellationToken>,
+    future: F,
+) -> Result<F::Output, Error> {
+    let token = match token {
+        Some(token) => token,
+        None => return Ok(future.await),
+    };
+    if token.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
+    pin_mut!(future);
+    let cancelled = token.cancelled();
+    pin_mut!(cancelled);
+    match future::select(future, cancelled).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Error::Cancelled),
+    }
+}
+
 // -------------------------- 公共数据 --------------------------
 
+/// OSS返回的时间
+///
+/// 同时保留OSS返回的原始字符串和解析后的UTC时间，支持ISO8601格式（如 2023-07-01T08:00:00.000Z ）和HTTP日期格式（如 Sat, 01 Jul 2023 08:00:00 GMT ）
+///
+/// ```
+/// let files = bucket.list_objects().send().await?;
+/// for file in files.contents.unwrap_or_default() {
+///     println!("{} {:?}", file.last_modified, file.last_modified.datetime());
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OssDateTime {
+    raw: String,
+    datetime: Option<DateTime<Utc>>,
+}
+impl OssDateTime {
+    /// 解析时间字符串，格式不支持时 datetime() 返回None
+    pub fn parse(raw: impl ToString) -> Self {
+        let raw = raw.to_string();
+        let datetime = DateTime::parse_from_rfc3339(&raw)
+            .or_else(|_| DateTime::parse_from_rfc2822(&raw))
+            .ok()
+            .map(|v| v.with_timezone(&Utc));
+        OssDateTime { raw, datetime }
+    }
+    /// OSS返回的原始字符串
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+    /// 解析后的UTC时间
+    pub fn datetime(&self) -> Option<DateTime<Utc>> {
+        self.datetime
+    }
+    /// 解析后的unix时间戳，单位秒
+    pub fn timestamp(&self) -> Option<i64> {
+        self.datetime.map(|v| v.timestamp())
+    }
+}
+impl fmt::Display for OssDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+impl<'de> serde::Deserialize<'de> for OssDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer).map(OssDateTime::parse)
+    }
+}
+impl serde::Serialize for OssDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+//按照字符串序列化、反序列化，OSS新增的取值反序列化为 Other ，避免解析失败
+macro_rules! string_enum_serde {
+    ($($name:ident),*) => {
+        $(
+            impl serde::Serialize for $name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&self.to_string())
+                }
+            }
+            impl<'de> serde::Deserialize<'de> for $name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <String as serde::Deserialize>::deserialize(deserializer)
+                        .map(|value| $name::from(value.as_str()))
+                }
+            }
+        )*
+    };
+}
+string_enum_serde!(Acl, StorageClass, DataRedundancyType, RestoreTier);
+
 /// 访问权限ACL
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Acl {
     /// 仅用于文件ACL，代表文件ACL继承存储空间ACL
-    #[serde(rename = "default")]
     Default,
     /// 私有，读写请求全部需要经过授权
-    #[serde(rename = "private")]
     Private,
     /// 公共读，存储空间中的文件可以被匿名读取，但无法写入文件
-    #[serde(rename = "public-read")]
     PublicRead,
     /// 公共读写，存储空间中的文件可以被匿名读取和写入
-    #[serde(rename = "public-read-write")]
     PublicReadWrite,
+    /// 其他取值，用于兼容OSS后续新增的类型
+    Other(String),
 }
 impl fmt::Display for Acl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
@@ -47,13 +239,25 @@ impl fmt::Display for Acl {
             Acl::Private => "private",
             Acl::PublicRead => "public-read",
             Acl::PublicReadWrite => "public-read-write",
+            Acl::Other(value) => value,
         };
         write!(f, "{}", value)
     }
 }
+impl From<&str> for Acl {
+    fn from(value: &str) -> Self {
+        match value {
+            "default" => Acl::Default,
+            "private" => Acl::Private,
+            "public-read" => Acl::PublicRead,
+            "public-read-write" => Acl::PublicReadWrite,
+            other => Acl::Other(other.to_owned()),
+        }
+    }
+}
 
 ///存储类型
-#[derive(Debug, Clone, Serialize, Deserialize, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StorageClass {
     /// 标准存储
     Standard,
@@ -65,6 +269,8 @@ pub enum StorageClass {
     ColdArchive,
     /// 深度冷归档存储
     DeepColdArchive,
+    /// 其他取值，用于兼容OSS后续新增的类型
+    Other(String),
 }
 impl fmt::Display for StorageClass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -74,29 +280,54 @@ impl fmt::Display for StorageClass {
             StorageClass::Archive => f.write_str("Archive"),
             StorageClass::ColdArchive => f.write_str("ColdArchive"),
             StorageClass::DeepColdArchive => f.write_str("DeepColdArchive"),
+            StorageClass::Other(value) => f.write_str(value),
+        }
+    }
+}
+impl From<&str> for StorageClass {
+    fn from(value: &str) -> Self {
+        match value {
+            "Standard" => StorageClass::Standard,
+            "IA" => StorageClass::IA,
+            "Archive" => StorageClass::Archive,
+            "ColdArchive" => StorageClass::ColdArchive,
+            "DeepColdArchive" => StorageClass::DeepColdArchive,
+            other => StorageClass::Other(other.to_owned()),
         }
     }
 }
 
 ///数据容灾类型
-#[derive(Debug, Clone, Serialize, Deserialize, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DataRedundancyType {
     ///本地冗余LRS将您的数据冗余存储在同一个可用区的不同存储设备上，可支持两个存储设备并发损坏时，仍维持数据不丢失，可正常访问。
     LRS,
     ///同城冗余ZRS采用多可用区（AZ）内的数据冗余存储机制，将用户的数据冗余存储在同一地域（Region）的多个可用区。当某个可用区不可用时，仍然能够保障数据的正常访问。
     ZRS,
+    /// 其他取值，用于兼容OSS后续新增的类型
+    Other(String),
 }
 impl fmt::Display for DataRedundancyType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DataRedundancyType::LRS => f.write_str("LRS"),
             DataRedundancyType::ZRS => f.write_str("ZRS"),
+            DataRedundancyType::Other(value) => f.write_str(value),
+        }
+    }
+}
+impl From<&str> for DataRedundancyType {
+    fn from(value: &str) -> Self {
+        match value {
+            "LRS" => DataRedundancyType::LRS,
+            "ZRS" => DataRedundancyType::ZRS,
+            other => DataRedundancyType::Other(other.to_owned()),
         }
     }
 }
 
 ///解冻优先级
-#[derive(Debug, Clone, Serialize, Deserialize, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RestoreTier {
     /// 高优先级
     Expedited,
@@ -104,6 +335,8 @@ pub enum RestoreTier {
     Standard,
     /// 批量
     Bulk,
+    /// 其他取值，用于兼容OSS后续新增的类型
+    Other(String),
 }
 impl fmt::Display for RestoreTier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -111,10 +344,83 @@ impl fmt::Display for RestoreTier {
             RestoreTier::Standard => f.write_str("Standard"),
             RestoreTier::Expedited => f.write_str("Expedited"),
             RestoreTier::Bulk => f.write_str("Bulk"),
+            RestoreTier::Other(value) => f.write_str(value),
+        }
+    }
+}
+impl From<&str> for RestoreTier {
+    fn from(value: &str) -> Self {
+        match value {
+            "Expedited" => RestoreTier::Expedited,
+            "Standard" => RestoreTier::Standard,
+            "Bulk" => RestoreTier::Bulk,
+            other => RestoreTier::Other(other.to_owned()),
         }
     }
 }
 
+/// 归档文件的解冻状态
+///
+/// 由响应头 x-oss-restore 或列举文件时返回的 RestoreInfo 解析而来
+///
+/// ```
+/// let status = RestoreStatus::from(r#"ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT""#);
+/// assert!(status.is_restored());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum RestoreStatus {
+    /// 未提交解冻请求，或解冻状态已过期
+    NotRestored,
+    /// 正在解冻中
+    Ongoing,
+    /// 已完成解冻，expiry_date 之前文件可以读取
+    Restored { expiry_date: OssDateTime },
+}
+impl RestoreStatus {
+    pub(crate) fn from_header(value: Option<&str>) -> Self {
+        match value {
+            None => RestoreStatus::NotRestored,
+            Some(value) if value.contains("ongoing-request=\"true\"") => RestoreStatus::Ongoing,
+            Some(value) => {
+                let expiry_date = value
+                    .split_once("expiry-date=\"")
+                    .and_then(|(_, rest)| rest.split_once('"'))
+                    .map(|(date, _)| OssDateTime::parse(date))
+                    .unwrap_or_default();
+                RestoreStatus::Restored { expiry_date }
+            }
+        }
+    }
+    /// 文件是否已经可以读取
+    pub fn is_restored(&self) -> bool {
+        matches!(self, RestoreStatus::Restored { .. })
+    }
+    /// 是否正在解冻中
+    pub fn is_ongoing(&self) -> bool {
+        matches!(self, RestoreStatus::Ongoing)
+    }
+    /// 解冻状态的过期时间，仅已完成解冻时存在
+    pub fn expiry_date(&self) -> Option<&OssDateTime> {
+        match self {
+            RestoreStatus::Restored { expiry_date } => Some(expiry_date),
+            _ => None,
+        }
+    }
+}
+impl From<&str> for RestoreStatus {
+    /// 解析 x-oss-restore 格式的字符串，如 ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT"
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        RestoreStatus::from_header((!value.is_empty()).then_some(value))
+    }
+}
+impl<'de> serde::Deserialize<'de> for RestoreStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer)
+            .map(|value| RestoreStatus::from(value.as_str()))
+    }
+}
+
 /// http头，cache_control
 #[derive(Debug, Clone)]
 pub enum CacheControl {
@@ -163,7 +469,7 @@ impl fmt::Display for ContentDisposition {
 }
 
 /// 所有者信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Owner {
     /// 用户ID
     #[serde(rename = "ID")]
@@ -172,3 +478,760 @@ pub struct Owner {
     #[serde(rename = "DisplayName")]
     pub display_name: String,
 }
+
+/// 下载文件保存到磁盘时，本地文件的写入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownloadMode {
+    /// 创建新文件，本地文件已存在时返回错误
+    #[default]
+    CreateNew,
+    /// 覆盖已存在的本地文件
+    Overwrite,
+    /// 断点续传，本地文件已存在时，只下载剩余的部分并追加到文件末尾
+    ///
+    /// 本地文件大小与OSS文件一致时直接返回成功；需要确保OSS文件在两次下载之间没有发生变化
+    Resume,
+    /// 先写入同目录下的临时文件，下载完成后重命名为目标文件，覆盖已存在的本地文件
+    ///
+    /// 下载过程中不会出现内容不完整的目标文件，下载失败时删除临时文件
+    Atomic,
+}
+
+/// 自定义元信息
+///
+/// key统一转换为小写，并去除 x-oss-meta- 前缀；发送请求前会校验key、value是否合规，以及总大小是否超过8KB
+///
+/// ```
+/// let mut metadata = ObjectMetadata::new();
+/// metadata.insert("Author", "ethan");
+/// metadata.insert("x-oss-meta-project", "rust");
+/// object.put_object().set_metadata(metadata).send_file("/data/rust.png").await?;
+///
+/// let metadata = object.head_object().send_metadata().await?;
+/// assert_eq!(metadata.get("author"), Some("ethan"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ObjectMetadata {
+    entries: BTreeMap<String, String>,
+}
+impl ObjectMetadata {
+    pub fn new() -> Self {
+        ObjectMetadata::default()
+    }
+    /// 从HeadObject等请求返回的响应头中提取自定义元信息
+    pub fn from_headers<'a>(headers: impl IntoIterator<Item = (&'a String, &'a String)>) -> Self {
+        headers
+            .into_iter()
+            .filter(|(key, _)| key.to_lowercase().starts_with(META_PREFIX))
+            .collect()
+    }
+    /// 添加元信息，返回key原有的值
+    pub fn insert(&mut self, key: impl ToString, value: impl ToString) -> Option<String> {
+        self.entries
+            .insert(normalize_metadata_key(&key.to_string()), value.to_string())
+    }
+    /// 获取元信息
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .get(&normalize_metadata_key(key))
+            .map(|v| v.as_str())
+    }
+    /// 删除元信息，返回key原有的值
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.entries.remove(&normalize_metadata_key(key))
+    }
+    /// 元信息的数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// 遍历元信息，key不包含 x-oss-meta- 前缀
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    /// 校验元信息是否合规
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut size = 0;
+        for (key, value) in &self.entries {
+            validate::metadata(key, value)?;
+            size += META_PREFIX.len() + key.len() + value.len();
+        }
+        if size > validate::MAX_METADATA_SIZE {
+            return Err(Error::InvalidMetadata(format!(
+                "元信息总大小不能超过{}字节",
+                validate::MAX_METADATA_SIZE
+            )));
+        }
+        Ok(())
+    }
+    //校验元信息，并写入请求头
+    pub(crate) fn apply(&self, req: &mut OssRequest) -> Result<(), Error> {
+        self.validate()?;
+        for (key, value) in &self.entries {
+            req.insert_header(format!("{}{}", META_PREFIX, key), value);
+        }
+        Ok(())
+    }
+}
+impl<K: ToString, V: ToString> FromIterator<(K, V)> for ObjectMetadata {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut metadata = ObjectMetadata::new();
+        for (key, value) in iter {
+            metadata.insert(key, value);
+        }
+        metadata
+    }
+}
+impl IntoIterator for ObjectMetadata {
+    type Item = (String, String);
+    type IntoIter = std::collections::btree_map::IntoIter<String, String>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+const META_PREFIX: &str = "x-oss-meta-";
+
+//规范化元信息的key：去除首尾空白和 x-oss-meta- 前缀，并转换为小写
+fn normalize_metadata_key(key: &str) -> String {
+    let key = key.trim().to_lowercase();
+    match key.strip_prefix(META_PREFIX) {
+        Some(key) => key.to_owned(),
+        None => key,
+    }
+}
+
+/// 授权url的过期时间
+///
+/// 可以由 DateTime<Utc> 、 std::time::Duration 、 chrono::Duration 转换而来
+///
+/// 不接受 NaiveDateTime ，无法确定其时区；本地时间请先通过 Local.from_local_datetime 等方法转换为 DateTime<Utc>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expires {
+    /// 在指定的时间过期
+    At(DateTime<Utc>),
+    /// 从当前时间开始的有效时长，当前时间会根据与OSS服务器的时间偏差进行校正
+    In(std::time::Duration),
+}
+impl From<DateTime<Utc>> for Expires {
+    fn from(value: DateTime<Utc>) -> Self {
+        Expires::At(value)
+    }
+}
+impl From<std::time::Duration> for Expires {
+    fn from(value: std::time::Duration) -> Self {
+        Expires::In(value)
+    }
+}
+impl From<chrono::Duration> for Expires {
+    fn from(value: chrono::Duration) -> Self {
+        Expires::In(value.to_std().unwrap_or_default())
+    }
+}
+
+/// 请求失败时的重试策略
+///
+/// 仅对不携带body的GET、HEAD、DELETE请求自动重试，重试条件为网络错误、请求超时以及OSS返回429、5xx状态码；两次重试之间的等待时间按照指数递增
+///
+/// OSS限流时如果返回了 Retry-After ，等待时间不少于 Retry-After 要求的时间
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_retry_policy(RetryPolicy::new(3).set_base_delay(Duration::from_millis(100)))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    base_delay: std::time::Duration,
+    max_delay: std::time::Duration,
+}
+impl RetryPolicy {
+    /// 初始化重试策略
+    ///
+    /// - max_retries：最大重试次数，为0时不重试
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            base_delay: std::time::Duration::from_millis(200),
+            max_delay: std::time::Duration::from_secs(10),
+        }
+    }
+    /// 不重试
+    pub fn none() -> Self {
+        RetryPolicy::new(0)
+    }
+    /// 设置首次重试前的等待时间，默认200毫秒
+    pub fn set_base_delay(mut self, base_delay: std::time::Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+    /// 设置单次等待的最长时间，默认10秒
+    pub fn set_max_delay(mut self, max_delay: std::time::Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+    /// 最大重试次数
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+    //第attempt次重试前的等待时间，attempt从1开始
+    pub(crate) fn delay(&self, attempt: u32) -> std::time::Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay)
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::none()
+    }
+}
+
+/// 熔断器
+///
+/// 对同一个Endpoint连续失败达到阈值后进入熔断状态，冷却时间内的请求直接返回 Error::CircuitOpen ，不再发送到OSS；
+/// 冷却时间结束后放行请求，成功则恢复，失败则重新熔断
+///
+/// 失败指网络错误、请求超时以及OSS返回5xx状态码
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_circuit_breaker(CircuitBreaker::new(5, Duration::from_secs(30)))
+///     .build()?;
+/// ```
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cool_down: std::time::Duration,
+    //各Endpoint的连续失败次数和熔断结束时间
+    states: Mutex<HashMap<String, (u32, Option<Instant>)>>,
+}
+impl CircuitBreaker {
+    /// - failure_threshold：连续失败多少次后熔断，为0时按1处理
+    /// - cool_down：熔断的持续时间
+    pub fn new(failure_threshold: u32, cool_down: std::time::Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Endpoint当前是否处于熔断状态
+    pub fn is_open(&self, endpoint: &str) -> bool {
+        self.lock()
+            .get(endpoint)
+            .and_then(|(_, open_until)| *open_until)
+            .is_some_and(|open_until| open_until > Instant::now())
+    }
+    //检查是否允许发送请求
+    pub(crate) fn check(&self, endpoint: &str) -> Result<(), Error> {
+        match self.is_open(endpoint) {
+            true => Err(Error::CircuitOpen(endpoint.to_owned())),
+            false => Ok(()),
+        }
+    }
+    //记录请求结果
+    pub(crate) fn record(&self, endpoint: &str, success: bool) {
+        let mut states = self.lock();
+        if success {
+            states.remove(endpoint);
+            return;
+        }
+        let state = states.entry(endpoint.to_owned()).or_insert((0, None));
+        state.0 = state.0.saturating_add(1);
+        if state.0 >= self.failure_threshold {
+            state.1 = Some(Instant::now() + self.cool_down);
+        }
+    }
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, (u32, Option<Instant>)>> {
+        match self.states.lock() {
+            Ok(states) => states,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// 对冲请求策略
+///
+/// 不携带body的GET、HEAD请求超过指定延迟仍未返回时，发送第二个相同的请求，使用最先成功的响应，用于降低长尾延迟
+///
+/// 延迟取最近请求耗时的分位数，如0.95代表P95；样本不足时使用最长延迟
+///
+/// 第二个请求同样需要并发名额和限速令牌，没有空闲的名额或令牌时不发送，继续等待第一个请求
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_hedge_policy(HedgePolicy::new(0.95).set_max_delay(Duration::from_millis(500)))
+///     .build()?;
+/// ```
+#[derive(Debug)]
+pub struct HedgePolicy {
+    percentile: f64,
+    min_delay: std::time::Duration,
+    max_delay: std::time::Duration,
+    //最近请求的耗时
+    samples: Mutex<VecDeque<std::time::Duration>>,
+}
+// 保留的耗时样本数量
+const HEDGE_WINDOW: usize = 1000;
+// 开始按分位数计算延迟所需的最少样本数量
+const HEDGE_MIN_SAMPLES: usize = 20;
+impl HedgePolicy {
+    /// - percentile：计算延迟使用的分位数，取值范围 0.0 - 1.0
+    pub fn new(percentile: f64) -> Self {
+        HedgePolicy {
+            percentile: percentile.clamp(0.0, 1.0),
+            min_delay: std::time::Duration::from_millis(10),
+            max_delay: std::time::Duration::from_secs(1),
+            samples: Mutex::new(VecDeque::with_capacity(HEDGE_WINDOW)),
+        }
+    }
+    /// 设置最短延迟，默认10毫秒
+    pub fn set_min_delay(mut self, min_delay: std::time::Duration) -> Self {
+        self.min_delay = min_delay;
+        self
+    }
+    /// 设置最长延迟，默认1秒
+    pub fn set_max_delay(mut self, max_delay: std::time::Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+    //发送第二个请求前的延迟
+    pub(crate) fn delay(&self) -> std::time::Duration {
+        let mut samples = self.lock().iter().copied().collect::<Vec<_>>();
+        if samples.len() < HEDGE_MIN_SAMPLES {
+            return self.max_delay;
+        }
+        samples.sort_unstable();
+        let index = ((samples.len() - 1) as f64 * self.percentile).round() as usize;
+        samples[index].clamp(self.min_delay, self.max_delay.max(self.min_delay))
+    }
+    //记录成功请求的耗时
+    pub(crate) fn record(&self, latency: std::time::Duration) {
+        let mut samples = self.lock();
+        if samples.len() >= HEDGE_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+    fn lock(&self) -> MutexGuard<'_, VecDeque<std::time::Duration>> {
+        match self.samples.lock() {
+            Ok(samples) => samples,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// 请求的操作类别，用于按类别限制请求速率
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestClass {
+    /// 列举类请求，如列举存储空间、文件、分片上传事件
+    List,
+    /// 读取类请求，如下载文件、获取文件元信息
+    Read,
+    /// 写入类请求，如上传、复制文件，修改文件元信息
+    Write,
+    /// 删除类请求
+    Delete,
+}
+impl RequestClass {
+    //按请求方法和是否指定文件判断操作类别
+    pub(crate) fn classify(method: &Method, has_object: bool) -> Self {
+        match *method {
+            Method::DELETE => RequestClass::Delete,
+            Method::GET | Method::HEAD if has_object => RequestClass::Read,
+            Method::GET | Method::HEAD => RequestClass::List,
+            //批量删除使用POST请求
+            _ => RequestClass::Write,
+        }
+    }
+}
+
+/// 客户端请求速率限制
+///
+/// 使用令牌桶算法限制每秒发送的请求数量，超出速率的请求会等待令牌，而不是发送到OSS后被限流；
+/// 可以同时设置全局限制和各操作类别的限制，请求需要同时满足两者
+///
+/// 重试的请求同样会消耗令牌
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_rate_limiter(
+///         RateLimiter::new(2000)
+///             .set_class_limit(RequestClass::List, 100)
+///             .set_class_limit(RequestClass::Delete, 500),
+///     )
+///     .build()?;
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    global: Option<Mutex<TokenBucket>>,
+    classes: HashMap<RequestClass, Mutex<TokenBucket>>,
+}
+impl RateLimiter {
+    /// - qps：全局每秒最多发送的请求数量，为0时不限制全局速率
+    pub fn new(qps: u32) -> Self {
+        RateLimiter {
+            global: (qps > 0).then(|| Mutex::new(TokenBucket::new(qps, qps))),
+            classes: HashMap::new(),
+        }
+    }
+    /// 设置全局允许的突发请求数量，默认与qps相同，最小为1
+    pub fn set_burst(mut self, burst: u32) -> Self {
+        if let Some(global) = &mut self.global {
+            let bucket = global.get_mut().unwrap_or_else(|e| e.into_inner());
+            *bucket = TokenBucket::new(bucket.rate as u32, burst);
+        }
+        self
+    }
+    /// 设置某一操作类别每秒最多发送的请求数量，为0时取消此类别的限制
+    pub fn set_class_limit(mut self, class: RequestClass, qps: u32) -> Self {
+        if qps > 0 {
+            self.classes
+                .insert(class, Mutex::new(TokenBucket::new(qps, qps)));
+        } else {
+            self.classes.remove(&class);
+        }
+        self
+    }
+    //等待直到可以发送请求
+    pub(crate) async fn acquire(&self, class: RequestClass) {
+        if let Some(global) = &self.global {
+            TokenBucket::acquire(global).await;
+        }
+        if let Some(bucket) = self.classes.get(&class) {
+            TokenBucket::acquire(bucket).await;
+        }
+    }
+    //不等待，全局和类别的令牌都充足时同时取出并返回true，否则不取出令牌
+    pub(crate) fn try_acquire(&self, class: RequestClass) -> bool {
+        let mut buckets = self
+            .global
+            .iter()
+            .chain(self.classes.get(&class))
+            .map(|bucket| bucket.lock().unwrap_or_else(|e| e.into_inner()))
+            .collect::<Vec<_>>();
+        if !buckets.iter_mut().all(|bucket| bucket.refill() >= 1.0) {
+            return false;
+        }
+        for bucket in &mut buckets {
+            bucket.tokens -= 1.0;
+        }
+        true
+    }
+}
+
+//令牌桶，按固定速率补充令牌，最多积累burst个
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+impl TokenBucket {
+    fn new(qps: u32, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        TokenBucket {
+            rate: f64::from(qps.max(1)),
+            burst,
+            tokens: burst,
+            updated: Instant::now(),
+        }
+    }
+    //按经过的时间补充令牌，返回当前的令牌数量
+    fn refill(&mut self) -> f64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated = now;
+        self.tokens
+    }
+    //取出一个令牌，令牌不足时返回需要等待的时间
+    fn take(&mut self) -> Option<std::time::Duration> {
+        if self.refill() >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(std::time::Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.rate,
+            ))
+        }
+    }
+    async fn acquire(bucket: &Mutex<TokenBucket>) {
+        loop {
+            let wait = match bucket.lock() {
+                Ok(mut bucket) => bucket.take(),
+                Err(poisoned) => poisoned.into_inner().take(),
+            };
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => break,
+            }
+        }
+    }
+}
+
+/// 请求被OSS限流的事件
+///
+/// OSS返回429或503状态码时产生，可以通过 OssClientBuilder::set_throttle_listener 统计限流情况
+#[derive(Debug, Clone)]
+pub struct ThrottleEvent {
+    /// 请求方法
+    pub method: String,
+    /// 请求地址，不包含查询参数
+    pub url: String,
+    /// OSS返回的HTTP状态码
+    pub status: u16,
+    /// OSS通过 Retry-After 要求的等待时间
+    pub retry_after: Option<std::time::Duration>,
+    /// 本次请求之前已经重试的次数
+    pub attempt: u32,
+    /// 是否会继续重试
+    pub will_retry: bool,
+}
+
+//限流事件的回调
+#[derive(Clone)]
+pub(crate) struct ThrottleListener(pub Arc<dyn Fn(&ThrottleEvent) + Send + Sync>);
+impl fmt::Debug for ThrottleListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ThrottleListener")
+    }
+}
+
+/// 一次请求的信息
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// 请求方法
+    pub method: String,
+    /// 请求地址，不包含查询参数
+    pub url: String,
+    /// OSS返回的HTTP状态码
+    pub status: u16,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    pub request_id: Option<String>,
+}
+
+/// 请求记录
+///
+/// 设置到OssBucket或OssObject后，记录通过它发起的每个请求（包括成功的请求）的Request ID，
+/// 克隆后共享同一份记录，只保留最近的若干条
+///
+/// 并发请求共享同一个记录时无法区分记录属于哪次调用，单次调用的Request ID可以从返回结果的 request_id 中读取
+///
+/// ```
+/// let recorder = RequestRecorder::new();
+/// let object = bucket.object("rust.mp4").set_request_recorder(&recorder);
+/// object.put_object().send_file("/data/rust.mp4").await?;
+/// for info in recorder.take() {
+///     println!("{} {} {:?}", info.method, info.url, info.request_id);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestRecorder {
+    records: Arc<Mutex<VecDeque<RequestInfo>>>,
+    capacity: usize,
+}
+impl RequestRecorder {
+    /// 保留最近100条记录
+    pub fn new() -> Self {
+        RequestRecorder::with_capacity(100)
+    }
+    /// 保留最近capacity条记录，超出后丢弃最早的记录，最小为1
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        RequestRecorder {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity.min(1024)))),
+            capacity,
+        }
+    }
+    /// 取出所有记录，并清空，按请求完成的先后排列
+    pub fn take(&self) -> Vec<RequestInfo> {
+        std::mem::take(&mut *self.lock()).into()
+    }
+    pub(crate) fn record(&self, info: RequestInfo) {
+        let mut records = self.lock();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(info);
+    }
+    fn lock(&self) -> MutexGuard<'_, VecDeque<RequestInfo>> {
+        match self.records.lock() {
+            Ok(records) => records,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+impl Default for RequestRecorder {
+    fn default() -> Self {
+        RequestRecorder::new()
+    }
+}
+
+/// 回调请求的Content-Type
+#[derive(Debug, Clone, Copy)]
+pub enum CallbackBodyType {
+    /// application/x-www-form-urlencoded
+    FormUrlEncoded,
+    /// application/json
+    Json,
+}
+impl fmt::Display for CallbackBodyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallbackBodyType::FormUrlEncoded => f.write_str("application/x-www-form-urlencoded"),
+            CallbackBodyType::Json => f.write_str("application/json"),
+        }
+    }
+}
+
+/// 上传回调
+///
+/// 文件上传完成后，OSS会向回调服务器发送POST请求，并将回调服务器的响应内容返回给调用者
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31989.html)
+///
+/// ```
+/// let callback = Callback::new(
+///     "https://example.com/callback",
+///     "bucket=${bucket}&object=${object}&size=${size}&uid=${x:uid}",
+/// )
+/// .add_var("uid", "10086");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Callback {
+    url: String,
+    body: String,
+    host: Option<String>,
+    body_type: Option<CallbackBodyType>,
+    sni: bool,
+    vars: Vec<(String, String)>,
+}
+impl Callback {
+    /// 初始化回调
+    ///
+    /// - url ：回调服务器地址，多个地址使用分号分隔
+    /// - body ：回调请求的内容，支持系统变量（如 ${bucket}、${object}）和自定义变量（如 ${x:uid}）
+    pub fn new(url: impl ToString, body: impl ToString) -> Self {
+        Callback {
+            url: url.to_string(),
+            body: body.to_string(),
+            host: None,
+            body_type: None,
+            sni: false,
+            vars: Vec::new(),
+        }
+    }
+    /// 设置回调请求的Host
+    pub fn set_host(mut self, host: impl ToString) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+    /// 设置回调请求的Content-Type，默认为 application/x-www-form-urlencoded
+    pub fn set_body_type(mut self, body_type: CallbackBodyType) -> Self {
+        self.body_type = Some(body_type);
+        self
+    }
+    /// 回调请求携带SNI
+    pub fn enable_sni(mut self) -> Self {
+        self.sni = true;
+        self
+    }
+    /// 添加自定义变量
+    ///
+    /// key无需添加 x: 前缀，在body中通过 ${x:key} 引用
+    pub fn add_var(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.vars.push((key.to_string(), value.to_string()));
+        self
+    }
+    //生成 x-oss-callback 请求头
+    pub(crate) fn callback_header(&self) -> String {
+        let mut json = format!(
+            "{{\"callbackUrl\":\"{}\",\"callbackBody\":\"{}\"",
+            json_escape(&self.url),
+            json_escape(&self.body)
+        );
+        if let Some(host) = &self.host {
+            json.push_str(&format!(",\"callbackHost\":\"{}\"", json_escape(host)));
+        }
+        if let Some(body_type) = &self.body_type {
+            json.push_str(&format!(",\"callbackBodyType\":\"{}\"", body_type));
+        }
+        if self.sni {
+            json.push_str(",\"callbackSNI\":true");
+        }
+        json.push('}');
+        general_purpose::STANDARD.encode(json)
+    }
+    //生成 x-oss-callback-var 请求头
+    pub(crate) fn callback_var_header(&self) -> Option<String> {
+        if self.vars.is_empty() {
+            return None;
+        }
+        let json = self
+            .vars
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "\"x:{}\":\"{}\"",
+                    json_escape(key.trim_start_matches("x:")),
+                    json_escape(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        Some(general_purpose::STANDARD.encode(format!("{{{}}}", json)))
+    }
+}
+
+// -------------------------- 分页 --------------------------
+/// 分页请求的结果，包含当前页的数据，以及用于获取下一页的请求
+pub type PageFuture<P> =
+    BoxFuture<'static, Result<(Vec<<P as Paginator>::Item>, Option<P>), Error>>;
+
+/// 分页列举
+///
+/// 自动处理列举结果中的分页标记，连续发送请求直到列举完所有数据
+///
+/// ```
+/// use aliyun_oss_rs::common::Paginator;
+/// use futures_util::StreamExt;
+///
+/// let mut objects = bucket.list_objects().set_prefix("images/").into_stream();
+/// while let Some(object) = objects.next().await {
+///     println!("{:?}", object);
+/// }
+/// let buckets = client.list_buckets().all().await;
+/// ```
+pub trait Paginator: Sized + Send + 'static {
+    /// 列举结果中的单条数据
+    type Item: Send + 'static;
+    /// 发送请求，返回当前页的数据，以及用于获取下一页的请求；已经是最后一页时，下一页请求为None
+    fn next_page(self) -> PageFuture<Self>;
+    /// 转换为数据流，逐条返回所有数据，需要时才会请求下一页
+    ///
+    /// 请求出错时，数据流返回错误后结束
+    fn into_stream(self) -> BoxStream<'static, Result<Self::Item, Error>> {
+        stream::unfold(Some(self), |state| async move {
+            match state?.next_page().await {
+                Ok((items, next)) => Some((items.into_iter().map(Ok).collect::<Vec<_>>(), next)),
+                Err(e) => Some((vec![Err(e)], None)),
+            }
+        })
+        .flat_map(stream::iter)
+        .boxed()
+    }
+    /// 列举所有数据
+    ///
+    /// 数据量较大时，此方法可能占用过多内存，谨慎使用
+    fn all(self) -> BoxFuture<'static, Result<Vec<Self::Item>, Error>> {
+        self.into_stream().try_collect().boxed()
+    }
+}
@@ -0,0 +1,101 @@
+//! gzip压缩与解压
+//!
+//! 列举类API的响应为XML，文件较多时单页可达数百KB，请求gzip压缩的响应可以大幅减少传输量；
+//! 解压以流的方式进行，不需要缓存完整的响应
+//!
+//! 文本、日志类文件也可以压缩上传，并设置 Content-Encoding: gzip ，下载时按需解压
+
+use bytes::Bytes;
+use flate2::{
+    write::{GzDecoder, GzEncoder},
+    Compression,
+};
+use futures_util::{stream, StreamExt};
+use hyper::{header, Body, Response};
+use std::io::{self, Write};
+
+//响应使用gzip压缩时解压body，并移除Content-Encoding和Content-Length
+pub(crate) fn decode_response(response: Response<Body>) -> Response<Body> {
+    let gzip = response
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("gzip"));
+    if !gzip {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, gzip_decode(body))
+}
+
+//流式解压gzip数据
+pub(crate) fn gzip_decode(body: Body) -> Body {
+    transcode(body, GzDecoder::new(Vec::new()))
+}
+
+//流式压缩数据
+pub(crate) fn gzip_encode(body: Body) -> Body {
+    transcode(body, GzEncoder::new(Vec::new(), Compression::default()))
+}
+
+//压缩内存中的数据
+pub(crate) fn gzip_encode_bytes(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(
+        Vec::with_capacity(content.len() / 2),
+        Compression::default(),
+    );
+    encoder.write_all(content)?;
+    encoder.finish()
+}
+
+//GzDecoder和GzEncoder共用的写入接口，写入的数据处理后输出到内部的Vec
+trait Coder: Write + Send + 'static {
+    fn output(&mut self) -> &mut Vec<u8>;
+    fn finish(self) -> io::Result<Vec<u8>>;
+}
+impl Coder for GzDecoder<Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+    fn finish(self) -> io::Result<Vec<u8>> {
+        GzDecoder::finish(self)
+    }
+}
+impl Coder for GzEncoder<Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+    fn finish(self) -> io::Result<Vec<u8>> {
+        GzEncoder::finish(self)
+    }
+}
+
+//逐块处理body，处理后的数据为空时继续读取下一块
+fn transcode<C: Coder>(body: Body, coder: C) -> Body {
+    Body::wrap_stream(stream::unfold(Some((body, coder)), |state| async move {
+        let (mut body, mut coder) = state?;
+        loop {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    if let Err(e) = coder.write_all(&chunk) {
+                        return Some((Err(e), None));
+                    }
+                    let output = std::mem::take(coder.output());
+                    if !output.is_empty() {
+                        return Some((Ok(Bytes::from(output)), Some((body, coder))));
+                    }
+                }
+                Some(Err(e)) => return Some((Err(io::Error::other(e)), None)),
+                None => {
+                    return match coder.finish() {
+                        Ok(output) if output.is_empty() => None,
+                        Ok(output) => Some((Ok(Bytes::from(output)), None)),
+                        Err(e) => Some((Err(e), None)),
+                    }
+                }
+            }
+        }
+    }))
+}
@@ -0,0 +1,151 @@
+use super::{fetch, json_value, parse_credentials, Credentials, CredentialsProvider};
+use crate::{backend::HmacSha1, common::uri_encode, Error};
+use base64::{engine::general_purpose, Engine};
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use hyper::{header, Body, Method, Request};
+use std::{
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// 通过STS AssumeRole扮演RAM角色，获取临时凭证
+///
+/// 使用RAM用户的AccessKey调用STS，可以通过权限策略进一步缩小临时凭证的权限，适用于向客户端下发受限的访问凭证
+///
+/// ```
+/// use aliyun_oss_rs::credentials::{AssumeRoleProvider, Credentials};
+///
+/// let provider = AssumeRoleProvider::new(
+///     Credentials::new("AccessKey ID", "AccessKey Secret"),
+///     "acs:ram::123456789012****:role/oss-reader",
+/// )
+/// .set_session_name("web-upload")
+/// .set_duration(Duration::from_secs(900));
+/// // 作为OssClient的凭证提供者，临近过期时自动刷新
+/// let client = OssClient::from_provider(provider.clone());
+/// // 或者直接获取临时凭证，下发给客户端
+/// let credentials = provider.assume_role().await?;
+/// ```
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/ram/developer-reference/api-sts-2015-04-01-assumerole)
+#[derive(Debug, Clone)]
+pub struct AssumeRoleProvider {
+    source: Credentials,
+    role_arn: String,
+    session_name: String,
+    duration: Duration,
+    policy: Option<String>,
+    external_id: Option<String>,
+    sts_endpoint: String,
+}
+impl AssumeRoleProvider {
+    /// - source：调用STS使用的访问凭证，需要有扮演此角色的权限
+    /// - role_arn：RAM角色的ARN
+    pub fn new(source: Credentials, role_arn: impl ToString) -> Self {
+        AssumeRoleProvider {
+            source,
+            role_arn: role_arn.to_string(),
+            session_name: format!("aliyun-oss-rs-{}", Utc::now().timestamp()),
+            duration: Duration::from_secs(3600),
+            policy: None,
+            external_id: None,
+            sts_endpoint: "sts.aliyuncs.com".to_owned(),
+        }
+    }
+    /// 设置角色会话名称，用于区分不同的令牌使用者
+    pub fn set_session_name(mut self, session_name: impl ToString) -> Self {
+        self.session_name = session_name.to_string();
+        self
+    }
+    /// 设置临时凭证的有效期，默认1小时，最小15分钟，最大不超过角色的最大会话时间
+    pub fn set_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration.max(Duration::from_secs(900));
+        self
+    }
+    /// 设置权限策略，进一步限制临时凭证的权限，可以使用 policy::Policy::to_json() 生成
+    pub fn set_policy(mut self, policy: impl ToString) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+    /// 设置角色外部ID，用于防止混淆代理人问题
+    pub fn set_external_id(mut self, external_id: impl ToString) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+    /// 设置STS的Endpoint，默认为 sts.aliyuncs.com ，可以使用 sts-vpc.cn-hangzhou.aliyuncs.com 等VPC地址
+    pub fn set_sts_endpoint(mut self, sts_endpoint: impl ToString) -> Self {
+        self.sts_endpoint = sts_endpoint.to_string();
+        self
+    }
+    /// 调用AssumeRole，返回临时凭证
+    pub async fn assume_role(&self) -> Result<Credentials, Error> {
+        let mut params = vec![
+            ("Action", "AssumeRole".to_owned()),
+            ("Format", "JSON".to_owned()),
+            ("Version", "2015-04-01".to_owned()),
+            ("AccessKeyId", self.source.access_key_id.clone()),
+            ("SignatureMethod", "HMAC-SHA1".to_owned()),
+            ("SignatureVersion", "1.0".to_owned()),
+            ("SignatureNonce", nonce()),
+            (
+                "Timestamp",
+                Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            ),
+            ("RoleArn", self.role_arn.clone()),
+            ("RoleSessionName", self.session_name.clone()),
+            ("DurationSeconds", self.duration.as_secs().to_string()),
+        ];
+        if let Some(security_token) = &self.source.security_token {
+            params.push(("SecurityToken", security_token.clone()));
+        }
+        if let Some(policy) = &self.policy {
+            params.push(("Policy", policy.clone()));
+        }
+        if let Some(external_id) = &self.external_id {
+            params.push(("ExternalId", external_id.clone()));
+        }
+        //RPC风格签名，参数按名称排序后编码
+        params.sort();
+        let query = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, uri_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let string_to_sign = format!("POST&%2F&{}", uri_encode(&query));
+        let key = format!("{}&", self.source.access_key_secret);
+        let signature = general_purpose::STANDARD
+            .encode(HmacSha1::new(key.as_bytes()).sign(string_to_sign.as_bytes())?);
+        let body = format!("{}&Signature={}", query, uri_encode(&signature));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("https://{}/", self.sts_endpoint))
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))?;
+        match fetch(request, Duration::from_secs(10)).await? {
+            (200, body) => parse_credentials(&body),
+            (status, body) => Err(Error::CredentialsError(format!(
+                "AssumeRole失败，HTTP状态码：{}，{}",
+                status,
+                json_value(&body, "Message").unwrap_or(body)
+            ))),
+        }
+    }
+}
+impl CredentialsProvider for AssumeRoleProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        Box::pin(self.assume_role())
+    }
+}
+
+//请求的唯一随机数，由时间、进程ID和计数器组成
+fn nonce() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}-{}",
+        Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
@@ -0,0 +1,80 @@
+use super::{fetch, parse_credentials, Credentials, CredentialsProvider};
+use crate::Error;
+use futures_util::future::BoxFuture;
+use hyper::{Body, Method, Request};
+use std::time::Duration;
+
+const METADATA_ENDPOINT: &str = "http://100.100.100.200";
+
+/// 从ECS实例元数据服务获取实例RAM角色的临时凭证
+///
+/// 未设置角色名称时，自动查询实例绑定的RAM角色；优先使用加固模式访问元数据服务，获取令牌失败时使用普通模式
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/ecs/user-guide/attach-an-instance-ram-role-to-an-ecs-instance)
+#[derive(Debug, Clone, Default)]
+pub struct EcsRamRoleProvider {
+    role_name: Option<String>,
+    disable_imds_v1: bool,
+}
+impl EcsRamRoleProvider {
+    pub fn new() -> Self {
+        EcsRamRoleProvider::default()
+    }
+    /// 设置实例RAM角色的名称
+    pub fn set_role_name(mut self, role_name: impl ToString) -> Self {
+        self.role_name = Some(role_name.to_string());
+        self
+    }
+    /// 只使用加固模式访问元数据服务，获取令牌失败时返回错误
+    pub fn disable_imds_v1(mut self) -> Self {
+        self.disable_imds_v1 = true;
+        self
+    }
+    //获取加固模式的令牌
+    async fn metadata_token(&self) -> Result<Option<String>, Error> {
+        let request = Request::builder()
+            .method(Method::PUT)
+            .uri(format!("{}/latest/api/token", METADATA_ENDPOINT))
+            .header("X-aliyun-ecs-metadata-token-ttl-seconds", "21600")
+            .body(Body::empty())?;
+        match fetch(request, Duration::from_secs(1)).await {
+            Ok((200, token)) => Ok(Some(token)),
+            Ok((status, body)) if self.disable_imds_v1 => Err(Error::CredentialsError(format!(
+                "获取元数据服务令牌失败，HTTP状态码：{}，{}",
+                status, body
+            ))),
+            Err(e) if self.disable_imds_v1 => Err(e),
+            _ => Ok(None),
+        }
+    }
+    //读取元数据
+    async fn metadata(&self, path: &str, token: Option<&str>) -> Result<String, Error> {
+        let mut request = Request::builder().uri(format!(
+            "{}/latest/meta-data/ram/security-credentials/{}",
+            METADATA_ENDPOINT, path
+        ));
+        if let Some(token) = token {
+            request = request.header("X-aliyun-ecs-metadata-token", token);
+        }
+        match fetch(request.body(Body::empty())?, Duration::from_secs(5)).await? {
+            (200, body) => Ok(body),
+            (status, body) => Err(Error::CredentialsError(format!(
+                "读取ECS实例元数据失败，HTTP状态码：{}，{}",
+                status, body
+            ))),
+        }
+    }
+}
+impl CredentialsProvider for EcsRamRoleProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        Box::pin(async move {
+            let token = self.metadata_token().await?;
+            let role_name = match &self.role_name {
+                Some(role_name) => role_name.clone(),
+                None => self.metadata("", token.as_deref()).await?.trim().to_owned(),
+            };
+            let body = self.metadata(&role_name, token.as_deref()).await?;
+            parse_credentials(&body)
+        })
+    }
+}
@@ -0,0 +1,380 @@
+//! 访问凭证
+//!
+//! 通过 CredentialsProvider 获取访问凭证，OssClient在发送请求前检查凭证的有效期，临近过期时自动刷新，适用于使用STS临时凭证的长期运行的服务
+//!
+//! 内置的凭证提供者：
+//! - StaticProvider：固定的AccessKey
+//! - EcsRamRoleProvider：从ECS实例元数据服务获取实例RAM角色的临时凭证
+//! - OidcRoleProvider：通过OIDC令牌扮演RAM角色（ACK集群的RRSA功能）
+//! - FnProvider：通过闭包获取凭证，用于对接自定义的凭证服务
+//! - AssumeRoleProvider：通过STS AssumeRole扮演RAM角色，需要开启 sts feature
+//!
+//! ```
+//! use aliyun_oss_rs::credentials::EcsRamRoleProvider;
+//!
+//! let client = OssClient::from_provider(EcsRamRoleProvider::new());
+//! let bucket = client.bucket("for-rs-test", "oss-cn-zhangjiakou.aliyuncs.com");
+//! bucket.list_objects().send().await?;
+//! ```
+//!
+//! 也可以通过 OssClient::from_env 从环境变量、通过 OssClient::from_profile 从aliyun命令行工具的配置文件中读取凭证
+//!
+//! 生成授权url、OssBucket::sign_request 等同步签名的方法不会刷新凭证，使用前可以通过 OssClient::refresh_credentials 确保凭证有效
+
+pub use self::{ecs_ram_role::EcsRamRoleProvider, oidc_role::OidcRoleProvider, profile::Profile};
+
+#[cfg(feature = "sts")]
+pub use self::assume_role::AssumeRoleProvider;
+
+#[cfg(feature = "sts")]
+mod assume_role;
+mod ecs_ram_role;
+mod oidc_role;
+mod profile;
+
+use crate::{
+    request::{self, HttpClient, Oss},
+    signer::HmacSigner,
+    Error,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{future::BoxFuture, Future, FutureExt};
+use hyper::{body::to_bytes, Body, Request};
+use std::{
+    env, fmt,
+    str::CharIndices,
+    sync::{Arc, Mutex, MutexGuard},
+    time,
+};
+
+/// 访问凭证
+#[derive(Clone)]
+pub struct Credentials {
+    /// AccessKey ID
+    pub access_key_id: String,
+    /// AccessKey Secret
+    pub access_key_secret: String,
+    /// STS临时凭证的安全令牌
+    pub security_token: Option<String>,
+    /// 过期时间，None表示长期有效
+    pub expiration: Option<DateTime<Utc>>,
+}
+impl Credentials {
+    pub fn new(access_key_id: impl ToString, access_key_secret: impl ToString) -> Self {
+        Credentials {
+            access_key_id: access_key_id.to_string(),
+            access_key_secret: access_key_secret.to_string(),
+            security_token: None,
+            expiration: None,
+        }
+    }
+    /// 从环境变量中读取访问凭证，缺少AccessKey时返回None
+    ///
+    /// 优先读取 OSS_ACCESS_KEY_ID、OSS_ACCESS_KEY_SECRET、OSS_SESSION_TOKEN ，
+    /// 其次读取 ALIBABA_CLOUD_ACCESS_KEY_ID、ALIBABA_CLOUD_ACCESS_KEY_SECRET、ALIBABA_CLOUD_SECURITY_TOKEN
+    pub fn from_env() -> Option<Self> {
+        let var = |key: &str| env::var(key).ok().filter(|v| !v.is_empty());
+        [
+            (
+                "OSS_ACCESS_KEY_ID",
+                "OSS_ACCESS_KEY_SECRET",
+                "OSS_SESSION_TOKEN",
+            ),
+            (
+                "ALIBABA_CLOUD_ACCESS_KEY_ID",
+                "ALIBABA_CLOUD_ACCESS_KEY_SECRET",
+                "ALIBABA_CLOUD_SECURITY_TOKEN",
+            ),
+        ]
+        .into_iter()
+        .find_map(|(id, secret, token)| {
+            let mut credentials = Credentials::new(var(id)?, var(secret)?);
+            credentials.security_token = var(token);
+            Some(credentials)
+        })
+    }
+    /// 设置STS临时凭证的安全令牌
+    pub fn set_security_token(mut self, security_token: impl ToString) -> Self {
+        self.security_token = Some(security_token.to_string());
+        self
+    }
+    /// 设置过期时间
+    pub fn set_expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+}
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("expiration", &self.expiration)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 访问凭证的提供者
+///
+/// 首次请求和凭证即将过期（剩余有效期不足5分钟）时调用，同一时间只有一个请求在刷新凭证，其他请求等待刷新完成
+pub trait CredentialsProvider: fmt::Debug + Send + Sync {
+    /// 获取访问凭证
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>>;
+}
+
+/// 固定的AccessKey
+#[derive(Debug, Clone)]
+pub struct StaticProvider {
+    credentials: Credentials,
+}
+impl StaticProvider {
+    pub fn new(credentials: Credentials) -> Self {
+        StaticProvider { credentials }
+    }
+}
+impl CredentialsProvider for StaticProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        Box::pin(futures_util::future::ready(Ok(self.credentials.clone())))
+    }
+}
+
+/// 通过闭包获取访问凭证
+///
+/// ```
+/// use aliyun_oss_rs::credentials::{Credentials, FnProvider};
+///
+/// let provider = FnProvider::new(|| async {
+///     let token = my_vault.fetch_oss_token().await?;
+///     Ok(Credentials::new(token.id, token.secret)
+///         .set_security_token(token.security_token)
+///         .set_expiration(token.expiration))
+/// });
+/// let client = OssClient::from_provider(provider);
+/// ```
+pub struct FnProvider<F> {
+    f: F,
+}
+impl<F, Fut> FnProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Credentials, Error>> + Send + 'static,
+{
+    pub fn new(f: F) -> Self {
+        FnProvider { f }
+    }
+}
+impl<F> fmt::Debug for FnProvider<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FnProvider")
+    }
+}
+impl<F, Fut> CredentialsProvider for FnProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Credentials, Error>> + Send + 'static,
+{
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        (self.f)().boxed()
+    }
+}
+
+// 缓存凭证提供者返回的凭证，临近过期时刷新
+//
+// 刷新后的凭证写入Oss中共享的访问凭证，同一个OssClient创建的OssBucket、OssObject同时生效
+#[derive(Debug)]
+pub(crate) struct CredentialsCache {
+    provider: Box<dyn CredentialsProvider>,
+    //None表示尚未获取过凭证
+    expiration: Mutex<Option<Option<DateTime<Utc>>>>,
+    refreshing: tokio::sync::Mutex<()>,
+}
+impl CredentialsCache {
+    pub fn new(provider: impl CredentialsProvider + 'static) -> Self {
+        CredentialsCache {
+            provider: Box::new(provider),
+            expiration: Mutex::new(None),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+    fn lock(&self) -> MutexGuard<'_, Option<Option<DateTime<Utc>>>> {
+        match self.expiration.lock() {
+            Ok(expiration) => expiration,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+    //凭证是否需要刷新，剩余有效期不足5分钟时提前刷新
+    fn need_refresh(&self, now: DateTime<Utc>) -> bool {
+        match *self.lock() {
+            None => true,
+            Some(None) => false,
+            Some(Some(expiration)) => expiration - now < Duration::minutes(5),
+        }
+    }
+    //按需刷新凭证；刷新失败但当前凭证尚未过期时，继续使用当前凭证
+    pub async fn refresh(&self, oss: &Oss, force: bool) -> Result<(), Error> {
+        if !force && !self.need_refresh(oss.now()) {
+            return Ok(());
+        }
+        let _refreshing = self.refreshing.lock().await;
+        //等待期间其他请求已经完成刷新
+        if !force && !self.need_refresh(oss.now()) {
+            return Ok(());
+        }
+        let credentials = match self.provider.credentials().await {
+            Ok(credentials) => credentials,
+            Err(e) => {
+                return match *self.lock() {
+                    Some(Some(expiration)) if expiration > oss.now() => Ok(()),
+                    _ => Err(e),
+                }
+            }
+        };
+        oss.update_credentials(request::Credentials {
+            signer: Arc::new(HmacSigner::new(
+                &credentials.access_key_id,
+                &credentials.access_key_secret,
+            )),
+            security_token: credentials.security_token.map(|v| v.into()),
+        });
+        *self.lock() = Some(credentials.expiration);
+        Ok(())
+    }
+}
+
+//发送获取凭证的请求，返回状态码和响应内容
+async fn fetch(request: Request<Body>, timeout: time::Duration) -> Result<(u16, String), Error> {
+    let sending = async {
+        let response = HttpClient::default().request(request).await?;
+        let status = response.status().as_u16();
+        let body = to_bytes(response.into_body()).await?;
+        Ok((status, String::from_utf8_lossy(&body).into_owned()))
+    };
+    tokio::time::timeout(timeout, sending)
+        .await
+        .unwrap_or(Err(Error::Timeout))
+}
+
+//查找JSON中的字段，返回字段值的起始位置
+//
+//在任意层级的对象中查找第一个同名的字段，只匹配对象的键，字符串值中出现的字段名不会被当作字段
+fn json_key(json: &str, key: &str) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(offset) = json[pos..].find('"') {
+        let (name, end) = json_string(json, pos + offset)?;
+        match json[end..].trim_start().strip_prefix(':') {
+            Some(value) if name == key => return Some(json.len() - value.trim_start().len()),
+            _ => pos = end,
+        }
+    }
+    None
+}
+
+//解析start处以引号开始的JSON字符串，返回反转义后的内容和结束引号之后的位置
+fn json_string(json: &str, start: usize) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = json[start + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, start + i + 2)),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'u' => {
+                    let mut code = json_hex(&mut chars)?;
+                    //UTF-16代理对，如 \ud83d\ude00
+                    if (0xD800..0xDC00).contains(&code) {
+                        if (chars.next()?.1, chars.next()?.1) != ('\\', 'u') {
+                            return None;
+                        }
+                        let low = json_hex(&mut chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return None;
+                        }
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    }
+                    value.push(char::from_u32(code)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+//读取 \u 之后的4位十六进制数
+fn json_hex(chars: &mut CharIndices) -> Option<u32> {
+    let code = chars.take(4).map(|(_, c)| c).collect::<String>();
+    match code.len() {
+        4 => u32::from_str_radix(&code, 16).ok(),
+        _ => None,
+    }
+}
+
+//读取JSON中字符串或数字类型的字段值，字段名在整个JSON中唯一；字段值为null、对象或数组时返回None
+fn json_value(json: &str, key: &str) -> Option<String> {
+    let start = json_key(json, key)?;
+    let rest = &json[start..];
+    if rest.starts_with('"') {
+        return json_string(json, start).map(|(value, _)| value);
+    }
+    let end = rest.find([',', '}', ']']).unwrap_or(rest.len());
+    match rest[..end].trim() {
+        "" | "null" => None,
+        value if value.starts_with(['{', '[']) => None,
+        value => Some(value.to_owned()),
+    }
+}
+
+//从STS、ECS元数据服务的响应中读取凭证
+fn parse_credentials(json: &str) -> Result<Credentials, Error> {
+    let field = |key: &str| {
+        json_value(json, key).ok_or_else(|| Error::CredentialsError(format!("响应中缺少{}", key)))
+    };
+    let mut credentials = Credentials::new(field("AccessKeyId")?, field("AccessKeySecret")?);
+    credentials.security_token = json_value(json, "SecurityToken");
+    credentials.expiration = json_value(json, "Expiration")
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        .map(|v| v.with_timezone(&Utc));
+    Ok(credentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_value;
+
+    #[test]
+    fn json_value_reads_nested_objects() {
+        let json = r#"{"RequestId":"1","Credentials":{"AccessKeyId":"ak","Expiration":"2023-12-16T00:00:00Z","Count":3}}"#;
+        assert_eq!(json_value(json, "AccessKeyId").as_deref(), Some("ak"));
+        assert_eq!(json_value(json, "Count").as_deref(), Some("3"));
+        assert_eq!(json_value(json, "Credentials"), None);
+        assert_eq!(json_value(json, "Missing"), None);
+    }
+
+    #[test]
+    fn json_value_ignores_keys_inside_values() {
+        let json =
+            r#"{"Message":"\"AccessKeyId\": missing","Code":"AccessKeyId","AccessKeyId":"ak"}"#;
+        assert_eq!(json_value(json, "AccessKeyId").as_deref(), Some("ak"));
+        assert_eq!(
+            json_value(json, "Message").as_deref(),
+            Some("\"AccessKeyId\": missing")
+        );
+        assert_eq!(
+            json_value(r#"{"Message":"AccessKeyId:x"}"#, "AccessKeyId"),
+            None
+        );
+    }
+
+    #[test]
+    fn json_value_decodes_escapes() {
+        let json = r#"{"a":"x\\y\/z\n\u00e9\ud83d\ude00","b":null, "c" : [1,2]}"#;
+        assert_eq!(json_value(json, "a").as_deref(), Some("x\\y/z\né😀"));
+        assert_eq!(json_value(json, "b"), None);
+        assert_eq!(json_value(json, "c"), None);
+        assert_eq!(json_value(r#"{"a":"\ud83d"}"#, "a"), None);
+    }
+}
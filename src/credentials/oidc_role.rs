@@ -0,0 +1,120 @@
+use super::{fetch, json_value, parse_credentials, Credentials, CredentialsProvider};
+use crate::{common::uri_encode, Error};
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use hyper::{header, Body, Method, Request};
+use std::{env, path::PathBuf, time::Duration};
+
+/// 通过OIDC令牌扮演RAM角色，获取临时凭证
+///
+/// 用于ACK集群的RRSA功能，Pod中的OIDC令牌文件会定期轮换，每次刷新凭证时重新读取
+///
+/// ```
+/// use aliyun_oss_rs::credentials::OidcRoleProvider;
+///
+/// // 读取 ALIBABA_CLOUD_ROLE_ARN、ALIBABA_CLOUD_OIDC_PROVIDER_ARN、ALIBABA_CLOUD_OIDC_TOKEN_FILE 环境变量
+/// let provider = OidcRoleProvider::from_env().ok_or("未开启RRSA")?;
+/// let client = OssClient::from_provider(provider.set_session_name("my-service"));
+/// ```
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/ram/developer-reference/api-sts-2015-04-01-assumerolewithoidc)
+#[derive(Debug, Clone)]
+pub struct OidcRoleProvider {
+    role_arn: String,
+    oidc_provider_arn: String,
+    oidc_token_file: PathBuf,
+    session_name: String,
+    duration: Duration,
+    policy: Option<String>,
+    sts_endpoint: String,
+}
+impl OidcRoleProvider {
+    /// - role_arn：RAM角色的ARN
+    /// - oidc_provider_arn：OIDC身份提供商的ARN
+    /// - oidc_token_file：OIDC令牌文件的路径
+    pub fn new(
+        role_arn: impl ToString,
+        oidc_provider_arn: impl ToString,
+        oidc_token_file: impl Into<PathBuf>,
+    ) -> Self {
+        OidcRoleProvider {
+            role_arn: role_arn.to_string(),
+            oidc_provider_arn: oidc_provider_arn.to_string(),
+            oidc_token_file: oidc_token_file.into(),
+            session_name: format!("aliyun-oss-rs-{}", Utc::now().timestamp()),
+            duration: Duration::from_secs(3600),
+            policy: None,
+            sts_endpoint: "sts.aliyuncs.com".to_owned(),
+        }
+    }
+    /// 从RRSA注入的环境变量中读取配置，缺少任意一个时返回None
+    pub fn from_env() -> Option<Self> {
+        Some(OidcRoleProvider::new(
+            env::var("ALIBABA_CLOUD_ROLE_ARN").ok()?,
+            env::var("ALIBABA_CLOUD_OIDC_PROVIDER_ARN").ok()?,
+            env::var("ALIBABA_CLOUD_OIDC_TOKEN_FILE").ok()?,
+        ))
+    }
+    /// 设置角色会话名称，用于区分不同的令牌使用者
+    pub fn set_session_name(mut self, session_name: impl ToString) -> Self {
+        self.session_name = session_name.to_string();
+        self
+    }
+    /// 设置临时凭证的有效期，默认1小时，最小15分钟，最大不超过角色的最大会话时间
+    pub fn set_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration.max(Duration::from_secs(900));
+        self
+    }
+    /// 设置权限策略，进一步限制临时凭证的权限，可以使用 policy::Policy::to_json() 生成
+    pub fn set_policy(mut self, policy: impl ToString) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+    /// 设置STS的Endpoint，默认为 sts.aliyuncs.com ，可以使用 sts-vpc.cn-hangzhou.aliyuncs.com 等VPC地址
+    pub fn set_sts_endpoint(mut self, sts_endpoint: impl ToString) -> Self {
+        self.sts_endpoint = sts_endpoint.to_string();
+        self
+    }
+}
+impl CredentialsProvider for OidcRoleProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        Box::pin(async move {
+            let oidc_token = tokio::fs::read_to_string(&self.oidc_token_file).await?;
+            let mut params = vec![
+                ("Action", "AssumeRoleWithOIDC".to_owned()),
+                ("Format", "JSON".to_owned()),
+                ("Version", "2015-04-01".to_owned()),
+                (
+                    "Timestamp",
+                    Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                ),
+                ("RoleArn", self.role_arn.clone()),
+                ("OIDCProviderArn", self.oidc_provider_arn.clone()),
+                ("OIDCToken", oidc_token.trim().to_owned()),
+                ("RoleSessionName", self.session_name.clone()),
+                ("DurationSeconds", self.duration.as_secs().to_string()),
+            ];
+            if let Some(policy) = &self.policy {
+                params.push(("Policy", policy.clone()));
+            }
+            let body = params
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, uri_encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri(format!("https://{}/", self.sts_endpoint))
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(body))?;
+            match fetch(request, Duration::from_secs(10)).await? {
+                (200, body) => parse_credentials(&body),
+                (status, body) => Err(Error::CredentialsError(format!(
+                    "AssumeRoleWithOIDC失败，HTTP状态码：{}，{}",
+                    status,
+                    json_value(&body, "Message").unwrap_or(body)
+                ))),
+            }
+        })
+    }
+}
@@ -0,0 +1,141 @@
+use super::{json_key, json_value, Credentials};
+use crate::Error;
+use std::{env, path::PathBuf};
+
+/// aliyun命令行工具的配置
+///
+/// 读取 ~/.aliyun/config.json 中的配置，支持 AK、StsToken、EcsRamRole 三种凭证模式
+///
+/// 未指定配置名称时，依次使用环境变量 ALIBABA_CLOUD_PROFILE、配置文件中的当前配置、default
+///
+/// ```
+/// use aliyun_oss_rs::credentials::Profile;
+///
+/// let profile = Profile::load(Some("prod"))?;
+/// println!("{:?}", profile.region_id());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Profile {
+    name: String,
+    mode: String,
+    region_id: Option<String>,
+    pub(crate) credentials: Option<Credentials>,
+    pub(crate) ram_role_name: Option<String>,
+}
+impl Profile {
+    /// 读取默认位置的配置文件，配置文件不存在、配置不存在或者凭证模式不支持时返回错误
+    pub fn load(name: Option<&str>) -> Result<Self, Error> {
+        let home = env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .ok_or_else(|| Error::CredentialsError("无法确定用户目录".to_owned()))?;
+        let path = PathBuf::from(home).join(".aliyun").join("config.json");
+        Profile::load_from(path, name)
+    }
+    /// 读取指定的配置文件
+    pub fn load_from(path: impl Into<PathBuf>, name: Option<&str>) -> Result<Self, Error> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            Error::CredentialsError(format!("读取配置文件{}失败：{}", path.display(), e))
+        })?;
+        let name = match name {
+            Some(name) => name.to_owned(),
+            None => env::var("ALIBABA_CLOUD_PROFILE")
+                .ok()
+                .or_else(|| json_value(&content, "current").filter(|v| !v.is_empty()))
+                .unwrap_or_else(|| "default".to_owned()),
+        };
+        let profile = json_objects(&content, "profiles")
+            .into_iter()
+            .find(|v| json_value(v, "name").as_deref() == Some(&name))
+            .ok_or_else(|| Error::CredentialsError(format!("配置不存在：{}", name)))?;
+        let field = |key: &str| json_value(profile, key).filter(|v| !v.is_empty());
+        let mode = field("mode").unwrap_or_else(|| "AK".to_owned());
+        let mut credentials = None;
+        let mut ram_role_name = None;
+        match mode.as_str() {
+            "AK" | "StsToken" => {
+                let (access_key_id, access_key_secret) =
+                    match (field("access_key_id"), field("access_key_secret")) {
+                        (Some(id), Some(secret)) => (id, secret),
+                        _ => {
+                            return Err(Error::CredentialsError(format!(
+                                "配置{}缺少access_key_id或access_key_secret",
+                                name
+                            )))
+                        }
+                    };
+                let mut value = Credentials::new(access_key_id, access_key_secret);
+                if mode == "StsToken" {
+                    value.security_token = field("sts_token");
+                }
+                credentials = Some(value);
+            }
+            "EcsRamRole" => ram_role_name = field("ram_role_name"),
+            mode => {
+                return Err(Error::CredentialsError(format!(
+                    "不支持的凭证模式：{}",
+                    mode
+                )))
+            }
+        }
+        Ok(Profile {
+            name,
+            region_id: field("region_id"),
+            mode,
+            credentials,
+            ram_role_name,
+        })
+    }
+    /// 配置名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// 凭证模式，如 AK、StsToken、EcsRamRole
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+    /// 默认地域，如 cn-hangzhou
+    pub fn region_id(&self) -> Option<&str> {
+        self.region_id.as_deref()
+    }
+}
+
+//读取JSON中数组字段的所有对象，返回每个对象的原始内容
+fn json_objects<'a>(json: &'a str, key: &str) -> Vec<&'a str> {
+    let mut objects = Vec::new();
+    let start = match json_key(json, key) {
+        Some(start) => start,
+        None => return objects,
+    };
+    let (mut depth, mut object_start, mut in_string, mut escaped) = (0, 0, false, false);
+    for (i, c) in json[start..].char_indices() {
+        let i = start + i;
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    object_start = i;
+                }
+                depth += 1;
+            }
+            '}' | ']' if depth == 0 => break,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    objects.push(&json[object_start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    objects
+}
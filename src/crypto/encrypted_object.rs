@@ -0,0 +1,209 @@
+use super::MasterCipher;
+use crate::{
+    error::normal_error,
+    request::{Oss, OssRequest},
+    Error, OssObject,
+};
+use base64::{engine::general_purpose, Engine};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use hyper::{header, Body, HeaderMap, Method};
+use openssl::{
+    rand::rand_bytes,
+    symm::{Cipher, Crypter, Mode},
+};
+use std::{io, path::Path, pin::Pin, sync::Arc};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncWriteExt, BufReader, BufWriter},
+};
+use tokio_util::io::ReaderStream;
+
+const META_KEY: &str = "x-oss-meta-client-side-encryption-key";
+const META_START: &str = "x-oss-meta-client-side-encryption-start";
+const META_CEK_ALG: &str = "x-oss-meta-client-side-encryption-cek-alg";
+const META_WRAP_ALG: &str = "x-oss-meta-client-side-encryption-wrap-alg";
+const META_MATDESC: &str = "x-oss-meta-client-side-encryption-matdesc";
+const META_CONTENT_LENGTH: &str = "x-oss-meta-client-side-encryption-unencrypted-content-length";
+const CEK_ALG: &str = "AES/CTR/NoPadding";
+
+/// 客户端加密的文件
+///
+/// 上传时使用随机生成的数据密钥加密内容，下载时读取元信息中的数据密钥解密内容，加解密过程对调用方透明
+///
+/// 加密后的文件不支持范围下载、追加上传
+///
+/// ```
+/// let master_key = RsaMasterKey::new(PUBLIC_KEY, Some(PRIVATE_KEY))?;
+/// let object = EncryptedObject::new(&bucket.object("secret.txt"), Arc::new(master_key));
+/// object.put_content(b"hello".to_vec()).await?;
+/// let content = object.get_content().await?;
+/// ```
+pub struct EncryptedObject {
+    oss: Oss,
+    cipher: Arc<dyn MasterCipher>,
+}
+impl EncryptedObject {
+    pub fn new(object: &OssObject, cipher: Arc<dyn MasterCipher>) -> Self {
+        EncryptedObject {
+            oss: object.oss.clone(),
+            cipher,
+        }
+    }
+    /// 加密内存中的数据并上传到OSS
+    ///
+    pub async fn put_content(&self, content: Vec<u8>) -> Result<(), Error> {
+        let content_size = content.len() as u64;
+        let stream = futures_util::stream::once(async move { Ok::<_, io::Error>(content.into()) });
+        self.put_stream(stream, content_size).await
+    }
+    /// 加密磁盘中的文件并上传到OSS
+    ///
+    /// 文件大小不能超过 5GB
+    pub async fn put_file(&self, file: impl ToString) -> Result<(), Error> {
+        let file = File::open(file.to_string()).await?;
+        let file_size = file.metadata().await?.len();
+        if file_size >= 5_368_709_120 {
+            return Err(Error::InvalidFileSize);
+        }
+        let buf = BufReader::with_capacity(self.oss.buffer_size, file);
+        let stream = ReaderStream::with_capacity(buf, self.oss.chunk_size);
+        self.put_stream(stream, file_size).await
+    }
+    /// 下载文件并解密，直接将内容返回
+    ///
+    /// 如果文件较大，此方法可能占用过多内存，谨慎使用
+    pub async fn get_content(&self) -> Result<Bytes, Error> {
+        let mut stream = self.download_to_stream().await?;
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk?);
+        }
+        Ok(content.into())
+    }
+    /// 下载文件并解密，保存到磁盘
+    ///
+    /// 不支持网络路径，如果需要保存到smb\nfs等网络存储，请先挂载到本地，再使用本地路径地址
+    pub async fn download_to_file(&self, save_path: &str) -> Result<(), Error> {
+        //判断路径
+        if save_path.contains("://") {
+            return Err(Error::PathNotSupported);
+        }
+        let mut stream = self.download_to_stream().await?;
+        //创建目录
+        if let Some(dir) = Path::new(save_path).parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        //创建文件
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(save_path)
+            .await?;
+        let mut writer = BufWriter::with_capacity(self.oss.buffer_size, file);
+        while let Some(chunk) = stream.next().await {
+            writer.write_all(&chunk?).await?;
+        }
+        writer.flush().await?;
+        Ok(())
+    }
+    /// 下载文件，返回解密后的数据流
+    ///
+    pub async fn download_to_stream(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>, Error> {
+        let req = OssRequest::new(self.oss.clone(), Method::GET);
+        let response = req.send_to_oss()?.await?;
+        if !response.status().is_success() {
+            return Err(normal_error(response).await);
+        }
+        let mut crypter = self.decrypter(response.headers())?;
+        let stream = response.into_body().map(move |item| {
+            let chunk = item?;
+            let mut buf = vec![0; chunk.len() + 16];
+            let len = crypter
+                .update(&chunk, &mut buf)
+                .map_err(|e| Error::CryptoError(e.to_string()))?;
+            buf.truncate(len);
+            Ok(Bytes::from(buf))
+        });
+        Ok(Box::pin(stream))
+    }
+    //加密数据流并上传
+    async fn put_stream<S>(&self, stream: S, content_size: u64) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
+    {
+        let mut req = OssRequest::new(self.oss.clone(), Method::PUT);
+        //生成数据密钥和初始向量，初始向量的8-11字节置0，与官方SDK保持一致
+        let mut key = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand_bytes(&mut key).map_err(|e| Error::CryptoError(e.to_string()))?;
+        rand_bytes(&mut iv).map_err(|e| Error::CryptoError(e.to_string()))?;
+        iv[8..12].fill(0);
+        //写入加密信息
+        req.insert_header(
+            META_KEY,
+            general_purpose::STANDARD.encode(self.cipher.encrypt(&key)?),
+        );
+        req.insert_header(
+            META_START,
+            general_purpose::STANDARD.encode(self.cipher.encrypt(&iv)?),
+        );
+        req.insert_header(META_CEK_ALG, CEK_ALG);
+        req.insert_header(META_WRAP_ALG, self.cipher.wrap_algorithm());
+        let mat_desc = self.cipher.mat_desc();
+        if !mat_desc.is_empty() {
+            req.insert_header(META_MATDESC, mat_desc);
+        }
+        req.insert_header(META_CONTENT_LENGTH, content_size);
+        //文件类型
+        let content_type = mime_guess::from_path(self.oss.object.as_deref().unwrap_or_default())
+            .first()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_owned());
+        req.insert_header(header::CONTENT_TYPE, content_type);
+        //CTR模式下，密文长度与明文一致
+        req.insert_header(header::CONTENT_LENGTH, content_size);
+        let mut crypter = Crypter::new(Cipher::aes_256_ctr(), Mode::Encrypt, &key, Some(&iv))
+            .map_err(|e| Error::CryptoError(e.to_string()))?;
+        let body = Body::wrap_stream(stream.map(move |item| {
+            let chunk = item?;
+            let mut buf = vec![0; chunk.len() + 16];
+            let len = crypter.update(&chunk, &mut buf).map_err(io::Error::other)?;
+            buf.truncate(len);
+            Ok::<_, io::Error>(Bytes::from(buf))
+        }));
+        req.set_body(body);
+        let response = req.send_to_oss()?.await?;
+        match response.status() {
+            code if code.is_success() => Ok(()),
+            _ => Err(normal_error(response).await),
+        }
+    }
+    //读取元信息中的加密信息，生成解密器
+    fn decrypter(&self, headers: &HeaderMap) -> Result<Crypter, Error> {
+        let get = |key: &str| {
+            headers
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| Error::CryptoError(format!("文件缺少加密信息：{}", key)))
+        };
+        let cek_alg = get(META_CEK_ALG)?;
+        if cek_alg != CEK_ALG {
+            return Err(Error::CryptoError(format!("不支持的加密算法：{}", cek_alg)));
+        }
+        let decode = |value: &str| {
+            general_purpose::STANDARD
+                .decode(value)
+                .map_err(|e| Error::CryptoError(e.to_string()))
+        };
+        let key = self.cipher.decrypt(&decode(get(META_KEY)?)?)?;
+        let iv = self.cipher.decrypt(&decode(get(META_START)?)?)?;
+        if key.len() != 32 || iv.len() != 16 {
+            return Err(Error::CryptoError("数据密钥或初始向量长度错误".to_owned()));
+        }
+        Crypter::new(Cipher::aes_256_ctr(), Mode::Decrypt, &key, Some(&iv))
+            .map_err(|e| Error::CryptoError(e.to_string()))
+    }
+}
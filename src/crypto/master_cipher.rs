@@ -0,0 +1,88 @@
+use crate::Error;
+use openssl::{
+    pkey::{Private, Public},
+    rsa::{Padding, Rsa},
+};
+
+/// 主密钥，用于加密和解密每个文件的数据密钥
+///
+/// 使用KMS托管主密钥时，可以实现此trait，在encrypt和decrypt中调用KMS的Encrypt和Decrypt接口，wrap_algorithm返回 KMS/ALICLOUD
+pub trait MasterCipher: Send + Sync {
+    /// 数据密钥的加密算法，写入 x-oss-meta-client-side-encryption-wrap-alg
+    fn wrap_algorithm(&self) -> String;
+    /// 主密钥的描述信息，JSON格式，写入 x-oss-meta-client-side-encryption-matdesc ，为空时不写入
+    fn mat_desc(&self) -> String {
+        String::new()
+    }
+    /// 加密数据密钥
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Error>;
+    /// 解密数据密钥
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// RSA主密钥
+///
+/// 使用PKCS1填充加密数据密钥，与官方SDK的 RSA/NONE/PKCS1Padding 一致
+pub struct RsaMasterKey {
+    public_key: Rsa<Public>,
+    private_key: Option<Rsa<Private>>,
+    mat_desc: String,
+}
+impl RsaMasterKey {
+    /// 使用PEM格式的公钥和私钥初始化
+    ///
+    /// 只需要上传文件时，可以不提供私钥
+    pub fn new(public_key: &str, private_key: Option<&str>) -> Result<Self, Error> {
+        let public_key = Rsa::public_key_from_pem(public_key.as_bytes())
+            .or_else(|_| Rsa::public_key_from_pem_pkcs1(public_key.as_bytes()))
+            .map_err(|e| Error::CryptoError(e.to_string()))?;
+        let private_key = match private_key {
+            Some(private_key) => Some(
+                Rsa::private_key_from_pem(private_key.as_bytes())
+                    .map_err(|e| Error::CryptoError(e.to_string()))?,
+            ),
+            None => None,
+        };
+        Ok(RsaMasterKey {
+            public_key,
+            private_key,
+            mat_desc: String::new(),
+        })
+    }
+    /// 设置主密钥的描述信息，JSON格式
+    ///
+    /// 用于在下载时区分文件使用的主密钥
+    pub fn set_mat_desc(mut self, mat_desc: impl ToString) -> Self {
+        self.mat_desc = mat_desc.to_string();
+        self
+    }
+}
+impl MasterCipher for RsaMasterKey {
+    fn wrap_algorithm(&self) -> String {
+        "RSA/NONE/PKCS1Padding".to_owned()
+    }
+    fn mat_desc(&self) -> String {
+        self.mat_desc.clone()
+    }
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![0; self.public_key.size() as usize];
+        let len = self
+            .public_key
+            .public_encrypt(data, &mut buf, Padding::PKCS1)
+            .map_err(|e| Error::CryptoError(e.to_string()))?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let private_key = self
+            .private_key
+            .as_ref()
+            .ok_or_else(|| Error::CryptoError("未设置RSA私钥".to_owned()))?;
+        let mut buf = vec![0; private_key.size() as usize];
+        let len = private_key
+            .private_decrypt(data, &mut buf, Padding::PKCS1)
+            .map_err(|e| Error::CryptoError(e.to_string()))?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
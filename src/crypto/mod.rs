@@ -0,0 +1,13 @@
+//! 客户端加密，与官方SDK的客户端加密格式兼容
+//!
+//! 每个文件使用随机生成的数据密钥，通过AES-256-CTR加密内容；数据密钥和初始向量使用主密钥加密后，保存在文件的 x-oss-meta-client-side-encryption-* 元信息中
+//!
+//! 需要开启 crypto 特性
+
+pub use self::{
+    encrypted_object::EncryptedObject,
+    master_cipher::{MasterCipher, RsaMasterKey},
+};
+
+mod encrypted_object;
+mod master_cipher;
@@ -0,0 +1,101 @@
+//! DNS缓存
+//!
+//! 在有效期内直接使用缓存的解析结果，避免每次建立连接都查询DNS
+
+use futures_util::Future;
+use hyper::{
+    client::connect::dns::{GaiResolver, Name},
+    service::Service,
+};
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+    vec,
+};
+
+//域名解析结果的缓存，同一个OssClient共享
+#[derive(Debug)]
+pub(crate) struct DnsCache {
+    ttl: Duration,
+    addrs: Mutex<HashMap<String, (Instant, Vec<SocketAddr>)>>,
+}
+impl DnsCache {
+    pub fn new(ttl: Duration) -> Self {
+        DnsCache {
+            ttl,
+            addrs: Mutex::new(HashMap::new()),
+        }
+    }
+    //清空缓存
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+    fn get(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let mut addrs = self.lock();
+        match addrs.get(host) {
+            Some((created, cached)) if created.elapsed() < self.ttl => Some(cached.clone()),
+            Some(_) => {
+                addrs.remove(host);
+                None
+            }
+            None => None,
+        }
+    }
+    fn insert(&self, host: String, resolved: Vec<SocketAddr>) {
+        self.lock().insert(host, (Instant::now(), resolved));
+    }
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, (Instant, Vec<SocketAddr>)>> {
+        match self.addrs.lock() {
+            Ok(addrs) => addrs,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+//带缓存的域名解析，未开启缓存时直接使用系统解析
+#[derive(Debug, Clone)]
+pub(crate) struct DnsResolver {
+    inner: GaiResolver,
+    cache: Option<Arc<DnsCache>>,
+}
+impl DnsResolver {
+    pub fn new(cache: Option<Arc<DnsCache>>) -> Self {
+        DnsResolver {
+            inner: GaiResolver::new(),
+            cache,
+        }
+    }
+}
+
+impl Service<Name> for DnsResolver {
+    type Response = vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, io::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let host = name.as_str().to_owned();
+        if let Some(resolved) = self.cache.as_ref().and_then(|cache| cache.get(&host)) {
+            return Box::pin(async move { Ok(resolved.into_iter()) });
+        }
+        let resolving = self.inner.call(name);
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let resolved = resolving.await?.collect::<Vec<_>>();
+            if let Some(cache) = cache {
+                if !resolved.is_empty() {
+                    cache.insert(host, resolved.clone());
+                }
+            }
+            Ok(resolved.into_iter())
+        })
+    }
+}
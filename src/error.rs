@@ -1,6 +1,8 @@
+use base64::{engine::general_purpose, Engine};
 use bytes::Bytes;
-use hyper::{body::to_bytes, Body, Response};
-use serde_derive::Deserialize;
+use hyper::{body::to_bytes, Body, Response, StatusCode};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,41 +16,416 @@ pub enum Error {
     #[error("{0}")]
     HttpError(#[from] hyper::http::Error),
     #[error("{0}")]
-    HyperError(#[from] hyper::Error),
+    HyperError(hyper::Error),
     #[error("OSS返回了成功，但消息体结构解析失败，请尝试自行解析")]
     OssInvalidResponse(Option<Bytes>),
     #[error("{0} \n {1:#?}")]
-    OssError(hyper::StatusCode, OssError),
+    OssError(hyper::StatusCode, Box<OssError>),
     #[error("OSS返回了错误，HTTP状态码：{0}，错误内容请自行解析")]
     OssInvalidError(hyper::StatusCode, Bytes),
     #[error("使用了不符合要求的字符")]
     InvalidCharacter,
+    #[error("标签不符合要求：{0}")]
+    InvalidTag(String),
+    #[error("文件路径不符合要求：{0}")]
+    InvalidKey(String),
+    #[error("存储空间名称不符合要求：{0}")]
+    InvalidBucketName(String),
+    #[error("元信息不符合要求：{0}")]
+    InvalidMetadata(String),
+    #[error("数据校验失败，本地：{0}，OSS：{1}")]
+    ChecksumMismatch(String, String),
+    #[error("文件已上传，但上传回调失败：{error:#?}")]
+    CallbackFailed {
+        /// 文件的ETag
+        e_tag: String,
+        /// 文件的版本ID，仅在开启了版本控制时返回
+        version_id: Option<String>,
+        /// OSS返回的回调失败信息
+        error: Box<OssError>,
+    },
+    #[error("上传已经结束或已经失败，无法继续写入")]
+    UploadClosed,
+    #[error("Endpoint处于熔断状态，请求未发送：{0}")]
+    CircuitOpen(String),
+    #[error("操作已取消")]
+    Cancelled,
+    #[error("等待超时")]
+    Timeout,
+    #[error("加解密失败：{0}")]
+    CryptoError(String),
+    #[error("使用V4签名时无法从Endpoint推断地域：{0}，请通过 set_region 设置")]
+    MissingRegion(String),
+    #[error("获取访问凭证失败：{0}")]
+    CredentialsError(String),
+    #[error("访问日志格式不符合要求：{0}")]
+    InvalidAccessLog(String),
 }
 
-#[derive(Debug, Deserialize)]
+impl Error {
+    /// OSS返回的HTTP状态码，非OSS返回的错误为None
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::OssError(status_code, _) | Error::OssInvalidError(status_code, _) => {
+                Some(*status_code)
+            }
+            Error::CallbackFailed { .. } => Some(StatusCode::NON_AUTHORITATIVE_INFORMATION),
+            _ => None,
+        }
+    }
+    /// OSS返回的错误详情
+    pub fn oss_error(&self) -> Option<&OssError> {
+        match self {
+            Error::OssError(_, oss_error) => Some(oss_error),
+            Error::CallbackFailed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+    /// OSS返回的错误码，如 NoSuchKey
+    pub fn oss_code(&self) -> Option<&str> {
+        self.oss_error().map(|e| e.code.as_str())
+    }
+    /// OSS返回的错误码，解析为 OssErrorCode
+    pub fn error_code(&self) -> Option<OssErrorCode> {
+        self.oss_error().map(OssError::error_code)
+    }
+    /// OSS返回的请求ID，可用于向阿里云提交工单排查问题
+    pub fn request_id(&self) -> Option<&str> {
+        self.oss_error()
+            .map(|e| e.request_id.as_str())
+            .filter(|v| !v.is_empty())
+    }
+    /// 文件、存储空间等资源不存在
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+    /// 没有访问权限，包括签名错误
+    pub fn is_access_denied(&self) -> bool {
+        self.status() == Some(StatusCode::FORBIDDEN)
+    }
+    /// 请求被OSS限流，包括429、503状态码以及限流相关的错误码
+    pub fn is_throttled(&self) -> bool {
+        matches!(
+            self.status(),
+            Some(StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE)
+        ) || matches!(
+            self.oss_code(),
+            Some("SlowDown" | "Throttling" | "TooManyRequests" | "QpsLimitExceeded")
+        )
+    }
+    /// 是否为临时性错误，可以重试
+    ///
+    /// 包括网络错误、超时、数据校验失败、限流以及OSS返回的5xx错误
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::HyperError(_) | Error::ChecksumMismatch(..) | Error::Timeout => true,
+            Error::OssError(status_code, _) | Error::OssInvalidError(status_code, _) => {
+                status_code.is_server_error() || self.is_throttled()
+            }
+            _ => false,
+        }
+    }
+}
+
+//读取响应body时的超时等错误由本库产生，经hyper包装后返回，还原为原始错误
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        if !std::error::Error::source(&e).is_some_and(|v| v.is::<Error>()) {
+            return Error::HyperError(e);
+        }
+        match e.into_cause().map(|v| v.downcast::<Error>()) {
+            Some(Ok(e)) => *e,
+            _ => Error::Timeout,
+        }
+    }
+}
+
+/// 转换为 std::io::Error ，用于 AsyncRead、AsyncWrite 等通用的IO代码
+///
+/// 根据错误类型设置 ErrorKind ，原始错误可以通过 io::Error::into_inner() 取回
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        use std::io::ErrorKind;
+        if let Error::IoError(e) = e {
+            return e;
+        }
+        let kind = match &e {
+            Error::Timeout => ErrorKind::TimedOut,
+            Error::PathNotSupported
+            | Error::InvalidFileSize
+            | Error::InvalidCharacter
+            | Error::InvalidTag(_)
+            | Error::InvalidKey(_)
+            | Error::InvalidBucketName(_)
+            | Error::InvalidMetadata(_)
+            | Error::MissingRegion(_) => ErrorKind::InvalidInput,
+            Error::ChecksumMismatch(..)
+            | Error::OssInvalidResponse(_)
+            | Error::InvalidAccessLog(_) => ErrorKind::InvalidData,
+            Error::UploadClosed => ErrorKind::BrokenPipe,
+            Error::Cancelled => ErrorKind::Interrupted,
+            _ if e.is_not_found() => ErrorKind::NotFound,
+            _ if e.is_access_denied() => ErrorKind::PermissionDenied,
+            _ if e.oss_code() == Some("FileAlreadyExists") => ErrorKind::AlreadyExists,
+            _ => ErrorKind::Other,
+        };
+        std::io::Error::new(kind, e)
+    }
+}
+
+/// OSS常见的错误码
+///
+/// ```
+/// match object.get_object().download().await {
+///     Err(e) if e.error_code() == Some(OssErrorCode::NoSuchKey) => println!("文件不存在"),
+///     other => println!("{:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OssErrorCode {
+    /// 没有访问权限
+    AccessDenied,
+    /// 存储空间已经存在
+    BucketAlreadyExists,
+    /// 存储空间不为空，无法删除
+    BucketNotEmpty,
+    /// 文件过大
+    EntityTooLarge,
+    /// 文件过小
+    EntityTooSmall,
+    /// 文件已经存在，禁止覆盖写时返回
+    FileAlreadyExists,
+    /// 服务端内部错误
+    InternalError,
+    /// AccessKey ID不存在
+    InvalidAccessKeyId,
+    /// 参数不符合要求
+    InvalidArgument,
+    /// Content-MD5与上传的内容不匹配
+    InvalidDigest,
+    /// 存储空间名称不符合要求
+    InvalidBucketName,
+    /// 文件路径不符合要求
+    InvalidObjectName,
+    /// 归档文件未解冻，或解冻状态不符合要求
+    InvalidObjectState,
+    /// 分片上传时分片不存在或ETag不匹配
+    InvalidPart,
+    /// 请求的范围不符合要求
+    InvalidRange,
+    /// 请求body中的XML格式不符合要求
+    MalformedXML,
+    /// 请求方法不被允许
+    MethodNotAllowed,
+    /// 存储空间不存在
+    NoSuchBucket,
+    /// 文件不存在
+    NoSuchKey,
+    /// 分片上传事件不存在
+    NoSuchUpload,
+    /// OSS不支持此操作
+    NotImplemented,
+    /// 追加上传的目标文件不是Appendable类型
+    ObjectNotAppendable,
+    /// 追加上传的位置与文件当前长度不一致
+    PositionNotEqualToLength,
+    /// 条件请求不满足
+    PreconditionFailed,
+    /// 请求频率超过限制
+    QpsLimitExceeded,
+    /// 请求超时
+    RequestTimeout,
+    /// 请求时间与服务器时间相差过大
+    RequestTimeTooSkewed,
+    /// 签名不匹配
+    SignatureDoesNotMatch,
+    /// STS临时凭证已过期
+    SecurityTokenExpired,
+    /// 服务不可用
+    ServiceUnavailable,
+    /// 请求被限流，需要降低请求频率
+    SlowDown,
+    /// 其他错误码
+    Other(String),
+}
+
+const KNOWN_ERROR_CODES: [(OssErrorCode, &str); 31] = [
+    (OssErrorCode::AccessDenied, "AccessDenied"),
+    (OssErrorCode::BucketAlreadyExists, "BucketAlreadyExists"),
+    (OssErrorCode::BucketNotEmpty, "BucketNotEmpty"),
+    (OssErrorCode::EntityTooLarge, "EntityTooLarge"),
+    (OssErrorCode::EntityTooSmall, "EntityTooSmall"),
+    (OssErrorCode::FileAlreadyExists, "FileAlreadyExists"),
+    (OssErrorCode::InternalError, "InternalError"),
+    (OssErrorCode::InvalidAccessKeyId, "InvalidAccessKeyId"),
+    (OssErrorCode::InvalidArgument, "InvalidArgument"),
+    (OssErrorCode::InvalidDigest, "InvalidDigest"),
+    (OssErrorCode::InvalidBucketName, "InvalidBucketName"),
+    (OssErrorCode::InvalidObjectName, "InvalidObjectName"),
+    (OssErrorCode::InvalidObjectState, "InvalidObjectState"),
+    (OssErrorCode::InvalidPart, "InvalidPart"),
+    (OssErrorCode::InvalidRange, "InvalidRange"),
+    (OssErrorCode::MalformedXML, "MalformedXML"),
+    (OssErrorCode::MethodNotAllowed, "MethodNotAllowed"),
+    (OssErrorCode::NoSuchBucket, "NoSuchBucket"),
+    (OssErrorCode::NoSuchKey, "NoSuchKey"),
+    (OssErrorCode::NoSuchUpload, "NoSuchUpload"),
+    (OssErrorCode::NotImplemented, "NotImplemented"),
+    (OssErrorCode::ObjectNotAppendable, "ObjectNotAppendable"),
+    (
+        OssErrorCode::PositionNotEqualToLength,
+        "PositionNotEqualToLength",
+    ),
+    (OssErrorCode::PreconditionFailed, "PreconditionFailed"),
+    (OssErrorCode::QpsLimitExceeded, "QpsLimitExceeded"),
+    (OssErrorCode::RequestTimeout, "RequestTimeout"),
+    (OssErrorCode::RequestTimeTooSkewed, "RequestTimeTooSkewed"),
+    (OssErrorCode::SignatureDoesNotMatch, "SignatureDoesNotMatch"),
+    (OssErrorCode::SecurityTokenExpired, "SecurityTokenExpired"),
+    (OssErrorCode::ServiceUnavailable, "ServiceUnavailable"),
+    (OssErrorCode::SlowDown, "SlowDown"),
+];
+
+impl OssErrorCode {
+    /// 错误码字符串
+    pub fn as_str(&self) -> &str {
+        match self {
+            OssErrorCode::Other(code) => code,
+            code => KNOWN_ERROR_CODES
+                .iter()
+                .find(|(known, _)| known == code)
+                .map(|(_, code)| *code)
+                .unwrap_or_default(),
+        }
+    }
+}
+impl fmt::Display for OssErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl From<&str> for OssErrorCode {
+    fn from(code: &str) -> Self {
+        KNOWN_ERROR_CODES
+            .iter()
+            .find(|(_, known)| *known == code)
+            .map(|(code, _)| code.clone())
+            .unwrap_or_else(|| OssErrorCode::Other(code.to_owned()))
+    }
+}
+
+/// OSS返回的错误详情
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename = "Error")]
 pub struct OssError {
+    /// 错误码
     #[serde(rename = "Code")]
     pub code: String,
+    /// 错误描述
     #[serde(rename = "Message")]
     pub message: String,
+    /// 请求ID
     #[serde(rename = "RequestId")]
     pub request_id: String,
-    #[serde(rename = "EC")]
+    /// 错误码EC，可在阿里云文档中查询具体的错误原因
+    #[serde(rename = "EC", default)]
     pub ec: String,
+    /// OSS返回的原始错误内容，HEAD请求时为响应头 x-oss-err 解码后的内容
+    #[serde(skip)]
+    pub raw: Bytes,
+}
+
+impl OssError {
+    /// 错误码，解析为 OssErrorCode
+    ///
+    /// ```
+    /// match object.get_object().download().await {
+    ///     Err(Error::OssError(_, e)) => match e.error_code() {
+    ///         OssErrorCode::NoSuchKey => println!("文件不存在"),
+    ///         OssErrorCode::AccessDenied => println!("没有访问权限"),
+    ///         code => println!("其他错误：{}", code),
+    ///     },
+    ///     other => println!("{:?}", other),
+    /// }
+    /// ```
+    pub fn error_code(&self) -> OssErrorCode {
+        OssErrorCode::from(self.code.as_str())
+    }
 }
 
+//解析上传回调失败时的响应，OSS返回203状态码，但文件已经上传成功
+pub(crate) async fn callback_failed_error(response: Response<Body>) -> Error {
+    let headers = response.headers();
+    let e_tag = headers
+        .get("ETag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_matches('"').to_owned())
+        .unwrap_or_default();
+    let version_id = headers
+        .get("x-oss-version-id")
+        .and_then(|v| v.to_str().ok().map(|s| s.to_owned()));
+    let request_id = headers
+        .get("x-oss-request-id")
+        .and_then(|v| v.to_str().ok().map(|s| s.to_owned()))
+        .unwrap_or_default();
+    let response_bytes = match to_bytes(response.into_body()).await {
+        Ok(response_bytes) => response_bytes,
+        Err(e) => return Error::HyperError(e),
+    };
+    let error = match serde_xml_rs::from_reader::<&[u8], OssError>(&*response_bytes) {
+        Ok(error) => OssError {
+            raw: response_bytes,
+            ..error
+        },
+        Err(_) => OssError {
+            code: "CallbackFailed".to_owned(),
+            message: String::from_utf8_lossy(&response_bytes).into_owned(),
+            request_id,
+            ec: String::new(),
+            raw: response_bytes,
+        },
+    };
+    Error::CallbackFailed {
+        e_tag,
+        version_id,
+        error: Box::new(error),
+    }
+}
+
+/// 解析OSS返回的错误
+///
+/// HEAD请求没有响应体，错误内容以base64编码放在响应头 x-oss-err 中；错误内容中缺少的请求ID和EC从响应头中补充
 pub async fn normal_error(response: Response<Body>) -> Error {
     let status_code = response.status();
-    let response_bytes = to_bytes(response.into_body()).await;
-    match response_bytes {
-        Err(e) => Error::HyperError(e),
-        Ok(response_bytes) => {
-            let oss_error = serde_xml_rs::from_reader::<&[u8], OssError>(&*response_bytes);
-            match oss_error {
-                Ok(oss_error) => Error::OssError(status_code, oss_error),
-                Err(_) => Error::OssInvalidError(status_code, response_bytes),
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_owned())
+            .unwrap_or_default()
+    };
+    let request_id = header("x-oss-request-id");
+    let ec = header("x-oss-ec");
+    let x_oss_err = general_purpose::STANDARD.decode(header("x-oss-err")).ok();
+    let response_bytes = match to_bytes(response.into_body()).await {
+        Ok(response_bytes) if response_bytes.is_empty() => {
+            x_oss_err.map(Bytes::from).unwrap_or_default()
+        }
+        Ok(response_bytes) => response_bytes,
+        Err(e) => return Error::HyperError(e),
+    };
+    match serde_xml_rs::from_reader::<&[u8], OssError>(&*response_bytes) {
+        Ok(mut oss_error) => {
+            if oss_error.request_id.is_empty() {
+                oss_error.request_id = request_id;
+            }
+            if oss_error.ec.is_empty() {
+                oss_error.ec = ec;
             }
+            oss_error.raw = response_bytes;
+            Error::OssError(status_code, Box::new(oss_error))
         }
+        Err(_) => Error::OssInvalidError(status_code, response_bytes),
     }
 }
@@ -0,0 +1,124 @@
+//! 文件路径工具
+//!
+//! 拼接、规范化文件路径，并按照OSS的命名规则进行校验，避免格式错误的路径在请求时才由OSS返回难以理解的错误
+//!
+//! OSS的命名规则：
+//! - 使用UTF-8编码，长度为1-1023字节
+//! - 不能以 / 或者 \ 开头
+//! - 不能包含回车、换行等控制字符
+//!
+//! ```
+//! use aliyun_oss_rs::key;
+//!
+//! let key = key::join(["users/", "/1001", "avatar.png"])?;
+//! assert_eq!(key, "users/1001/avatar.png");
+//! assert_eq!(key::parent(&key), Some("users/1001/"));
+//! assert_eq!(key::file_name(&key), "avatar.png");
+//! ```
+
+use crate::Error;
+
+/// 文件路径的最大长度，单位字节
+pub const MAX_KEY_LEN: usize = 1023;
+
+/// 按照OSS的命名规则校验文件路径
+pub fn validate(key: &str) -> Result<(), Error> {
+    if key.is_empty() {
+        return Err(Error::InvalidKey("文件路径不能为空".to_owned()));
+    }
+    if key.len() > MAX_KEY_LEN {
+        return Err(Error::InvalidKey(format!(
+            "文件路径长度不能超过{}字节",
+            MAX_KEY_LEN
+        )));
+    }
+    if key.starts_with('/') || key.starts_with('\\') {
+        return Err(Error::InvalidKey(
+            "文件路径不能以 / 或者 \\ 开头".to_owned(),
+        ));
+    }
+    if key.chars().any(|c| c.is_control()) {
+        return Err(Error::InvalidKey("文件路径不能包含控制字符".to_owned()));
+    }
+    Ok(())
+}
+
+/// 规范化文件路径
+///
+/// 去除开头的 / ，合并连续的 / ，去除 . ，保留结尾的 / ；路径中存在 .. 时返回错误
+///
+/// 规范化后的路径会按照OSS的命名规则进行校验
+pub fn normalize(key: &str) -> Result<String, Error> {
+    join([key])
+}
+
+/// 拼接文件路径，各部分之间使用 / 连接，并对结果进行规范化
+///
+/// 最后一部分以 / 结尾时，结果也以 / 结尾
+pub fn join<I, S>(parts: I) -> Result<String, Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut segments = Vec::new();
+    let mut is_dir = false;
+    for part in parts {
+        let part = part.as_ref();
+        if part.is_empty() {
+            continue;
+        }
+        is_dir = part.ends_with('/');
+        for segment in part.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => return Err(Error::InvalidKey("文件路径中不能包含 ..".to_owned())),
+                segment => segments.push(segment.to_owned()),
+            }
+        }
+    }
+    let mut key = segments.join("/");
+    if is_dir && !key.is_empty() {
+        key.push('/');
+    }
+    validate(&key)?;
+    Ok(key)
+}
+
+/// 是否为目录，即以 / 结尾的路径
+pub fn is_dir(key: &str) -> bool {
+    key.ends_with('/')
+}
+
+/// 转换为目录前缀，不以 / 结尾时追加 / ，空字符串保持不变
+pub fn as_dir(prefix: &str) -> String {
+    match prefix.is_empty() || prefix.ends_with('/') {
+        true => prefix.to_owned(),
+        false => format!("{}/", prefix),
+    }
+}
+
+/// 获取所在目录，以 / 结尾；位于根目录时返回None
+///
+/// ```
+/// assert_eq!(key::parent("a/b/c.txt"), Some("a/b/"));
+/// assert_eq!(key::parent("a/b/"), Some("a/"));
+/// assert_eq!(key::parent("c.txt"), None);
+/// ```
+pub fn parent(key: &str) -> Option<&str> {
+    let trimmed = key.strip_suffix('/').unwrap_or(key);
+    trimmed.rfind('/').map(|index| &key[..=index])
+}
+
+/// 获取文件名，即最后一个 / 之后的部分；目录返回目录名，不包含结尾的 /
+pub fn file_name(key: &str) -> &str {
+    let trimmed = key.strip_suffix('/').unwrap_or(key);
+    match trimmed.rfind('/') {
+        Some(index) => &trimmed[index + 1..],
+        None => trimmed,
+    }
+}
+
+/// 去除前缀，返回相对路径；不以前缀开头时返回None
+pub fn strip_prefix<'a>(key: &'a str, prefix: &str) -> Option<&'a str> {
+    key.strip_prefix(prefix)
+}
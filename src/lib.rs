@@ -6,7 +6,17 @@
 //! #### 提醒
 //! - 暂不支持版本控制功能，如你的存储空间已经开启了版本控制，可能会出现功能和数据不全的情况
 //! - 暂不支持服务端加密的相关功能
-//! - 大部份方法的参数的字符合法性未进行校验，需要严格按照OSS要求传参，否则可能会产生本地或远程错误
+//! - 大部份方法的参数的字符合法性默认不进行校验，需要严格按照OSS要求传参，否则可能会产生本地或远程错误；可以通过 OssClient::enable_strict_validation() 开启发送前的参数校验
+//!
+//! #### 签名算法实现
+//! 通过feature选择HMAC-SHA1、SHA-256的实现，无法编译ring的平台可以关闭默认feature后切换：
+//! - backend-ring：使用ring，默认开启
+//! - backend-rustcrypto：使用纯Rust实现的RustCrypto
+//! - backend-openssl：使用OpenSSL，可以搭配通过FIPS认证的OpenSSL使用
+//!
+//! ```toml
+//! aliyun-oss-rs = { version = "0.0.5", default-features = false, features = ["backend-rustcrypto"] }
+//! ```
 //!
 //! ## 使用方法
 //! ##### 初始化
@@ -38,11 +48,18 @@
 //!
 //! ##### 获取文件访问地址
 //! ```
-//! use chrono::{Duration, Local};
+//! use chrono::Duration;
+//!
+//! let url = object.get_object_url().url(Duration::days(3))?;
 //!
-//! let date = Local::now().naive_local() + Duration::days(3);
-//! let url = object.get_object_url().url(date);
+//! ```
+//!
+//! ##### 同步调用
+//! 开启 blocking feature 后，通过 Wait::wait() 同步执行任意操作
+//! ```
+//! use aliyun_oss_rs::blocking::Wait;
 //!
+//! let result = object.put_object().send_file("Your File Path").wait();
 //! ```
 //!
 
@@ -51,13 +68,41 @@ pub use crate::bucket::OssBucket;
 #[doc(inline)]
 pub use crate::client::OssClient;
 #[doc(inline)]
-pub use crate::error::Error;
+pub use crate::error::{Error, OssError, OssErrorCode};
 #[doc(inline)]
 pub use crate::object::OssObject;
 
+pub mod access_log;
+mod backend;
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod bucket;
+mod cache;
+pub mod checksum;
 pub mod client;
 pub mod common;
+#[cfg(feature = "gzip")]
+mod compress;
+pub mod credentials;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+mod dns;
 mod error;
+pub mod key;
+pub mod lifecycle;
+pub mod mime;
 pub mod object;
+pub mod policy;
+pub mod progress;
+mod proxy;
 mod request;
+pub mod signer;
+#[cfg(feature = "object-store")]
+pub mod store;
+pub mod sync;
+mod trace;
+pub mod transfer;
+pub mod transport;
+pub mod validate;
+mod xml;
@@ -0,0 +1,290 @@
+//! 生命周期规则的预演
+//!
+//! 在应用新的生命周期规则之前，根据列举结果计算哪些文件会被删除或转换存储类型，以及执行的时间，用于检查规则是否符合预期
+//!
+//! ```
+//! use aliyun_oss_rs::lifecycle::{LifecycleActionKind, LifecycleConfiguration};
+//!
+//! let config = LifecycleConfiguration::from_xml(&std::fs::read_to_string("lifecycle.xml")?)?;
+//! let mut actions = config.dry_run(bucket.list_objects().set_prefix("log/").into_stream());
+//! while let Some(action) = actions.next().await {
+//!     let action = action?;
+//!     if action.action == LifecycleActionKind::Expire && action.is_due(Utc::now()) {
+//!         println!("{} 将被规则 {} 删除", action.key, action.rule_id);
+//!     }
+//! }
+//! ```
+//!
+//! 列举结果不包含文件的标签和最后访问时间，包含标签条件或基于最后访问时间的规则只能按前缀和大小匹配，
+//! 相应的结果会标记为 uncertain
+//!
+//! 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/oss/user-guide/lifecycle-rules-based-on-the-last-modified-time)
+
+use crate::{
+    bucket::ObjectInfo,
+    common::{OssDateTime, StorageClass},
+    Error,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use serde_derive::Deserialize;
+
+/// 生命周期配置，可以从GetBucketLifecycle返回的XML解析
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LifecycleConfiguration {
+    /// 规则列表
+    #[serde(rename = "Rule", default)]
+    pub rules: Vec<LifecycleRule>,
+}
+
+/// 生命周期规则
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleRule {
+    /// 规则ID
+    #[serde(rename = "ID", default)]
+    pub id: String,
+    /// 规则作用的文件前缀，为空时作用于整个存储空间
+    #[serde(default)]
+    pub prefix: String,
+    /// 规则状态，Enabled 或 Disabled
+    #[serde(default)]
+    pub status: String,
+    /// 文件需要同时具有的标签
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<LifecycleTag>,
+    /// 过滤条件
+    #[serde(default)]
+    pub filter: Option<LifecycleFilter>,
+    /// 过期删除
+    #[serde(default)]
+    pub expiration: Option<LifecycleExpiration>,
+    /// 转换存储类型
+    #[serde(rename = "Transition", default)]
+    pub transitions: Vec<LifecycleTransition>,
+}
+
+/// 标签
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleTag {
+    /// 标签的key
+    pub key: String,
+    /// 标签的value
+    #[serde(default)]
+    pub value: String,
+}
+
+/// 过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleFilter {
+    /// 只匹配大于此大小的文件，单位字节
+    #[serde(default)]
+    pub object_size_greater_than: Option<u64>,
+    /// 只匹配小于此大小的文件，单位字节
+    #[serde(default)]
+    pub object_size_less_than: Option<u64>,
+    /// 排除的文件
+    #[serde(rename = "Not", default)]
+    pub not: Vec<LifecycleNot>,
+}
+
+/// 排除条件，文件同时满足前缀和标签时被排除
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleNot {
+    /// 排除的文件前缀
+    #[serde(default)]
+    pub prefix: String,
+    /// 排除的标签
+    #[serde(default)]
+    pub tag: Option<LifecycleTag>,
+}
+
+/// 过期删除，天数和日期二选一
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleExpiration {
+    /// 最后修改时间之后多少天删除
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// 删除最后修改时间早于此日期的文件
+    #[serde(default)]
+    pub created_before_date: Option<OssDateTime>,
+}
+
+/// 转换存储类型，天数和日期二选一
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleTransition {
+    /// 最后修改（或访问）时间之后多少天转换
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// 转换最后修改时间早于此日期的文件
+    #[serde(default)]
+    pub created_before_date: Option<OssDateTime>,
+    /// 转换后的存储类型
+    pub storage_class: StorageClass,
+    /// 是否基于最后访问时间
+    #[serde(default)]
+    pub is_access_time: bool,
+}
+
+/// 生命周期规则对文件执行的操作类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleActionKind {
+    /// 删除文件
+    Expire,
+    /// 转换为指定的存储类型
+    Transition(StorageClass),
+}
+
+/// 生命周期规则对文件执行的操作
+#[derive(Debug, Clone)]
+pub struct LifecycleAction {
+    /// 文件路径
+    pub key: String,
+    /// 产生此操作的规则ID
+    pub rule_id: String,
+    /// 操作类型
+    pub action: LifecycleActionKind,
+    /// 文件满足条件的时间，OSS会在此时间之后的下一次执行周期处理
+    pub due: DateTime<Utc>,
+    /// 规则包含标签条件或基于最后访问时间，列举结果中没有相应的信息，实际是否执行需要另行确认
+    pub uncertain: bool,
+}
+impl LifecycleAction {
+    /// 在指定的时间是否已经满足执行条件
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due <= now
+    }
+}
+
+impl LifecycleConfiguration {
+    /// 解析GetBucketLifecycle返回的XML，或PutBucketLifecycle的请求body
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        serde_xml_rs::from_str(xml)
+            .map_err(|_| Error::OssInvalidResponse(Some(xml.to_owned().into())))
+    }
+    /// 计算规则对文件执行的操作，按满足条件的时间排序
+    ///
+    /// 文件删除之后的操作不会返回；最后修改时间无法解析的文件不返回任何操作
+    pub fn evaluate(&self, object: &ObjectInfo) -> Vec<LifecycleAction> {
+        let Some(last_modified) = object.last_modified.datetime() else {
+            return Vec::new();
+        };
+        let mut actions = Vec::new();
+        for rule in &self.rules {
+            let Some(uncertain) = rule.matches(object) else {
+                continue;
+            };
+            let mut push = |action, due, access_time| {
+                actions.push(LifecycleAction {
+                    key: object.key.clone(),
+                    rule_id: rule.id.clone(),
+                    action,
+                    due,
+                    uncertain: uncertain || access_time,
+                })
+            };
+            if let Some(expiration) = &rule.expiration {
+                if let Some(due) = due(
+                    last_modified,
+                    expiration.days,
+                    &expiration.created_before_date,
+                ) {
+                    push(LifecycleActionKind::Expire, due, false);
+                }
+            }
+            for transition in &rule.transitions {
+                if transition.storage_class == object.storage_class {
+                    continue;
+                }
+                if let Some(due) = due(
+                    last_modified,
+                    transition.days,
+                    &transition.created_before_date,
+                ) {
+                    push(
+                        LifecycleActionKind::Transition(transition.storage_class.clone()),
+                        due,
+                        transition.is_access_time,
+                    );
+                }
+            }
+        }
+        actions.sort_by_key(|v| v.due);
+        //删除之后不会再有其他操作
+        if let Some(index) = actions
+            .iter()
+            .position(|v| v.action == LifecycleActionKind::Expire && !v.uncertain)
+        {
+            actions.truncate(index + 1);
+        }
+        actions
+    }
+    /// 对列举结果逐个计算，返回所有将被执行的操作
+    ///
+    /// 列举出错时返回错误后继续处理后续的结果
+    pub fn dry_run<S>(&self, objects: S) -> BoxStream<'static, Result<LifecycleAction, Error>>
+    where
+        S: Stream<Item = Result<ObjectInfo, Error>> + Send + 'static,
+    {
+        let config = self.clone();
+        objects
+            .map(move |object| match object {
+                Ok(object) => config.evaluate(&object).into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+            .flat_map(stream::iter)
+            .boxed()
+    }
+}
+
+impl LifecycleRule {
+    //规则是否作用于文件，作用时返回结果是否不确定
+    fn matches(&self, object: &ObjectInfo) -> Option<bool> {
+        if self.status != "Enabled" || !object.key.starts_with(&self.prefix) {
+            return None;
+        }
+        let mut uncertain = !self.tags.is_empty();
+        if let Some(filter) = &self.filter {
+            if filter
+                .object_size_greater_than
+                .is_some_and(|v| object.size <= v)
+                || filter
+                    .object_size_less_than
+                    .is_some_and(|v| object.size >= v)
+            {
+                return None;
+            }
+            for not in &filter.not {
+                if object.key.starts_with(&not.prefix) {
+                    match not.tag {
+                        //只按前缀排除
+                        None => return None,
+                        Some(_) => uncertain = true,
+                    }
+                }
+            }
+        }
+        Some(uncertain)
+    }
+}
+
+//满足条件的时间，天数从最后修改时间开始计算
+fn due(
+    last_modified: DateTime<Utc>,
+    days: Option<u32>,
+    created_before_date: &Option<OssDateTime>,
+) -> Option<DateTime<Utc>> {
+    match (days, created_before_date) {
+        (Some(days), _) => Some(last_modified + Duration::days(days.into())),
+        (None, Some(date)) => date.datetime().filter(|date| last_modified < *date),
+        (None, None) => None,
+    }
+}
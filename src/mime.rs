@@ -0,0 +1,112 @@
+//! 文件类型识别
+//!
+//! 上传文件未设置mime类型时，使用MimeDetector根据文件开头的数据和OSS文件路径识别文件类型
+//!
+//! ```
+//! use aliyun_oss_rs::mime::MimeMap;
+//!
+//! let detector = MimeMap::new()
+//!     .insert("wasm", "application/wasm")
+//!     .insert("md", "text/markdown; charset=utf-8");
+//! object
+//!     .put_object()
+//!     .set_mime_detector(Arc::new(detector))
+//!     .send_file("/data/readme.md")
+//!     .await?;
+//! ```
+
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+/// 识别文件类型时读取的文件开头数据的最大长度
+pub const SNIFF_LEN: usize = 8192;
+
+/// 默认的文件类型
+pub const DEFAULT_MIME: &str = "application/octet-stream";
+
+/// 文件类型识别
+pub trait MimeDetector: Send + Sync {
+    /// 识别文件类型，无法识别时返回None，将使用 application/octet-stream
+    ///
+    /// - head：文件开头的数据，最多 SNIFF_LEN 字节
+    /// - key：OSS文件路径
+    fn detect(&self, head: &[u8], key: &str) -> Option<String>;
+}
+
+/// 默认的文件类型识别，优先根据文件内容的特征识别，再根据OSS文件路径的扩展名识别
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultMimeDetector;
+impl MimeDetector for DefaultMimeDetector {
+    fn detect(&self, head: &[u8], key: &str) -> Option<String> {
+        match infer::get(head) {
+            Some(kind) => Some(kind.mime_type().to_owned()),
+            None => mime_guess::from_path(key).first().map(|v| v.to_string()),
+        }
+    }
+}
+
+/// 自定义扩展名与文件类型的对应关系，未匹配时使用默认的文件类型识别
+#[derive(Debug, Clone, Default)]
+pub struct MimeMap {
+    types: HashMap<String, String>,
+}
+impl MimeMap {
+    pub fn new() -> Self {
+        MimeMap::default()
+    }
+    /// 添加扩展名对应的文件类型，扩展名不区分大小写，不包含 .
+    pub fn insert(mut self, extension: impl ToString, mime: impl ToString) -> Self {
+        self.types
+            .insert(extension.to_string().to_lowercase(), mime.to_string());
+        self
+    }
+}
+impl MimeDetector for MimeMap {
+    fn detect(&self, head: &[u8], key: &str) -> Option<String> {
+        Path::new(key)
+            .extension()
+            .and_then(|v| v.to_str())
+            .and_then(|v| self.types.get(&v.to_lowercase()).cloned())
+            .or_else(|| DefaultMimeDetector.detect(head, key))
+    }
+}
+
+//识别文件类型，未设置识别方式时使用默认的识别方式
+pub(crate) fn detect_mime(
+    detector: Option<&Arc<dyn MimeDetector>>,
+    head: &[u8],
+    key: &str,
+) -> String {
+    let head = &head[..head.len().min(SNIFF_LEN)];
+    match detector {
+        Some(detector) => detector.detect(head, key),
+        None => DefaultMimeDetector.detect(head, key),
+    }
+    .unwrap_or_else(|| DEFAULT_MIME.to_owned())
+}
+
+//读取数据流开头的数据用于识别文件类型，返回读取到的数据和完整的数据流
+pub(crate) async fn sniff_stream<S, E>(
+    mut stream: S,
+) -> (Vec<u8>, impl Stream<Item = Result<Bytes, E>>)
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    let mut head = Vec::new();
+    let mut chunks = Vec::new();
+    while head.len() < SNIFF_LEN {
+        match stream.next().await {
+            Some(Ok(chunk)) => {
+                head.extend_from_slice(&chunk[..chunk.len().min(SNIFF_LEN - head.len())]);
+                chunks.push(Ok(chunk));
+            }
+            Some(Err(e)) => {
+                chunks.push(Err(e));
+                break;
+            }
+            None => break,
+        }
+    }
+    (head, stream::iter(chunks).chain(stream))
+}
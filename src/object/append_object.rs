@@ -1,13 +1,15 @@
 use crate::{
     common::{
-        invalid_metadata_key, url_encode, Acl, CacheControl, ContentDisposition, StorageClass,
+        check_tags, url_encode, Acl, CacheControl, ContentDisposition, ObjectMetadata, StorageClass,
     },
     error::{normal_error, Error},
+    mime::{detect_mime, sniff_stream, MimeDetector},
+    progress::{ProgressEvent, ProgressListener},
     request::{Oss, OssRequest},
 };
 use futures_util::StreamExt;
 use hyper::{header, Body, Method};
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use tokio::{fs::File, io::BufReader};
 use tokio_util::io::ReaderStream;
 
@@ -20,9 +22,11 @@ use tokio_util::io::ReaderStream;
 /// 追加文件的逻辑和限制较为复杂，建议仔细阅读 [阿里云官方文档](https://help.aliyun.com/document_detail/31978.html)
 pub struct AppendObject {
     req: OssRequest,
+    metadata: ObjectMetadata,
     mime: Option<String>,
     tags: HashMap<String, String>,
-    callback: Option<Box<dyn Fn(u64, u64) + Send + Sync + 'static>>,
+    mime_detector: Option<Arc<dyn MimeDetector>>,
+    listener: Option<Arc<dyn ProgressListener>>,
 }
 
 impl AppendObject {
@@ -32,9 +36,11 @@ impl AppendObject {
         req.insert_query("position", "0");
         AppendObject {
             req,
+            metadata: ObjectMetadata::new(),
             mime: None,
+            mime_detector: None,
             tags: HashMap::new(),
-            callback: None,
+            listener: None,
         }
     }
     /// 设置追加内容的起点
@@ -44,11 +50,16 @@ impl AppendObject {
     }
     /// 设置文件的mime类型
     ///
-    /// 如果未设置mime类型，请求发送时，会尝试从内容、本地路径、远程路径获取mime，如果依然未获取成功，则使用默认mime类型（application/octet-stream）
+    /// 如果未设置mime类型，请求发送时，会使用MimeDetector根据内容开头的数据、远程路径识别mime，如果依然未识别成功，则使用默认mime类型（application/octet-stream）
     pub fn set_mime(mut self, mime: impl ToString) -> Self {
         self.mime = Some(mime.to_string());
         self
     }
+    /// 设置文件类型的识别方式，未设置时使用 DefaultMimeDetector
+    pub fn set_mime_detector(mut self, mime_detector: Arc<dyn MimeDetector>) -> Self {
+        self.mime_detector = Some(mime_detector);
+        self
+    }
     /// 设置文件的访问权限
     pub fn set_acl(mut self, acl: Acl) -> Self {
         self.req.insert_header("x-oss-object-acl", acl);
@@ -72,16 +83,19 @@ impl AppendObject {
     }
     /// 设置需要附加的metadata
     ///
-    /// key只允许存在英文字母、数字、连字符，如果存在其他字符，则metadata将直接被抛弃
+    /// key只允许存在英文字母、数字、连字符，不区分大小写；不符合要求时，发送请求时返回 Error::InvalidMetadata
     pub fn set_meta(mut self, key: impl ToString, value: impl ToString) -> Self {
-        let key = key.to_string();
-        if !invalid_metadata_key(&key) {
-            self.req
-                .insert_header(format!("x-oss-meta-{}", key.to_string()), value);
-        }
+        self.metadata.insert(key, value);
+        self
+    }
+    /// 设置需要附加的metadata，会覆盖之前设置的所有metadata
+    pub fn set_metadata(mut self, metadata: ObjectMetadata) -> Self {
+        self.metadata = metadata;
         self
     }
     /// 设置标签信息
+    ///
+    /// 标签限制见 check_tags，不符合要求时发送请求将返回 Error::InvalidTag
     pub fn set_tagging(mut self, key: impl ToString, value: impl ToString) -> Self {
         self.tags.insert(key.to_string(), value.to_string());
         self
@@ -98,7 +112,22 @@ impl AppendObject {
     /// });
     /// ```
     pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
-        self.callback = Some(callback);
+        self.listener = Some(Arc::new(callback));
+        self
+    }
+    /// 设置上传进度的监听，与set_callback()的生效范围相同，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+    /// 设置读取本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.req.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.req.oss.chunk_size = chunk_size.max(1024);
         self
     }
     /// 将磁盘中的文件上传到OSS
@@ -106,27 +135,10 @@ impl AppendObject {
     /// 如果设置了上传进度的回调方法，调用者将会实时获得最新的上传进度
     ///
     pub async fn send_file(mut self, file: impl ToString) -> Result<Option<String>, Error> {
-        //生成文件类型
-        let file_type = match self.mime {
-            Some(mime) => mime,
-            None => match infer::get_from_path(&file.to_string())? {
-                Some(ext) => ext.mime_type().to_owned(),
-                None => mime_guess::from_path(
-                    &self
-                        .req
-                        .oss
-                        .object
-                        .clone()
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| String::new()),
-                )
-                .first()
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "application/octet-stream".to_owned())
-                .to_string(),
-            },
-        };
-        self.req.insert_header(header::CONTENT_TYPE, file_type);
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
@@ -156,17 +168,34 @@ impl AppendObject {
         }
         self.req.insert_header(header::CONTENT_LENGTH, file_size);
         //初始化文件内容读取数据流
-        let buf = BufReader::with_capacity(131072, file);
-        let stream = ReaderStream::with_capacity(buf, 16384);
+        let buf = BufReader::with_capacity(self.req.oss.buffer_size, file);
+        let stream = ReaderStream::with_capacity(buf, self.req.oss.chunk_size);
+        //生成文件类型，读取文件开头的数据进行识别
+        let (stream, file_type) = match self.mime.take() {
+            Some(mime) => (stream.left_stream(), mime),
+            None => {
+                let (head, stream) = sniff_stream(stream).await;
+                let file_type = detect_mime(
+                    self.mime_detector.as_ref(),
+                    &head,
+                    self.req.oss.object.as_deref().unwrap_or_default(),
+                );
+                (stream.right_stream(), file_type)
+            }
+        };
+        self.req.insert_header(header::CONTENT_TYPE, file_type);
         //初始化已上传内容大小
         let mut uploaded_size = 0;
         //创建body对象
         let body = Body::wrap_stream(stream.map(move |result| match result {
             Ok(chunk) => {
-                if let Some(callback) = &self.callback {
+                if let Some(listener) = &self.listener {
                     let upload_size = chunk.len() as u64;
                     uploaded_size = uploaded_size + upload_size;
-                    callback(uploaded_size, file_size);
+                    listener.on_event(&ProgressEvent::Progress {
+                        transferred: uploaded_size,
+                        total: file_size,
+                    });
                 }
                 Ok(chunk)
             }
@@ -191,6 +220,8 @@ impl AppendObject {
     /// 将内存中的数据上传到OSS
     ///
     pub async fn send_content(mut self, content: Vec<u8>) -> Result<Option<String>, Error> {
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
         //读取文件大小
         let content_size = content.len();
         if content_size >= 5_368_709_120 {
@@ -198,25 +229,17 @@ impl AppendObject {
         }
         self.req.insert_header(header::CONTENT_LENGTH, content_size);
         //生成文件类型
-        let content_type = match self.mime {
+        let content_type = match self.mime.take() {
             Some(mime) => mime,
-            None => match infer::get(&content) {
-                Some(ext) => ext.mime_type().to_string(),
-                None => mime_guess::from_path(
-                    self.req
-                        .oss
-                        .object
-                        .clone()
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| String::new().into()),
-                )
-                .first()
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "application/octet-stream".to_owned())
-                .to_string(),
-            },
+            None => detect_mime(
+                self.mime_detector.as_ref(),
+                &content,
+                self.req.oss.object.as_deref().unwrap_or_default(),
+            ),
         };
         self.req.insert_header(header::CONTENT_TYPE, content_type);
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
@@ -1,5 +1,5 @@
 use crate::{
-    common::{invalid_metadata_key, url_encode, Acl, StorageClass},
+    common::{check_tags, url_encode, Acl, ObjectMetadata, StorageClass},
     error::{normal_error, Error},
     request::{Oss, OssRequest},
 };
@@ -14,6 +14,7 @@ use std::collections::HashMap;
 /// 其他较多的限制，具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31979.html)
 pub struct CopyObject {
     req: OssRequest,
+    metadata: ObjectMetadata,
     tags: HashMap<String, String>,
 }
 
@@ -23,6 +24,7 @@ impl CopyObject {
         req.insert_header("x-oss-copy-source", copy_source);
         CopyObject {
             req,
+            metadata: ObjectMetadata::new(),
             tags: HashMap::new(),
         }
     }
@@ -38,13 +40,14 @@ impl CopyObject {
     }
     /// 设置需要附加的metadata
     ///
-    /// key只允许存在英文字母、数字、连字符，如果存在其他字符，则metadata将直接被抛弃
+    /// key只允许存在英文字母、数字、连字符，不区分大小写；不符合要求时，发送请求时返回 Error::InvalidMetadata
     pub fn set_meta(mut self, key: impl ToString, value: impl ToString) -> Self {
-        let key = key.to_string();
-        if !invalid_metadata_key(&key) {
-            self.req
-                .insert_header(format!("x-oss-meta-{}", key.to_string()), value);
-        }
+        self.metadata.insert(key, value);
+        self
+    }
+    /// 设置需要附加的metadata，会覆盖之前设置的所有metadata
+    pub fn set_metadata(mut self, metadata: ObjectMetadata) -> Self {
+        self.metadata = metadata;
         self
     }
     /// 如果指定的时间早于文件实际修改时间，则正常拷贝文件。
@@ -87,6 +90,8 @@ impl CopyObject {
         self
     }
     /// 设置标签信息
+    ///
+    /// 标签限制见 check_tags，不符合要求时发送请求将返回 Error::InvalidTag
     pub fn set_tagging(mut self, key: impl ToString, value: impl ToString) -> Self {
         self.tags.insert(key.to_string(), value.to_string());
         self
@@ -106,6 +111,10 @@ impl CopyObject {
     /// 复制文件
     ///
     pub async fn send(mut self) -> Result<(), Error> {
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
@@ -1,28 +1,43 @@
 use crate::{
+    checksum::{read_file, Crc64},
+    common::DownloadMode,
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use bytes::Bytes;
 use chrono::NaiveDateTime;
-use futures_util::{Stream, StreamExt};
-use hyper::{body::to_bytes, Method};
-use std::pin::Pin;
+use futures_util::{future, stream, Stream, StreamExt};
+use hyper::{body::to_bytes, header, Body, HeaderMap, Method, StatusCode};
+use std::{
+    io,
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tokio::{
-    fs::{create_dir_all, OpenOptions},
-    io::{AsyncWriteExt, BufWriter},
+    fs::{create_dir_all, metadata, remove_file, rename, File, OpenOptions},
+    io::{AsyncRead, AsyncWriteExt, BufWriter},
 };
+use tokio_util::io::StreamReader;
 
 /// 获取文件内容
 ///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31980.html)
 pub struct GetObject {
     req: OssRequest,
+    verify: bool,
+    decode: bool,
+    download_mode: DownloadMode,
 }
 impl GetObject {
     pub(super) fn new(oss: Oss) -> Self {
         GetObject {
             req: OssRequest::new(oss, Method::GET),
+            verify: false,
+            decode: false,
+            download_mode: DownloadMode::CreateNew,
         }
     }
     /// 设置响应时的range
@@ -72,46 +87,135 @@ impl GetObject {
         self.req.insert_header("If-None-Match", if_none_match);
         self
     }
+    /// 限制下载速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.req.insert_header("x-oss-traffic-limit", traffic_limit);
+        self
+    }
+    /// 设置写入本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.req.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 校验下载的数据
+    ///
+    /// 下载时计算CRC64，并与OSS返回的 x-oss-hash-crc64ecma 比对；设置了range时不进行校验
+    ///
+    /// 校验不通过时，返回 Error::ChecksumMismatch ；download_to_stream()会在数据流的最后返回错误
+    pub fn verify_checksum(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+    /// 文件以 Content-Encoding: gzip 存储时，下载时解压
+    ///
+    /// 未压缩的文件原样返回；OSS返回的CRC64按压缩后的数据计算，解压时不进行校验，设置的range也作用于压缩后的数据
+    #[cfg(feature = "gzip")]
+    pub fn decode_content(mut self) -> Self {
+        self.decode = true;
+        self
+    }
+    /// 设置本地文件的写入方式，仅对download_to_file()有效
+    ///
+    /// 默认值：DownloadMode::CreateNew ，本地文件已存在时返回错误；使用 DownloadMode::Resume 时，设置的range将被忽略
+    pub fn set_download_mode(mut self, download_mode: DownloadMode) -> Self {
+        self.download_mode = download_mode;
+        self
+    }
     /// 下载文件保存到磁盘
     ///
     /// 不支持网络路径，如果需要保存到smb\nfs等网络存储，请先挂载到本地，再使用本地路径地址
-    pub async fn download_to_file(self, save_path: &str) -> Result<(), Error> {
+    pub async fn download_to_file(mut self, save_path: &str) -> Result<(), Error> {
         //判断路径
         if save_path.contains("://") {
             return Err(Error::PathNotSupported);
         }
+        //创建目录
+        let parent_dir = Path::new(save_path).parent();
+        if let Some(dir) = parent_dir {
+            create_dir_all(dir).await?;
+        }
+        //断点续传时，从本地文件的末尾继续下载
+        let verify = self.verify();
+        let mut crc64 = Crc64::new();
+        let mut downloaded = 0;
+        if self.download_mode == DownloadMode::Resume {
+            downloaded = metadata(save_path).await.map(|v| v.len()).unwrap_or(0);
+            if downloaded > 0 {
+                if verify {
+                    read_file(save_path, |data| crc64.update(data)).await?;
+                }
+                self.req
+                    .insert_header("Range", format!("bytes={}-", downloaded));
+            }
+        }
         //发起请求
+        let download_mode = self.download_mode;
+        let buffer_size = self.req.oss.buffer_size;
         let response = self.req.send_to_oss()?.await?;
+        #[cfg(feature = "gzip")]
+        let response = match self.decode {
+            true => crate::compress::decode_response(response),
+            false => response,
+        };
         //拆解响应消息
         let status_code = response.status();
-        match status_code {
-            code if code.is_success() => {
-                //创建目录
-                let parent_dir = std::path::Path::new(save_path).parent();
-                if let Some(dir) = parent_dir {
-                    create_dir_all(dir).await?;
-                }
-                //创建文件
-                let file = OpenOptions::new()
-                    .write(true)
-                    .create_new(true)
-                    .open(save_path)
-                    .await?;
-                //创建写入缓冲区
-                let mut writer = BufWriter::with_capacity(131072, file);
-                //读取字节流
-                let mut response_bytes = response.into_body();
-                while let Some(chunk) = response_bytes.next().await {
-                    match chunk {
-                        Ok(data) => writer.write_all(&data).await?,
-                        Err(e) => return Err(Error::HyperError(e)),
-                    }
+        //本地文件已经完整
+        if status_code == StatusCode::RANGE_NOT_SATISFIABLE && downloaded > 0 {
+            let total_size = response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.rsplit('/').next())
+                .and_then(|v| v.parse::<u64>().ok());
+            if total_size == Some(downloaded) {
+                return Ok(());
+            }
+        }
+        if !status_code.is_success() {
+            return Err(normal_error(response).await);
+        }
+        let remote_crc64 = remote_crc64(verify, response.headers());
+        //确定写入的文件，OSS返回了完整的文件时，从头开始写入
+        let append = status_code == StatusCode::PARTIAL_CONTENT && downloaded > 0;
+        if !append {
+            crc64 = Crc64::new();
+        }
+        let write_path = match download_mode {
+            DownloadMode::Atomic => temp_path(save_path),
+            _ => save_path.to_owned(),
+        };
+        let mut options = OpenOptions::new();
+        options.write(true);
+        match download_mode {
+            DownloadMode::CreateNew | DownloadMode::Atomic => options.create_new(true),
+            DownloadMode::Resume if append => options.append(true),
+            _ => options.create(true).truncate(true),
+        };
+        let file = options.open(&write_path).await?;
+        //写入文件
+        let result = write_body(file, buffer_size, response.into_body(), &mut crc64)
+            .await
+            .and_then(|_| check_crc64(remote_crc64, crc64));
+        match result {
+            Ok(_) if download_mode == DownloadMode::Atomic => {
+                if let Err(e) = rename(&write_path, save_path).await {
+                    let _ = remove_file(&write_path).await;
+                    return Err(e.into());
                 }
-                writer.flush().await?;
-                writer.shutdown().await?;
                 Ok(())
             }
-            _ => Err(normal_error(response).await),
+            Ok(_) => Ok(()),
+            //临时文件全部删除，其他文件仅在校验失败时删除
+            Err(e) => {
+                if download_mode == DownloadMode::Atomic
+                    || matches!(e, Error::ChecksumMismatch(_, _))
+                {
+                    let _ = remove_file(&write_path).await;
+                }
+                Err(e)
+            }
         }
     }
     /// 下载文件，直接将内容返回
@@ -119,11 +223,26 @@ impl GetObject {
     /// 如果文件较大，此方法可能占用过多内存，谨慎使用
     pub async fn download(self) -> Result<Bytes, Error> {
         //发起请求
+        let verify = self.verify();
         let response = self.req.send_to_oss()?.await?;
+        #[cfg(feature = "gzip")]
+        let response = match self.decode {
+            true => crate::compress::decode_response(response),
+            false => response,
+        };
         //拆解响应消息
         let status_code = response.status();
         match status_code {
-            code if code.is_success() => Ok(to_bytes(response.into_body()).await?),
+            code if code.is_success() => {
+                let remote_crc64 = remote_crc64(verify, response.headers());
+                let content = to_bytes(response.into_body()).await?;
+                let mut crc64 = Crc64::new();
+                if remote_crc64.is_some() {
+                    crc64.update(&content);
+                }
+                check_crc64(remote_crc64, crc64)?;
+                Ok(content)
+            }
             _ => Err(normal_error(response).await),
         }
     }
@@ -148,18 +267,209 @@ impl GetObject {
         self,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<bytes::Bytes, Error>> + Send>>, Error> {
         //发起请求
+        let verify = self.verify();
         let response = self.req.send_to_oss()?.await?;
+        #[cfg(feature = "gzip")]
+        let response = match self.decode {
+            true => crate::compress::decode_response(response),
+            false => response,
+        };
         //拆解响应消息
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
-                let stream = response.into_body().map(|item| match item {
-                    Ok(bytes) => Ok(bytes),
-                    Err(e) => Err(e.into()),
-                });
+                let remote_crc64 = remote_crc64(verify, response.headers());
+                if remote_crc64.is_none() {
+                    let stream = response.into_body().map(|item| match item {
+                        Ok(bytes) => Ok(bytes),
+                        Err(e) => Err(e.into()),
+                    });
+                    return Ok(Box::pin(stream));
+                }
+                //数据流结束时校验CRC64，校验失败时追加一个错误
+                let crc64 = Arc::new(Mutex::new(Crc64::new()));
+                let crc64_writer = crc64.clone();
+                let stream = response
+                    .into_body()
+                    .map(move |item| match item {
+                        Ok(bytes) => {
+                            if let Ok(mut crc64) = crc64_writer.lock() {
+                                crc64.update(&bytes);
+                            }
+                            Ok(bytes)
+                        }
+                        Err(e) => Err(e.into()),
+                    })
+                    .chain(
+                        stream::once(async move {
+                            let crc64 = crc64.lock().map(|v| *v).unwrap_or_default();
+                            check_crc64(remote_crc64, crc64).err().map(Err)
+                        })
+                        .filter_map(future::ready),
+                    );
                 Ok(Box::pin(stream))
             }
             _ => Err(normal_error(response).await),
         }
     }
+    /// 下载文件，返回一个实现了 AsyncRead 的读取器
+    ///
+    /// 可以直接传给 tokio::io::copy 、解压缩、tar等通用的IO代码；读取出错时，错误会转换为对应 ErrorKind 的 io::Error
+    ///
+    /// ```
+    /// let mut reader = object.get_object().download_to_reader().await?;
+    /// let mut file = tokio::fs::File::create("/tmp/file.bin").await?;
+    /// tokio::io::copy(&mut reader, &mut file).await?;
+    /// ```
+    pub async fn download_to_reader(self) -> Result<impl AsyncRead + Send + Unpin, Error> {
+        let stream = self.download_to_stream().await?;
+        Ok(StreamReader::new(
+            stream.map(|item| item.map_err(io::Error::from)),
+        ))
+    }
+    /// 下载文件，转换为可以直接返回给客户端的HTTP响应
+    ///
+    /// 保留状态码及 Content-Type、Content-Length、ETag、Content-Range 等响应头，响应体直接转发OSS返回的数据流，不在内存中缓存；
+    /// 条件请求未命中时，返回状态码为304的空响应。hyper 0.14、axum 0.6 可以直接返回此响应
+    ///
+    /// 不进行CRC64校验，verify_checksum()的设置将被忽略
+    ///
+    /// ```
+    /// async fn download(Path(key): Path<String>) -> Result<Response<Body>, StatusCode> {
+    ///     bucket
+    ///         .object(&key)
+    ///         .get_object()
+    ///         .into_response()
+    ///         .await
+    ///         .map_err(|e| e.status().unwrap_or(StatusCode::BAD_GATEWAY))
+    /// }
+    /// ```
+    #[cfg(feature = "http-response")]
+    pub async fn into_response(self) -> Result<hyper::Response<Body>, Error> {
+        //发起请求
+        let response = self.req.send_to_oss()?.await?;
+        //拆解响应消息
+        let status_code = response.status();
+        if !status_code.is_success() && status_code != StatusCode::NOT_MODIFIED {
+            return Err(normal_error(response).await);
+        }
+        let (parts, body) = response.into_parts();
+        let mut builder = hyper::Response::builder().status(status_code);
+        for name in &FORWARD_HEADERS {
+            if let Some(value) = parts.headers.get(name) {
+                builder = builder.header(name, value);
+            }
+        }
+        Ok(builder.body(body)?)
+    }
+    /// 下载文件，转换为actix-web的HttpResponse
+    ///
+    /// 与 into_response() 相同，保留状态码及相关响应头，响应体直接转发OSS返回的数据流，不进行CRC64校验
+    ///
+    /// ```
+    /// #[get("/files/{key}")]
+    /// async fn download(key: web::Path<String>) -> actix_web::Result<HttpResponse> {
+    ///     bucket
+    ///         .object(&key)
+    ///         .get_object()
+    ///         .into_actix_response()
+    ///         .await
+    ///         .map_err(actix_web::error::ErrorBadGateway)
+    /// }
+    /// ```
+    #[cfg(feature = "actix")]
+    pub async fn into_actix_response(self) -> Result<actix_web::HttpResponse, Error> {
+        use actix_web::http::StatusCode as ActixStatusCode;
+        //发起请求
+        let response = self.req.send_to_oss()?.await?;
+        //拆解响应消息
+        let status_code = response.status();
+        if !status_code.is_success() && status_code != StatusCode::NOT_MODIFIED {
+            return Err(normal_error(response).await);
+        }
+        let status = ActixStatusCode::from_u16(status_code.as_u16()).unwrap_or(ActixStatusCode::OK);
+        let mut builder = actix_web::HttpResponse::build(status);
+        let headers = response.headers();
+        for name in &FORWARD_HEADERS {
+            if let Some(value) = headers.get(name).and_then(|v| v.to_str().ok()) {
+                builder.insert_header((name.as_str(), value));
+            }
+        }
+        //已知长度时使用Content-Length，避免actix-web改为分块传输
+        if let Some(content_length) = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            builder.no_chunking(content_length);
+        }
+        Ok(builder.streaming(response.into_body()))
+    }
+    //是否需要校验，设置了range时OSS返回的是整个文件的CRC64，无法校验
+    fn verify(&self) -> bool {
+        self.verify && !self.decode && !self.req.headers.contains_key("Range")
+    }
+}
+
+//转发给客户端的响应头
+#[cfg(any(feature = "http-response", feature = "actix"))]
+const FORWARD_HEADERS: [header::HeaderName; 10] = [
+    header::CONTENT_TYPE,
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::CONTENT_ENCODING,
+    header::CONTENT_DISPOSITION,
+    header::ACCEPT_RANGES,
+    header::CACHE_CONTROL,
+    header::ETAG,
+    header::EXPIRES,
+    header::LAST_MODIFIED,
+];
+
+//读取OSS返回的CRC64
+fn remote_crc64(verify: bool, headers: &HeaderMap) -> Option<String> {
+    if !verify {
+        return None;
+    }
+    headers
+        .get("x-oss-hash-crc64ecma")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_owned())
+}
+
+//将响应内容写入文件，同时计算CRC64
+async fn write_body(
+    file: File,
+    buffer_size: usize,
+    mut body: Body,
+    crc64: &mut Crc64,
+) -> Result<(), Error> {
+    let mut writer = BufWriter::with_capacity(buffer_size, file);
+    while let Some(chunk) = body.next().await {
+        let data = chunk?;
+        crc64.update(&data);
+        writer.write_all(&data).await?;
+    }
+    writer.flush().await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+//生成同目录下的临时文件路径
+fn temp_path(save_path: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|v| v.as_nanos())
+        .unwrap_or_default();
+    format!("{}.{}.tmp", save_path, nanos)
+}
+
+//比对CRC64
+fn check_crc64(remote_crc64: Option<String>, crc64: Crc64) -> Result<(), Error> {
+    match remote_crc64 {
+        Some(remote_crc64) if remote_crc64 != crc64.finalize().to_string() => Err(
+            Error::ChecksumMismatch(crc64.finalize().to_string(), remote_crc64),
+        ),
+        _ => Ok(()),
+    }
 }
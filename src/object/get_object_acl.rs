@@ -1,16 +1,17 @@
 use crate::{
-    common::Acl,
+    common::{request_id, Acl, Owner},
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回的内容
-/// 文件ACL信息
 #[derive(Debug, Deserialize)]
 struct AccessControlPolicy {
+    #[serde(rename = "Owner")]
+    owner: Owner,
     #[serde(rename = "AccessControlList")]
     access_control_list: AccessControlList,
 }
@@ -21,6 +22,17 @@ struct AccessControlList {
     grant: Acl,
 }
 
+/// 文件ACL信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectAcl {
+    /// 所有者信息
+    pub owner: Owner,
+    /// 访问权限
+    pub acl: Acl,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    pub request_id: Option<String>,
+}
+
 /// 获取文件的ACL信息
 ///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31987.html)
@@ -35,19 +47,24 @@ impl GetObjectAcl {
     }
     /// 发送请求
     ///
-    pub async fn send(self) -> Result<Acl, Error> {
+    pub async fn send(self) -> Result<ObjectAcl, Error> {
         //构建http请求
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
                 let acl: AccessControlPolicy = serde_xml_rs::from_reader(&*response_bytes)
                     .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
-                Ok(acl.access_control_list.grant)
+                Ok(ObjectAcl {
+                    owner: acl.owner,
+                    acl: acl.access_control_list.grant,
+                    request_id,
+                })
             }
             _ => Err(normal_error(response).await),
         }
@@ -1,16 +1,17 @@
+use super::get_symlink::follow_symlink;
 use crate::{
-    error::OssError,
+    cache::CacheKey,
+    common::{request_id, OssDateTime},
+    error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
-use base64::{engine::general_purpose, Engine};
-use bytes::Bytes;
 use hyper::Method;
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回的内容
 /// 文件meta信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ObjectMeta {
     /// 文件大小，单位字节
@@ -18,9 +19,12 @@ pub struct ObjectMeta {
     /// 用于标识一个文件的内容
     pub e_tag: String,
     /// 文件最后访问时间
-    pub last_access_time: Option<String>,
+    pub last_access_time: Option<OssDateTime>,
     /// 文件最后修改时间
-    pub last_modified: String,
+    pub last_modified: OssDateTime,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
 /// 获取文件的Meta信息
@@ -28,16 +32,42 @@ pub struct ObjectMeta {
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31985.html)
 pub struct GetObjectMeta {
     req: OssRequest,
+    follow_symlink: bool,
 }
 impl GetObjectMeta {
     pub(super) fn new(oss: Oss) -> Self {
         let mut req = OssRequest::new(oss, Method::HEAD);
         req.insert_query("objectMeta", "");
-        GetObjectMeta { req }
+        GetObjectMeta {
+            req,
+            follow_symlink: false,
+        }
+    }
+    /// 如果文件是软链接，则获取目标文件的meta信息
+    ///
+    /// 开启后，每次发送请求前都会额外发起一次GetSymlink请求
+    pub fn follow_symlink(mut self) -> Self {
+        self.follow_symlink = true;
+        self
     }
     /// 发送请求
     ///
-    pub async fn send(self) -> Result<ObjectMeta, Error> {
+    pub async fn send(mut self) -> Result<ObjectMeta, Error> {
+        //读取缓存
+        let cache = self.req.oss.cache.clone();
+        let cache_key = cache
+            .as_ref()
+            .map(|_| CacheKey::new("meta", &self.req.oss, self.follow_symlink));
+        if let (Some(cache), Some(key)) = (&cache, &cache_key) {
+            if let Some(mut object_meta) = cache.get::<ObjectMeta>(key) {
+                object_meta.request_id = None;
+                return Ok(object_meta);
+            }
+        }
+        //解析软链接
+        if self.follow_symlink {
+            follow_symlink(&mut self.req.oss).await?;
+        }
         //构建http请求
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
@@ -55,37 +85,24 @@ impl GetObjectMeta {
                     .unwrap_or_else(|| String::new());
                 let last_access_time = headers
                     .get("x-oss-last-access-time")
-                    .and_then(|header| header.to_str().ok().map(|s| s.to_owned()));
+                    .and_then(|header| header.to_str().ok().map(OssDateTime::parse));
                 let last_modified = headers
                     .get("Last-Modified")
-                    .and_then(|header| header.to_str().ok().map(|s| s.to_owned()))
-                    .unwrap_or_else(|| String::new());
-                Ok(ObjectMeta {
+                    .and_then(|header| header.to_str().ok().map(OssDateTime::parse))
+                    .unwrap_or_default();
+                let object_meta = ObjectMeta {
                     content_length,
                     e_tag,
                     last_access_time,
                     last_modified,
-                })
-            }
-            _ => {
-                let x_oss_error = response.headers().get("x-oss-err").and_then(|header| {
-                    general_purpose::STANDARD
-                        .decode(header)
-                        .ok()
-                        .map(|v| Bytes::from(v))
-                });
-                match x_oss_error {
-                    None => Err(Error::OssInvalidError(status_code, Bytes::new())),
-                    Some(response_bytes) => {
-                        let oss_error =
-                            serde_xml_rs::from_reader::<&[u8], OssError>(&*response_bytes);
-                        match oss_error {
-                            Ok(oss_error) => Err(Error::OssError(status_code, oss_error)),
-                            Err(_) => Err(Error::OssInvalidError(status_code, response_bytes)),
-                        }
-                    }
+                    request_id: request_id(headers),
+                };
+                if let (Some(cache), Some(key)) = (cache, cache_key) {
+                    cache.insert(key, object_meta.clone());
                 }
+                Ok(object_meta)
             }
+            _ => Err(normal_error(response).await),
         }
     }
 }
@@ -4,7 +4,7 @@ use crate::{
     Error,
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
 // 返回的内容
 #[derive(Debug, Deserialize)]
@@ -19,7 +19,7 @@ pub(crate) struct TagSet {
     pub tags: Option<Vec<Tag>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// 标签信息
 pub struct Tag {
     #[serde(rename = "Key")]
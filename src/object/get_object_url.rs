@@ -1,15 +1,27 @@
 use crate::{
-    common::{CacheControl, ContentDisposition},
+    common::{CacheControl, ContentDisposition, Expires},
     request::{Oss, OssRequest},
+    Error,
 };
-use chrono::NaiveDateTime;
-use hyper::Method;
+use hyper::{header, Method};
 use std::net::IpAddr;
 
 /// 获取文件的url
 ///
 /// 私有文件可以通过此方法获取一个授权url，即可直接下载此文件
 ///
+/// 通过 set_method 可以生成PUT、HEAD、DELETE等其他方法的授权url，例如由客户端直接上传文件
+///
+/// ```
+/// use hyper::Method;
+///
+/// let url = object
+///     .get_object_url()
+///     .set_method(Method::PUT)
+///     .set_content_type("image/png")
+///     .url(expires)?;
+/// ```
+///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31952.html)
 pub struct GetObjectUrl {
     req: OssRequest,
@@ -20,6 +32,50 @@ impl GetObjectUrl {
             req: OssRequest::new(oss, Method::GET),
         }
     }
+    /// 设置url对应的请求方法
+    ///
+    /// 默认为GET，使用url发起请求时，必须使用相同的方法
+    pub fn set_method(mut self, method: Method) -> Self {
+        self.req.method = method;
+        self
+    }
+    /// 设置请求时的Content-Type
+    ///
+    /// Content-Type会参与签名，使用url发起请求时，必须携带相同的Content-Type请求头
+    pub fn set_content_type(mut self, content_type: impl ToString) -> Self {
+        self.req.insert_header(header::CONTENT_TYPE, content_type);
+        self
+    }
+    /// 设置请求时的Content-MD5
+    ///
+    /// Content-MD5会参与签名，使用url发起请求时，必须携带相同的Content-MD5请求头
+    pub fn set_content_md5(mut self, content_md5: impl ToString) -> Self {
+        self.req.insert_header("Content-MD5", content_md5);
+        self
+    }
+    /// 设置使用url发起请求时必须携带的请求头，如 content-length
+    ///
+    /// 仅V4签名有效：请求头参与签名，请求头名称列表写入url的 x-oss-additional-headers 参数；x-oss-*、content-type、content-md5 始终参与签名，无需使用此方法
+    pub fn set_signed_header(mut self, key: impl ToString, value: impl ToString) -> Self {
+        let key = key.to_string().to_lowercase();
+        self.req.insert_header(&key, value);
+        if !self.req.oss.additional_headers.contains(&key) {
+            self.req.oss.additional_headers.push(key);
+        }
+        self
+    }
+    /// 追加V4签名时额外参与签名的请求头名称，如 host
+    ///
+    /// 与OssClient配置的名称合并；请求中不存在的请求头会被忽略，host 除外
+    pub fn set_additional_headers(mut self, headers: Vec<impl ToString>) -> Self {
+        for key in headers {
+            let key = key.to_string().to_lowercase();
+            if !self.req.oss.additional_headers.contains(&key) {
+                self.req.oss.additional_headers.push(key);
+            }
+        }
+        self
+    }
     /// 设置IP信息
     ///
     /// 如果只允许单IP，将subnet_mask设置为32即可
@@ -76,17 +132,102 @@ impl GetObjectUrl {
             .insert_query("response-content-disposition", content_disposition);
         self
     }
+    /// 设置STS临时访问凭证的SecurityToken
+    ///
+    /// 如果OssClient已经携带了SecurityToken，则无需重复设置；生成的url中会包含 security-token 参数，并参与签名
+    pub fn set_security_token(mut self, security_token: impl ToString) -> Self {
+        self.req.oss.set_security_token(security_token);
+        self
+    }
+    /// 设置响应时的content-language
+    ///
+    pub fn set_response_content_language(mut self, content_language: impl ToString) -> Self {
+        self.req
+            .insert_query("response-content-language", content_language);
+        self
+    }
+    /// 设置响应时的content-encoding
+    ///
+    pub fn set_response_content_encoding(mut self, content_encoding: impl ToString) -> Self {
+        self.req
+            .insert_query("response-content-encoding", content_encoding);
+        self
+    }
+    /// 设置响应时的expires
+    ///
+    pub fn set_response_expires(mut self, expires: impl ToString) -> Self {
+        self.req.insert_query("response-expires", expires);
+        self
+    }
+    /// 设置图片处理、视频截帧等数据处理参数，即 x-oss-process
+    ///
+    /// 例如 image/resize,w_100
+    pub fn set_process(mut self, process: impl ToString) -> Self {
+        self.req.insert_query("x-oss-process", process);
+        self
+    }
+    /// 使用图片样式处理文件
+    ///
+    /// 与 set_process 互相覆盖，最后设置的生效
+    pub fn set_style(mut self, style_name: impl ToString) -> Self {
+        self.req
+            .insert_query("x-oss-process", format!("style/{}", style_name.to_string()));
+        self
+    }
+    /// 限制使用url下载时的速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.req.insert_query("x-oss-traffic-limit", traffic_limit);
+        self
+    }
+    /// 设置其他查询参数
+    ///
+    /// OSS要求参与签名的参数（如 response-* 、x-oss-process 等）会自动加入签名，其他参数仅附加在url中
+    pub fn set_query(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.req.insert_query(key, value);
+        self
+    }
     /// 设置自定义域名
     ///
+    /// 自定义域名（CNAME）已经绑定到存储空间，生成的url中不再包含bucket
     pub fn set_custom_domain(mut self, custom_domain: impl ToString, enable_https: bool) -> Self {
-        self.req.set_endpoint(custom_domain);
+        self.req.oss.set_custom_domain(custom_domain);
+        self.req.set_https(enable_https);
+        self
+    }
+    /// 设置生成的url是否使用https
+    ///
+    /// 默认与OssBucket保持一致
+    pub fn set_https(mut self, enable_https: bool) -> Self {
         self.req.set_https(enable_https);
         self
     }
+    /// 生成path-style形式的url，即 endpoint/bucket/object
+    ///
+    /// 默认生成 bucket.endpoint/object 形式的url；设置了自定义域名时，此选项无效
+    pub fn path_style(mut self) -> Self {
+        self.req.oss.set_path_style(true);
+        self
+    }
     /// 生成url
     ///
-    pub fn url(mut self, expires: NaiveDateTime) -> String {
-        self.req.query_sign(expires);
-        self.req.uri()
+    /// expires可以是过期时间（DateTime<Utc>），也可以是从当前时间开始的有效时长（Duration）
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    ///
+    /// let url = object.get_object_url().url(Duration::hours(1))?;
+    /// let url = object.get_object_url().url(Utc::now() + Duration::days(3))?;
+    /// ```
+    ///
+    /// 自定义签名器签名失败或设置了不合法的请求头时返回错误
+    pub fn url(mut self, expires: impl Into<Expires>) -> Result<String, Error> {
+        let expires = match expires.into() {
+            Expires::At(time) => time.timestamp(),
+            Expires::In(duration) => self.req.oss.now().timestamp() + duration.as_secs() as i64,
+        };
+        self.req.query_sign(expires)?;
+        Ok(self.req.uri())
     }
 }
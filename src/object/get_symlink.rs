@@ -1,7 +1,7 @@
 use crate::{
     error::normal_error,
     request::{Oss, OssRequest},
-    Error,
+    Error, OssObject,
 };
 use hyper::Method;
 use percent_encoding::percent_decode;
@@ -40,4 +40,23 @@ impl GetSymlink {
             _ => Err(normal_error(response).await),
         }
     }
+    /// 发送请求，并返回软链接目标文件的OssObject
+    ///
+    pub async fn resolve(self) -> Result<OssObject, Error> {
+        let oss = self.req.oss.clone();
+        let target = self.send().await?;
+        Ok(OssObject::new(oss, target))
+    }
+}
+
+// 如果文件是软链接，则将oss中的文件替换为目标文件；如果不是软链接，则保持不变
+pub(super) async fn follow_symlink(oss: &mut Oss) -> Result<(), Error> {
+    match GetSymlink::new(oss.clone()).send().await {
+        Ok(target) => {
+            oss.set_object(target);
+            Ok(())
+        }
+        Err(Error::OssError(_, oss_error)) if oss_error.code == "NotSymlink" => Ok(()),
+        Err(e) => Err(e),
+    }
 }
@@ -1,10 +1,11 @@
+use super::get_symlink::follow_symlink;
 use crate::{
-    error::OssError,
+    cache::CacheKey,
+    common::ObjectMetadata,
+    error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
-use base64::{engine::general_purpose, Engine};
-use bytes::Bytes;
 use chrono::NaiveDateTime;
 use hyper::Method;
 use std::collections::HashMap;
@@ -14,11 +15,13 @@ use std::collections::HashMap;
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31984.html)
 pub struct HeadObject {
     req: OssRequest,
+    follow_symlink: bool,
 }
 impl HeadObject {
     pub(super) fn new(oss: Oss) -> Self {
         HeadObject {
             req: OssRequest::new(oss, Method::HEAD),
+            follow_symlink: false,
         }
     }
     /// 如果传入参数中的时间早于实际修改时间，则正常返回
@@ -52,9 +55,40 @@ impl HeadObject {
         self.req.insert_header("If-None-Match", if_none_match);
         self
     }
+    /// 如果文件是软链接，则获取目标文件的元信息
+    ///
+    /// 开启后，每次发送请求前都会额外发起一次GetSymlink请求
+    pub fn follow_symlink(mut self) -> Self {
+        self.follow_symlink = true;
+        self
+    }
+    /// 发送请求，只返回自定义元信息
+    ///
+    pub async fn send_metadata(self) -> Result<ObjectMetadata, Error> {
+        let headers = self.send().await?;
+        Ok(ObjectMetadata::from_headers(&headers))
+    }
     /// 发送请求
     ///
-    pub async fn send(self) -> Result<HashMap<String, String>, Error> {
+    /// 启用了元信息缓存时，设置了条件请求头的请求不读取、不写入缓存
+    pub async fn send(mut self) -> Result<HashMap<String, String>, Error> {
+        //读取缓存
+        let cache = self.req.oss.cache.clone();
+        let cache_key = match cache {
+            Some(_) if self.req.headers.is_empty() => {
+                Some(CacheKey::new("head", &self.req.oss, self.follow_symlink))
+            }
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (&cache, &cache_key) {
+            if let Some(result) = cache.get::<HashMap<String, String>>(key) {
+                return Ok(result);
+            }
+        }
+        //解析软链接
+        if self.follow_symlink {
+            follow_symlink(&mut self.req.oss).await?;
+        }
         //构建http请求
         let mut response = self.req.send_to_oss()?.await?;
         //拆解响应消息
@@ -81,27 +115,12 @@ impl HeadObject {
                         (key, value)
                     })
                     .collect::<HashMap<String, String>>();
-                Ok(result)
-            }
-            _ => {
-                let x_oss_error = response.headers().get("x-oss-err").and_then(|header| {
-                    general_purpose::STANDARD
-                        .decode(header)
-                        .ok()
-                        .map(|v| Bytes::from(v))
-                });
-                match x_oss_error {
-                    None => Err(Error::OssInvalidError(status_code, Bytes::new())),
-                    Some(response_bytes) => {
-                        let oss_error =
-                            serde_xml_rs::from_reader::<&[u8], OssError>(&*response_bytes);
-                        match oss_error {
-                            Ok(oss_error) => Err(Error::OssError(status_code, oss_error)),
-                            Err(_) => Err(Error::OssInvalidError(status_code, response_bytes)),
-                        }
-                    }
+                if let (Some(cache), Some(key)) = (cache, cache_key) {
+                    cache.insert(key, result.clone());
                 }
+                Ok(result)
             }
+            _ => Err(normal_error(response).await),
         }
     }
 }
@@ -3,14 +3,31 @@
 #[doc(hidden)]
 pub use self::oss_object::OssObject;
 pub use self::{
-    append_object::AppendObject, copy_object::CopyObject, del_object::DelObject,
-    del_object_tagging::DelObjectTagging, get_object::GetObject, get_object_acl::GetObjectAcl,
-    get_object_meta::GetObjectMeta, get_object_tagging::GetObjectTagging,
-    get_object_url::GetObjectUrl, get_symlink::GetSymlink, head_object::HeadObject,
-    multipart_abort_upload::AbortUpload, multipart_complete_upload::CompleteUpload,
-    multipart_copyto_part::CopyToPart, multipart_init_upload::InitUpload,
-    multipart_list_parts::ListParts, multipart_upload_part::UploadPart, put_object::PutObject,
-    put_object_acl::PutObjectAcl, put_object_tagging::PutObjectTagging, put_symlink::PutSymlink,
+    append_object::AppendObject,
+    copy_object::CopyObject,
+    del_object::DelObject,
+    del_object_tagging::DelObjectTagging,
+    get_object::GetObject,
+    get_object_acl::GetObjectAcl,
+    get_object_meta::GetObjectMeta,
+    get_object_tagging::GetObjectTagging,
+    get_object_url::GetObjectUrl,
+    get_symlink::GetSymlink,
+    head_object::HeadObject,
+    multipart_abort_upload::AbortUpload,
+    multipart_complete_upload::CompleteUpload,
+    multipart_copyto_part::CopyToPart,
+    multipart_download_file::DownloadFile,
+    multipart_init_upload::InitUpload,
+    multipart_list_parts::ListParts,
+    multipart_sink::UploadSink,
+    multipart_upload_file::UploadFile,
+    multipart_upload_part::UploadPart,
+    pipe_object::PipeObject,
+    put_object::{PutObject, PutObjectResult},
+    put_object_acl::PutObjectAcl,
+    put_object_tagging::PutObjectTagging,
+    put_symlink::PutSymlink,
     restore_object::RestoreObject,
 };
 
@@ -28,10 +45,14 @@ mod head_object;
 mod multipart_abort_upload;
 mod multipart_complete_upload;
 mod multipart_copyto_part;
+mod multipart_download_file;
 mod multipart_init_upload;
 mod multipart_list_parts;
+mod multipart_sink;
+mod multipart_upload_file;
 mod multipart_upload_part;
 mod oss_object;
+mod pipe_object;
 mod put_object;
 mod put_object_acl;
 mod put_object_tagging;
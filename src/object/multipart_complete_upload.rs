@@ -1,56 +1,161 @@
 use crate::{
-    error::{normal_error, Error},
+    common::{request_id, Callback},
+    error::{callback_failed_error, normal_error, Error},
     request::{Oss, OssRequest},
+    xml::XmlBuilder,
 };
-use hyper::Method;
+use bytes::Bytes;
+use hyper::{body::to_bytes, Method, StatusCode};
+use serde_derive::{Deserialize, Serialize};
+
+// 返回内容
+/// 完成分片上传的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CompleteUploadResult {
+    /// 文件的访问地址，设置了上传回调时为空
+    pub location: String,
+    /// 存储空间名称
+    pub bucket: String,
+    /// 文件路径
+    pub key: String,
+    /// 文件的ETag
+    pub e_tag: String,
+    /// 文件的CRC64
+    #[serde(skip_deserializing)]
+    pub hash_crc64ecma: Option<u64>,
+    /// 文件的版本ID，仅在开启了版本控制时返回
+    #[serde(skip_deserializing)]
+    pub version_id: Option<String>,
+    /// 回调服务器返回的内容，仅在设置了上传回调时返回
+    #[serde(skip_deserializing)]
+    pub callback_result: Option<Bytes>,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
+}
 
 /// 完成分片上传
 ///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31995.html)
-pub struct CompleteUpload<'a> {
+pub struct CompleteUpload {
     req: OssRequest,
-    parts: Vec<(&'a str, &'a str)>,
+    parts: Vec<(u32, String)>,
+    callback: bool,
 }
-impl<'a> CompleteUpload<'a> {
+impl CompleteUpload {
     pub(super) fn new(oss: Oss, upload_id: impl ToString) -> Self {
         let mut req = OssRequest::new(oss, Method::POST);
         req.insert_query("uploadId", upload_id);
         CompleteUpload {
             req,
             parts: Vec::new(),
+            callback: false,
         }
     }
     /// 新增分片信息
     ///
     /// 数据结构为 (PartNumber,ETag)
-    pub fn add_parts(mut self, parts: Vec<(&'a str, &'a str)>) -> Self {
-        self.parts.extend(parts);
+    pub fn add_parts(mut self, parts: Vec<(u32, impl ToString)>) -> Self {
+        self.parts.extend(
+            parts
+                .into_iter()
+                .map(|(part_number, e_tag)| (part_number, e_tag.to_string())),
+        );
+        self
+    }
+    /// 使用所有已上传的分片完成上传，无需提供分片信息
+    ///
+    /// 开启后，通过 add_parts 添加的分片信息将被忽略
+    pub fn complete_all(mut self) -> Self {
+        self.req.insert_header("x-oss-complete-all", "yes");
+        self
+    }
+    /// 设置上传回调
+    ///
+    /// 分片上传完成后，OSS会请求回调服务器，并将回调服务器的响应内容通过 callback_result 返回
+    ///
+    /// 回调失败时返回 Error::CallbackFailed ，此时文件已经上传成功
+    pub fn set_callback(mut self, callback: Callback) -> Self {
+        self.req
+            .insert_header("x-oss-callback", callback.callback_header());
+        if let Some(callback_var) = callback.callback_var_header() {
+            self.req.insert_header("x-oss-callback-var", callback_var);
+        }
+        self.callback = true;
         self
     }
     /// 完成分片上传
     ///
-    pub async fn send(mut self) -> Result<(), Error> {
-        // 构建body
-        let body = format!(
-            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
-            self.parts
-                .iter()
-                .map(|(part_num, e_tag)| format!(
-                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
-                    part_num, e_tag
-                ))
-                .collect::<Vec<_>>()
-                .join("")
-        );
-        let body_len = body.len();
-        self.req.set_body(body.into());
-        self.req.insert_header("Content-Length", body_len);
+    pub async fn send(mut self) -> Result<CompleteUploadResult, Error> {
+        // 构建body，complete-all模式下不允许携带body
+        if !self.req.headers.contains_key("x-oss-complete-all") {
+            self.parts.sort_by_key(|(part_number, _)| *part_number);
+            let mut xml = XmlBuilder::new();
+            xml.start("CompleteMultipartUpload");
+            for (part_num, e_tag) in &self.parts {
+                xml.start("Part")
+                    .element("PartNumber", part_num)
+                    .element("ETag", e_tag)
+                    .end("Part");
+            }
+            xml.end("CompleteMultipartUpload");
+            let body = xml.finish();
+            let body_len = body.len();
+            self.req.set_body(body.into());
+            self.req.insert_header("Content-Length", body_len);
+        }
+        let bucket = self.req.oss.bucket.clone().unwrap_or_default();
+        let key = self.req.oss.object.clone().unwrap_or_default();
         //上传文件
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
         let status_code = response.status();
         match status_code {
-            code if code.is_success() => Ok(()),
+            //回调失败时OSS返回203，文件已经上传成功
+            StatusCode::NON_AUTHORITATIVE_INFORMATION if self.callback => {
+                Err(callback_failed_error(response).await)
+            }
+            code if code.is_success() => {
+                let headers = response.headers();
+                let hash_crc64ecma = headers
+                    .get("x-oss-hash-crc64ecma")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok());
+                let version_id = headers
+                    .get("x-oss-version-id")
+                    .and_then(|v| v.to_str().ok().map(|s| s.to_owned()));
+                let request_id = request_id(headers);
+                //设置了上传回调时，返回的内容为回调服务器的响应
+                if self.callback {
+                    let e_tag = headers
+                        .get("ETag")
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.trim_matches('"').to_owned())
+                        .unwrap_or_default();
+                    let callback_result = to_bytes(response.into_body()).await?;
+                    return Ok(CompleteUploadResult {
+                        location: String::new(),
+                        bucket: bucket.to_string(),
+                        key: key.to_string(),
+                        e_tag,
+                        hash_crc64ecma,
+                        version_id,
+                        callback_result: Some(callback_result),
+                        request_id,
+                    });
+                }
+                let response_bytes = to_bytes(response.into_body())
+                    .await
+                    .map_err(|_| Error::OssInvalidResponse(None))?;
+                let mut result: CompleteUploadResult = serde_xml_rs::from_reader(&*response_bytes)
+                    .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                result.e_tag = result.e_tag.trim_matches('"').to_owned();
+                result.hash_crc64ecma = hash_crc64ecma;
+                result.version_id = version_id;
+                result.request_id = request_id;
+                Ok(result)
+            }
             _ => Err(normal_error(response).await),
         }
     }
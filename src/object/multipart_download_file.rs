@@ -0,0 +1,381 @@
+use super::{GetObject, GetObjectMeta};
+use crate::{
+    common::cancellable,
+    error::Error,
+    progress::{self, Aggregator, ProgressEvent, ProgressListener},
+    request::Oss,
+};
+use futures_util::{stream, StreamExt};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::{AsyncSeekExt, AsyncWriteExt, BufWriter, SeekFrom},
+};
+use tokio_util::sync::CancellationToken;
+
+// 断点记录
+//
+// 文件头部为下载任务信息，每行一个 key=value ，之后每下载完成一个分片，追加一行 part=分片号
+#[derive(Debug)]
+struct Checkpoint {
+    object: String,
+    e_tag: String,
+    file_size: u64,
+    part_size: u64,
+    parts: BTreeSet<u32>,
+}
+impl Checkpoint {
+    //读取断点记录，文件不存在或者内容无法解析时返回None
+    async fn load(path: &PathBuf) -> Option<Checkpoint> {
+        let content = fs::read_to_string(path).await.ok()?;
+        let mut values = BTreeMap::new();
+        let mut parts = BTreeSet::new();
+        for line in content.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            if key == "part" {
+                parts.insert(value.parse().ok()?);
+            } else {
+                values.insert(key, value);
+            }
+        }
+        Some(Checkpoint {
+            object: values.get("object")?.to_string(),
+            e_tag: values.get("e_tag")?.to_string(),
+            file_size: values.get("file_size")?.parse().ok()?,
+            part_size: values.get("part_size")?.parse().ok()?,
+            parts,
+        })
+    }
+    //写入新的断点记录
+    async fn save(&self, path: &PathBuf) -> Result<(), Error> {
+        let content = format!(
+            "object={}\ne_tag={}\nfile_size={}\npart_size={}\n",
+            self.object, self.e_tag, self.file_size, self.part_size
+        );
+        fs::write(path, content).await?;
+        Ok(())
+    }
+}
+
+/// 分片下载文件到本地
+///
+/// 将文件按范围拆分为多个分片并发下载，适用于大文件下载
+///
+/// 网络异常的分片会重新下载，默认最多重试2次
+///
+/// 下载过程中数据写入 保存路径.download 临时文件，全部分片完成后再重命名为保存路径；下载期间文件在OSS上发生变化时，将返回错误
+///
+/// 设置了断点记录文件时，每个分片下载完成后都会写入记录；下载中断后，使用同一个断点记录文件再次下载，将跳过已经下载完成的分片
+///
+/// ```
+/// let result = object
+///     .multipart_download_file()
+///     .set_checkpoint("/tmp/rust.mp4.checkpoint")
+///     .download_to_file("/data/rust.mp4")
+///     .await;
+/// ```
+pub struct DownloadFile {
+    oss: Oss,
+    part_size: u64,
+    parallel: usize,
+    max_retries: u32,
+    traffic_limit: Option<u64>,
+    checkpoint: Option<PathBuf>,
+    listener: Option<Arc<dyn ProgressListener>>,
+    cancellation_token: Option<CancellationToken>,
+}
+impl DownloadFile {
+    pub(super) fn new(oss: Oss) -> Self {
+        DownloadFile {
+            oss,
+            part_size: 10_485_760,
+            parallel: 4,
+            max_retries: 2,
+            traffic_limit: None,
+            checkpoint: None,
+            listener: None,
+            cancellation_token: None,
+        }
+    }
+    /// 设置分片大小，单位字节
+    ///
+    /// 默认值：10MB，最小值：100KB
+    pub fn set_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(102_400);
+        self
+    }
+    /// 设置同时下载的分片数量
+    ///
+    /// 默认值：4
+    pub fn set_parallel(mut self, parallel: usize) -> Self {
+        self.parallel = parallel.max(1);
+        self
+    }
+    /// 设置分片下载失败时的最大重试次数
+    ///
+    /// 默认值：2
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// 限制每个分片的下载速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.traffic_limit = Some(traffic_limit);
+        self
+    }
+    /// 设置写入本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置断点记录文件的路径
+    ///
+    /// 下载成功后，断点记录文件会被删除
+    pub fn set_checkpoint(mut self, checkpoint: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(checkpoint.into());
+        self
+    }
+    /// 设置文件下载进度的回调方法，汇总所有分片的下载进度，下载过程中实时调用
+    /// ```
+    /// let callback = Box::new(|downloaded_size: u64, total_size: u64| {
+    ///     let percentage = if total_size == 0 {
+    ///         100.0
+    ///     } else {
+    ///         (downloaded_size as f64) / (total_size as f64) * 100.00
+    ///     };
+    ///     println!("{:.2}%", percentage);
+    /// });
+    /// ```
+    pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
+        self.listener = Some(Arc::new(callback));
+        self
+    }
+    /// 设置下载进度的监听，除下载进度外，还会收到分片完成、重试、下载完成和失败的事件
+    ///
+    /// 与set_callback()只能生效一个，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+    /// 设置取消令牌，令牌被触发后停止下载，返回 Error::Cancelled
+    ///
+    /// 正在下载的分片会被中断；未设置断点记录时会删除临时文件，设置了断点记录时保留临时文件，可以继续下载
+    pub fn set_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+    /// 下载文件保存到磁盘
+    ///
+    /// 不支持网络路径，如果需要保存到smb\nfs等网络存储，请先挂载到本地，再使用本地路径地址
+    pub async fn download_to_file(self, save_path: &str) -> Result<(), Error> {
+        let listener = self.listener.clone();
+        let result = self.download(save_path).await;
+        progress::finish(listener.as_ref(), &result);
+        result
+    }
+    async fn download(self, save_path: &str) -> Result<(), Error> {
+        //判断路径
+        if save_path.contains("://") {
+            return Err(Error::PathNotSupported);
+        }
+        let temp_path = format!("{}.download", save_path);
+        //查询文件信息
+        let meta = GetObjectMeta::new(self.oss.without_cache()).send().await?;
+        let file_size: u64 = meta
+            .content_length
+            .parse()
+            .map_err(|_| Error::OssInvalidResponse(None))?;
+        let part_size = self.part_size;
+        let part_count = file_size.div_ceil(part_size) as u32;
+        let object = self.oss.object.clone().unwrap_or_default().to_string();
+        //读取断点记录，临时文件不存在时断点记录无效
+        let temp_size = fs::metadata(&temp_path).await.map(|v| v.len()).ok();
+        let checkpoint = match &self.checkpoint {
+            Some(path) => Checkpoint::load(path).await.filter(|v| {
+                v.object == object
+                    && v.e_tag == meta.e_tag
+                    && v.file_size == file_size
+                    && v.part_size == part_size
+                    && temp_size == Some(file_size)
+            }),
+            None => None,
+        };
+        let mut checkpoint = match checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => {
+                //创建目录
+                if let Some(dir) = Path::new(save_path).parent() {
+                    fs::create_dir_all(dir).await?;
+                }
+                //创建临时文件
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&temp_path)
+                    .await?;
+                file.set_len(file_size).await?;
+                let checkpoint = Checkpoint {
+                    object,
+                    e_tag: meta.e_tag.clone(),
+                    file_size,
+                    part_size,
+                    parts: BTreeSet::new(),
+                };
+                if let Some(path) = &self.checkpoint {
+                    checkpoint.save(path).await?;
+                }
+                checkpoint
+            }
+        };
+        //分片范围
+        let part_range = |part_number: u32| {
+            let start = (part_number as u64 - 1) * part_size;
+            (start, part_size.min(file_size - start))
+        };
+        //已下载的内容大小
+        let downloaded_size = checkpoint
+            .parts
+            .iter()
+            .map(|part_number| part_range(*part_number).1)
+            .sum::<u64>();
+        let progress = self
+            .listener
+            .clone()
+            .map(|listener| Aggregator::new(listener, downloaded_size, file_size));
+        if let Some(progress) = &progress {
+            progress.notify();
+        }
+        //并发下载剩余分片
+        let pending = (1..=part_count)
+            .filter(|part_number| !checkpoint.parts.contains(part_number))
+            .collect::<Vec<_>>();
+        let if_match = format!("\"{}\"", meta.e_tag);
+        let mut downloads = stream::iter(pending)
+            .map(|part_number| {
+                let oss = self.oss.clone();
+                let buffer_size = oss.buffer_size;
+                let temp_path = temp_path.clone();
+                let if_match = if_match.clone();
+                let (start, len) = part_range(part_number);
+                let (max_retries, traffic_limit) = (self.max_retries, self.traffic_limit);
+                let listener = progress.as_ref().map(|progress| progress.part());
+                async move {
+                    //网络异常时重新下载
+                    let mut retries = 0;
+                    loop {
+                        let mut get_object = GetObject::new(oss.clone())
+                            .set_range(start as usize, Some((start + len - 1) as usize))
+                            .set_if_match(&if_match);
+                        if let Some(traffic_limit) = traffic_limit {
+                            get_object = get_object.set_traffic_limit(traffic_limit);
+                        }
+                        let listener = listener.as_deref();
+                        let result = download_part(
+                            get_object,
+                            &temp_path,
+                            buffer_size,
+                            start,
+                            len,
+                            listener,
+                        )
+                        .await;
+                        match result {
+                            Err(e) if retries < max_retries && e.is_retryable() => {
+                                retries += 1;
+                                if let Some(listener) = &listener {
+                                    listener.on_event(&ProgressEvent::Retry {
+                                        part_number: Some(part_number),
+                                        attempt: retries,
+                                        error: e.to_string(),
+                                    });
+                                }
+                            }
+                            result => return result.map(|_| (part_number, len)),
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(self.parallel);
+        let mut checkpoint_writer = match &self.checkpoint {
+            Some(path) => Some(OpenOptions::new().append(true).open(path).await?),
+            None => None,
+        };
+        while let Some(result) = cancellable(self.cancellation_token.as_ref(), downloads.next())
+            .await
+            .transpose()
+        {
+            let (part_number, len) = match result.and_then(|result| result) {
+                Ok(part) => part,
+                Err(e) => {
+                    //未设置断点记录时，临时文件无法继续使用，直接删除
+                    if self.checkpoint.is_none() {
+                        drop(downloads);
+                        let _ = fs::remove_file(&temp_path).await;
+                    }
+                    return Err(e);
+                }
+            };
+            if let Some(writer) = &mut checkpoint_writer {
+                writer
+                    .write_all(format!("part={}\n", part_number).as_bytes())
+                    .await?;
+                writer.flush().await?;
+            }
+            checkpoint.parts.insert(part_number);
+            if let Some(progress) = &progress {
+                progress.emit(ProgressEvent::PartCompleted {
+                    part_number,
+                    size: len,
+                });
+            }
+        }
+        drop(downloads);
+        //下载完成，重命名临时文件
+        fs::rename(&temp_path, save_path).await?;
+        //删除断点记录
+        if let Some(path) = &self.checkpoint {
+            drop(checkpoint_writer);
+            let _ = fs::remove_file(path).await;
+        }
+        Ok(())
+    }
+}
+
+//下载一个分片，写入临时文件的指定位置
+async fn download_part(
+    get_object: GetObject,
+    temp_path: &str,
+    buffer_size: usize,
+    start: u64,
+    len: u64,
+    listener: Option<&dyn ProgressListener>,
+) -> Result<(), Error> {
+    let mut stream = get_object.download_to_stream().await?;
+    let mut file = OpenOptions::new().write(true).open(temp_path).await?;
+    file.seek(SeekFrom::Start(start)).await?;
+    let mut writer = BufWriter::with_capacity(buffer_size, file);
+    let mut downloaded_size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        if let Some(listener) = listener {
+            downloaded_size += chunk.len() as u64;
+            listener.on_event(&ProgressEvent::Progress {
+                transferred: downloaded_size,
+                total: len,
+            });
+        }
+    }
+    writer.flush().await?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
+use super::UploadSink;
 use crate::{
     common::{
-        invalid_metadata_key, url_encode, Acl, CacheControl, ContentDisposition, StorageClass,
+        check_tags, url_encode, Acl, CacheControl, ContentDisposition, ObjectMetadata, StorageClass,
     },
     error::{normal_error, Error},
     request::{Oss, OssRequest},
@@ -21,6 +22,7 @@ struct InitiateMultipartUploadResult {
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31992.html)
 pub struct InitUpload {
     req: OssRequest,
+    metadata: ObjectMetadata,
     tags: HashMap<String, String>,
 }
 impl InitUpload {
@@ -29,6 +31,7 @@ impl InitUpload {
         req.insert_query("uploads", "");
         InitUpload {
             req,
+            metadata: ObjectMetadata::new(),
             tags: HashMap::new(),
         }
     }
@@ -67,23 +70,34 @@ impl InitUpload {
     }
     /// 设置需要附加的metadata
     ///
-    /// key只允许存在英文字母、数字、连字符，如果存在其他字符，则metadata将直接被抛弃
+    /// key只允许存在英文字母、数字、连字符，不区分大小写；不符合要求时，发送请求时返回 Error::InvalidMetadata
     pub fn set_meta(mut self, key: impl ToString, value: impl ToString) -> Self {
-        let key = key.to_string();
-        if !invalid_metadata_key(&key) {
-            self.req
-                .insert_header(format!("x-oss-meta-{}", key.to_string()), value);
-        }
+        self.metadata.insert(key, value);
+        self
+    }
+    /// 设置需要附加的metadata，会覆盖之前设置的所有metadata
+    pub fn set_metadata(mut self, metadata: ObjectMetadata) -> Self {
+        self.metadata = metadata;
         self
     }
     /// 设置标签信息
+    ///
+    /// 标签限制见 check_tags，不符合要求时发送请求将返回 Error::InvalidTag
     pub fn set_tagging(mut self, key: impl ToString, value: impl ToString) -> Self {
         self.tags.insert(key.to_string(), value.to_string());
         self
     }
+    /// 转换为Sink，写入的数据自动分片上传，详见 UploadSink
+    pub fn into_sink(self) -> UploadSink {
+        UploadSink::new(self.req.oss.clone(), self)
+    }
     /// 将磁盘中的文件上传到OSS
     ///
     pub async fn send(mut self) -> Result<String, Error> {
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
@@ -1,14 +1,14 @@
 use crate::{
-    common::StorageClass,
+    common::{request_id, OssDateTime, PageFuture, Paginator, StorageClass},
     error::{normal_error, Error},
     request::{Oss, OssRequest},
 };
 use hyper::{body::to_bytes, Method};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use std::cmp;
 
 // 返回的内容
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListPartsResult {
     pub storage_class: StorageClass,
@@ -16,13 +16,16 @@ pub struct ListPartsResult {
     pub next_part_number_marker: u32,
     pub is_truncated: bool,
     pub part: Option<Vec<Part>>,
+    /// OSS返回的 x-oss-request-id ，向阿里云提交工单时需要提供
+    #[serde(skip_deserializing)]
+    pub request_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Part {
     pub part_number: u32,
-    pub last_modified: String,
+    pub last_modified: OssDateTime,
     pub e_tag: String,
     pub hash_crc64ecma: u64,
     pub size: u64,
@@ -39,6 +42,7 @@ impl ListParts {
     pub(super) fn new(oss: Oss, upload_id: impl ToString) -> Self {
         let mut req = OssRequest::new(oss, Method::GET);
         req.insert_query("uploadId", upload_id);
+        req.accept_gzip();
         ListParts { req }
     }
     /// 限定此次返回分片数据的最大个数
@@ -65,14 +69,33 @@ impl ListParts {
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
+                let request_id = request_id(response.headers());
                 let response_bytes = to_bytes(response.into_body())
                     .await
                     .map_err(|_| Error::OssInvalidResponse(None))?;
-                let result: ListPartsResult = serde_xml_rs::from_reader(&*response_bytes)
+                let mut result: ListPartsResult = serde_xml_rs::from_reader(&*response_bytes)
                     .map_err(|_| Error::OssInvalidResponse(Some(response_bytes)))?;
+                result.request_id = request_id;
                 Ok(result)
             }
             _ => Err(normal_error(response).await),
         }
     }
 }
+impl Paginator for ListParts {
+    type Item = Part;
+    fn next_page(self) -> PageFuture<Self> {
+        Box::pin(async move {
+            let mut next = ListParts {
+                req: self.req.clone_without_body(),
+            };
+            let result = self.send().await?;
+            let next = result.is_truncated.then(|| {
+                next.req
+                    .insert_query("part-number-marker", result.next_part_number_marker);
+                next
+            });
+            Ok((result.part.unwrap_or_default(), next))
+        })
+    }
+}
@@ -0,0 +1,281 @@
+use super::{
+    multipart_complete_upload::CompleteUploadResult, AbortUpload, CompleteUpload, InitUpload,
+    UploadPart,
+};
+use crate::{request::Oss, Error};
+use bytes::Bytes;
+use futures_util::{Future, Sink, Stream, StreamExt, TryStreamExt};
+use std::{
+    io, mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::io::ReaderStream;
+
+// 分片上传过程中的一个步骤
+enum Step {
+    Init(String),
+    Part(u32, String),
+    Complete(CompleteUploadResult),
+}
+
+type StepFuture = Pin<Box<dyn Future<Output = Result<Step, Error>> + Send>>;
+
+/// 以Sink的方式分片上传文件
+///
+/// 写入的数据在内存中累积到分片大小后上传一个分片，上传分片的同时可以继续写入下一个分片的数据；
+/// close()时上传剩余的数据并完成分片上传
+///
+/// 同时实现了 AsyncWrite ，shutdown()时完成分片上传，可以用于 tokio::io::copy 等通用的IO代码
+///
+/// 长度未知的数据流（如标准输入、实时编码的输出）可以直接使用 send_stream() 或 send_reader() 上传
+///
+/// 首个分片上传前才会初始化分片上传；任一请求失败后不能继续写入，可以通过 upload_id() 取消分片上传，清理已上传的分片
+///
+/// ```
+/// use futures_util::StreamExt;
+///
+/// let mut sink = object
+///     .multipart_init_upload()
+///     .set_mime("text/csv")
+///     .into_sink()
+///     .set_part_size(16 * 1024 * 1024);
+/// stream.forward(&mut sink).await?;
+/// println!("{:?}", sink.result());
+/// ```
+pub struct UploadSink {
+    oss: Oss,
+    init: Option<InitUpload>,
+    part_size: usize,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    parts: Vec<(u32, String)>,
+    pending: Option<StepFuture>,
+    result: Option<CompleteUploadResult>,
+    failed: bool,
+}
+
+impl UploadSink {
+    pub(super) fn new(oss: Oss, init: InitUpload) -> Self {
+        UploadSink {
+            oss,
+            init: Some(init),
+            part_size: 8_388_608,
+            buffer: Vec::new(),
+            upload_id: None,
+            parts: Vec::new(),
+            pending: None,
+            result: None,
+            failed: false,
+        }
+    }
+    //使用已经初始化的分片上传
+    #[cfg(feature = "object-store")]
+    pub(crate) fn from_upload_id(oss: Oss, upload_id: String) -> Self {
+        UploadSink {
+            oss,
+            init: None,
+            part_size: 8_388_608,
+            buffer: Vec::new(),
+            upload_id: Some(upload_id),
+            parts: Vec::new(),
+            pending: None,
+            result: None,
+            failed: false,
+        }
+    }
+    /// 设置分片大小，默认8MB
+    ///
+    /// 最小为100KB，小于100KB时自动调整；内存中最多缓存两个分片的数据
+    pub fn set_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.max(102_400);
+        self
+    }
+    /// 分片上传的Upload ID，尚未初始化分片上传时为None
+    pub fn upload_id(&self) -> Option<&str> {
+        self.upload_id.as_deref()
+    }
+    /// 完成分片上传的结果，close()成功后可用
+    pub fn result(&self) -> Option<&CompleteUploadResult> {
+        self.result.as_ref()
+    }
+    /// 上传数据流中的全部数据并完成分片上传，适用于无法预先知道长度的数据流
+    ///
+    /// 失败时自动取消分片上传，清理已上传的分片
+    ///
+    /// ```
+    /// let result = object
+    ///     .multipart_upload_sink()
+    ///     .set_part_size(16 * 1024 * 1024)
+    ///     .send_stream(encoder_output)
+    ///     .await?;
+    /// ```
+    pub async fn send_stream<S, E>(mut self, stream: S) -> Result<CompleteUploadResult, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+        Error: From<E>,
+    {
+        if let Err(e) = stream.map_err(Error::from).forward(&mut self).await {
+            if let Some(upload_id) = &self.upload_id {
+                let _ = AbortUpload::new(self.oss.clone(), upload_id).send().await;
+            }
+            return Err(e);
+        }
+        self.result.take().ok_or(Error::UploadClosed)
+    }
+    /// 读取AsyncRead中的全部数据上传并完成分片上传，读取到EOF时结束
+    ///
+    /// 失败时自动取消分片上传，清理已上传的分片
+    ///
+    /// ```
+    /// let result = object
+    ///     .multipart_upload_sink()
+    ///     .send_reader(tokio::io::stdin())
+    ///     .await?;
+    /// ```
+    pub async fn send_reader<R>(self, reader: R) -> Result<CompleteUploadResult, Error>
+    where
+        R: AsyncRead,
+    {
+        let stream = ReaderStream::with_capacity(reader, self.oss.chunk_size);
+        self.send_stream(stream).await
+    }
+    //开始下一个步骤，last为true时上传剩余的所有数据
+    fn start_step(&mut self, last: bool) {
+        let future: StepFuture = match (&self.upload_id, self.init.take()) {
+            (None, Some(init)) => Box::pin(async move { init.send().await.map(Step::Init) }),
+            (Some(upload_id), _) if !self.buffer.is_empty() || self.parts.is_empty() => {
+                let content = if last || self.buffer.len() <= self.part_size {
+                    mem::take(&mut self.buffer)
+                } else {
+                    let rest = self.buffer.split_off(self.part_size);
+                    mem::replace(&mut self.buffer, rest)
+                };
+                let part_number = self.parts.len() as u32 + 1;
+                let part = UploadPart::new(self.oss.clone(), part_number, upload_id);
+                Box::pin(async move {
+                    part.send_content(content)
+                        .await
+                        .map(|e_tag| Step::Part(part_number, e_tag))
+                })
+            }
+            (Some(upload_id), _) => {
+                let complete = CompleteUpload::new(self.oss.clone(), upload_id)
+                    .add_parts(mem::take(&mut self.parts));
+                Box::pin(async move { complete.send().await.map(Step::Complete) })
+            }
+            //初始化请求正在进行时不会调用，不会出现
+            (None, None) => return,
+        };
+        self.pending = Some(future);
+    }
+    //等待正在进行的步骤完成
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if self.failed {
+            return Poll::Ready(Err(Error::UploadClosed));
+        }
+        let pending = match self.pending.as_mut() {
+            Some(pending) => pending,
+            None => return Poll::Ready(Ok(())),
+        };
+        let step = match pending.as_mut().poll(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(step) => step,
+        };
+        self.pending = None;
+        //失败后已经取出的数据无法重新上传，不再继续
+        self.failed = step.is_err();
+        match step? {
+            Step::Init(upload_id) => self.upload_id = Some(upload_id),
+            Step::Part(part_number, e_tag) => self.parts.push((part_number, e_tag)),
+            Step::Complete(result) => self.result = Some(result),
+        }
+        Poll::Ready(Ok(()))
+    }
+    //上传所有已满的分片
+    fn poll_full_parts(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        loop {
+            if self.buffer.len() < self.part_size {
+                return Poll::Ready(Ok(()));
+            }
+            match self.poll_pending(cx) {
+                Poll::Ready(Ok(())) => self.start_step(false),
+                other => return other,
+            }
+        }
+    }
+}
+
+impl Sink<Bytes> for UploadSink {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        //正在上传的分片与缓存中的数据并行，缓存已满时才等待
+        if this.buffer.len() < this.part_size {
+            if let Poll::Ready(Err(e)) = this.poll_pending(cx) {
+                return Poll::Ready(Err(e));
+            }
+            return Poll::Ready(Ok(()));
+        }
+        this.poll_full_parts(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        if this.failed || this.result.is_some() {
+            return Err(Error::UploadClosed);
+        }
+        this.buffer.extend_from_slice(&item);
+        Ok(())
+    }
+
+    /// 上传所有已满的分片，不足一个分片的数据继续缓存，直到close()时上传
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        match this.poll_full_parts(cx) {
+            Poll::Ready(Ok(())) => this.poll_pending(cx),
+            other => other,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        loop {
+            if let Poll::Ready(Err(e)) = this.poll_full_parts(cx) {
+                return Poll::Ready(Err(e));
+            }
+            match this.poll_pending(cx) {
+                Poll::Ready(Ok(())) if this.result.is_some() => return Poll::Ready(Ok(())),
+                Poll::Ready(Ok(())) => this.start_step(true),
+                other => return other,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for UploadSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match Sink::<Bytes>::poll_ready(self.as_mut(), cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
+            Poll::Pending => return Poll::Pending,
+        }
+        self.start_send(Bytes::copy_from_slice(buf))
+            .map_err(io::Error::from)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_flush(self, cx).map_err(io::Error::from)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_close(self, cx).map_err(io::Error::from)
+    }
+}
@@ -0,0 +1,426 @@
+use super::{
+    multipart_complete_upload::CompleteUploadResult, AbortUpload, CompleteUpload, InitUpload,
+    UploadPart,
+};
+use crate::{
+    common::cancellable,
+    error::{Error, OssErrorCode},
+    progress::{self, Aggregator, ProgressEvent, ProgressListener},
+    request::Oss,
+};
+use futures_util::{stream, StreamExt};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc, time::UNIX_EPOCH};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+use tokio_util::sync::CancellationToken;
+
+// 断点记录
+//
+// 文件头部为上传任务信息，每行一个 key=value ，之后每上传成功一个分片，追加一行 part=分片号,ETag
+//
+// 追加分片记录时进程中断可能留下不完整的行，读取时跳过，对应的分片会重新上传
+#[derive(Debug)]
+struct Checkpoint {
+    object: String,
+    file_size: u64,
+    file_modified: u64,
+    part_size: u64,
+    upload_id: String,
+    parts: BTreeMap<u32, String>,
+}
+impl Checkpoint {
+    //读取断点记录，文件不存在或者上传任务信息无法解析时返回None
+    async fn load(path: &PathBuf) -> Option<Checkpoint> {
+        let content = fs::read_to_string(path).await.ok()?;
+        let mut values = BTreeMap::new();
+        let mut parts = BTreeMap::new();
+        //只读取以换行结尾的完整行
+        for line in content.split_inclusive('\n') {
+            let Some((key, value)) = line.strip_suffix('\n').and_then(|v| v.split_once('=')) else {
+                continue;
+            };
+            if key == "part" {
+                let part = value
+                    .split_once(',')
+                    .and_then(|(part_number, e_tag)| Some((part_number.parse().ok()?, e_tag)));
+                if let Some((part_number, e_tag)) = part.filter(|(_, e_tag)| !e_tag.is_empty()) {
+                    parts.insert(part_number, e_tag.to_owned());
+                }
+            } else {
+                values.insert(key, value);
+            }
+        }
+        Some(Checkpoint {
+            object: values.get("object")?.to_string(),
+            file_size: values.get("file_size")?.parse().ok()?,
+            file_modified: values.get("file_modified")?.parse().ok()?,
+            part_size: values.get("part_size")?.parse().ok()?,
+            upload_id: values.get("upload_id")?.to_string(),
+            parts,
+        })
+    }
+    //写入新的断点记录，先写入临时文件再替换，中断时不会留下不完整的上传任务信息
+    async fn save(&self, path: &PathBuf) -> Result<(), Error> {
+        let mut content = format!(
+            "object={}\nfile_size={}\nfile_modified={}\npart_size={}\nupload_id={}\n",
+            self.object, self.file_size, self.file_modified, self.part_size, self.upload_id
+        );
+        for (part_number, e_tag) in &self.parts {
+            content.push_str(&format!("part={},{}\n", part_number, e_tag));
+        }
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, content).await?;
+        fs::rename(&temp_path, path).await?;
+        Ok(())
+    }
+}
+
+/// 分片上传本地文件
+///
+/// 自动完成初始化分片上传、并发上传分片、完成分片上传的全部流程，适用于大文件上传
+///
+/// 每个分片上传时都会校验CRC64，校验失败或者网络异常的分片会重新上传，默认最多重试2次
+///
+/// 设置了断点记录文件时，每个分片上传成功后都会写入记录；上传中断后，使用同一个断点记录文件再次上传，将跳过已经上传成功的分片。本地文件发生变化或者上传任务已被取消（NoSuchUpload）时，断点记录失效，将重新上传
+///
+/// ```
+/// let result = object
+///     .multipart_upload_file()
+///     .set_part_size(20 * 1024 * 1024)
+///     .set_checkpoint("/tmp/rust.mp4.checkpoint")
+///     .send_file("/data/rust.mp4")
+///     .await;
+/// ```
+pub struct UploadFile {
+    oss: Oss,
+    mime: Option<String>,
+    part_size: u64,
+    parallel: usize,
+    max_retries: u32,
+    traffic_limit: Option<u64>,
+    checkpoint: Option<PathBuf>,
+    listener: Option<Arc<dyn ProgressListener>>,
+    cancellation_token: Option<CancellationToken>,
+}
+impl UploadFile {
+    pub(super) fn new(oss: Oss) -> Self {
+        UploadFile {
+            oss,
+            mime: None,
+            part_size: 10_485_760,
+            parallel: 4,
+            max_retries: 2,
+            traffic_limit: None,
+            checkpoint: None,
+            listener: None,
+            cancellation_token: None,
+        }
+    }
+    /// 设置文件的mime类型
+    ///
+    /// 如果未设置mime类型，则使用默认mime类型（application/octet-stream）
+    pub fn set_mime(mut self, mime: impl ToString) -> Self {
+        self.mime = Some(mime.to_string());
+        self
+    }
+    /// 设置分片大小，单位字节
+    ///
+    /// 默认值：10MB，最小值：100KB；分片数量最多为10000，如果文件过大，分片大小会自动调整
+    pub fn set_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(102_400);
+        self
+    }
+    /// 设置同时上传的分片数量
+    ///
+    /// 默认值：4
+    pub fn set_parallel(mut self, parallel: usize) -> Self {
+        self.parallel = parallel.max(1);
+        self
+    }
+    /// 设置分片上传失败时的最大重试次数
+    ///
+    /// 默认值：2
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// 限制每个分片的上传速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.traffic_limit = Some(traffic_limit);
+        self
+    }
+    /// 设置读取本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.oss.chunk_size = chunk_size.max(1024);
+        self
+    }
+    /// 设置断点记录文件的路径
+    ///
+    /// 上传成功后，断点记录文件会被删除
+    pub fn set_checkpoint(mut self, checkpoint: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(checkpoint.into());
+        self
+    }
+    /// 设置文件上传进度的回调方法，汇总所有分片的上传进度，上传过程中实时调用
+    /// ```
+    /// let callback = Box::new(|uploaded_size: u64, total_size: u64| {
+    ///     let percentage = if total_size == 0 {
+    ///         100.0
+    ///     } else {
+    ///         (uploaded_size as f64) / (total_size as f64) * 100.00
+    ///     };
+    ///     println!("{:.2}%", percentage);
+    /// });
+    /// ```
+    pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
+        self.listener = Some(Arc::new(callback));
+        self
+    }
+    /// 设置上传进度的监听，除上传进度外，还会收到分片完成、重试、上传完成和失败的事件
+    ///
+    /// 与set_callback()只能生效一个，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+    /// 设置取消令牌，令牌被触发后停止上传，返回 Error::Cancelled
+    ///
+    /// 正在上传的分片会被中断；未设置断点记录时会取消分片上传，设置了断点记录时保留已上传的分片，可以继续上传
+    pub fn set_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+    /// 将磁盘中的文件上传到OSS
+    ///
+    pub async fn send_file(self, file: impl ToString) -> Result<CompleteUploadResult, Error> {
+        let listener = self.listener.clone();
+        let result = self.upload(file.to_string()).await;
+        progress::finish(listener.as_ref(), &result);
+        result
+    }
+    async fn upload(self, file_path: String) -> Result<CompleteUploadResult, Error> {
+        match self.upload_file(&file_path, true).await {
+            //断点记录中的上传任务已经被取消或者清理，重新开始上传
+            Err(e)
+                if self.checkpoint.is_some()
+                    && matches!(e.error_code(), Some(OssErrorCode::NoSuchUpload)) =>
+            {
+                self.upload_file(&file_path, false).await
+            }
+            result => result,
+        }
+    }
+    //resume为false时忽略断点记录，重新初始化上传任务
+    async fn upload_file(
+        &self,
+        file_path: &str,
+        resume: bool,
+    ) -> Result<CompleteUploadResult, Error> {
+        //读取文件信息
+        let metadata = fs::metadata(&file_path).await?;
+        let file_size = metadata.len();
+        let file_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|v| v.duration_since(UNIX_EPOCH).ok())
+            .map(|v| v.as_secs())
+            .unwrap_or_default();
+        //计算分片大小，分片数量不超过10000
+        let part_size = self.part_size.max(file_size.div_ceil(10_000));
+        let part_count = file_size.div_ceil(part_size).max(1) as u32;
+        let object = self.oss.object.clone().unwrap_or_default().to_string();
+        //读取断点记录
+        let checkpoint = match &self.checkpoint {
+            Some(path) if resume => Checkpoint::load(path).await.filter(|v| {
+                v.object == object
+                    && v.file_size == file_size
+                    && v.file_modified == file_modified
+                    && v.part_size == part_size
+            }),
+            _ => None,
+        };
+        let mut checkpoint = match checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let mut init = InitUpload::new(self.oss.clone());
+                if let Some(mime) = &self.mime {
+                    init = init.set_mime(mime);
+                }
+                let upload_id = init.send().await?;
+                let checkpoint = Checkpoint {
+                    object,
+                    file_size,
+                    file_modified,
+                    part_size,
+                    upload_id,
+                    parts: BTreeMap::new(),
+                };
+                if let Some(path) = &self.checkpoint {
+                    checkpoint.save(path).await?;
+                }
+                checkpoint
+            }
+        };
+        let upload_id = checkpoint.upload_id.clone();
+        //分片大小
+        let part_len = |part_number: u32| {
+            let start = (part_number as u64 - 1) * part_size;
+            (start, part_size.min(file_size - start))
+        };
+        //已上传的内容大小
+        let uploaded_size = checkpoint
+            .parts
+            .keys()
+            .map(|part_number| part_len(*part_number).1)
+            .sum::<u64>();
+        let progress = self
+            .listener
+            .clone()
+            .map(|listener| Aggregator::new(listener, uploaded_size, file_size));
+        if let Some(progress) = &progress {
+            progress.notify();
+        }
+        //并发上传剩余分片
+        let pending = (1..=part_count)
+            .filter(|part_number| !checkpoint.parts.contains_key(part_number))
+            .collect::<Vec<_>>();
+        let mut uploads = stream::iter(pending)
+            .map(|part_number| {
+                let oss = self.oss.clone();
+                let upload_id = upload_id.clone();
+                let file_path = file_path.to_owned();
+                let (start, len) = part_len(part_number);
+                let (max_retries, traffic_limit) = (self.max_retries, self.traffic_limit);
+                let listener = progress.as_ref().map(|progress| progress.part());
+                async move {
+                    //分片数据校验失败或者网络异常时重新上传
+                    let mut retries = 0;
+                    loop {
+                        let mut part =
+                            UploadPart::new(oss.clone(), part_number, &upload_id).verify_checksum();
+                        if let Some(traffic_limit) = traffic_limit {
+                            part = part.set_traffic_limit(traffic_limit);
+                        }
+                        if let Some(listener) = &listener {
+                            part = part.set_progress_listener(listener.clone());
+                        }
+                        let result = part.send_file_range(&file_path, start, len).await;
+                        match result {
+                            Err(e) if retries < max_retries && e.is_retryable() => {
+                                retries += 1;
+                                if let Some(listener) = &listener {
+                                    listener.on_event(&ProgressEvent::Retry {
+                                        part_number: Some(part_number),
+                                        attempt: retries,
+                                        error: e.to_string(),
+                                    });
+                                }
+                            }
+                            result => return result.map(|e_tag| (part_number, e_tag, len)),
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(self.parallel);
+        let mut checkpoint_writer = match &self.checkpoint {
+            Some(path) => Some(OpenOptions::new().append(true).open(path).await?),
+            None => None,
+        };
+        while let Some(result) = cancellable(self.cancellation_token.as_ref(), uploads.next())
+            .await
+            .transpose()
+        {
+            let (part_number, e_tag, len) = match result.and_then(|result| result) {
+                Ok(part) => part,
+                Err(e) => {
+                    //未设置断点记录时，无法续传，直接取消分片上传
+                    if self.checkpoint.is_none() {
+                        drop(uploads);
+                        let _ = AbortUpload::new(self.oss.clone(), &upload_id).send().await;
+                    }
+                    return Err(e);
+                }
+            };
+            if let Some(writer) = &mut checkpoint_writer {
+                writer
+                    .write_all(format!("part={},{}\n", part_number, e_tag).as_bytes())
+                    .await?;
+                writer.flush().await?;
+            }
+            checkpoint.parts.insert(part_number, e_tag);
+            if let Some(progress) = &progress {
+                progress.emit(ProgressEvent::PartCompleted {
+                    part_number,
+                    size: len,
+                });
+            }
+        }
+        drop(uploads);
+        //完成分片上传
+        let result = CompleteUpload::new(self.oss.clone(), &upload_id)
+            .add_parts(checkpoint.parts.into_iter().collect())
+            .send()
+            .await?;
+        //删除断点记录
+        if let Some(path) = &self.checkpoint {
+            drop(checkpoint_writer);
+            let _ = fs::remove_file(path).await;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn checkpoint_skips_truncated_part_lines() {
+        let path = std::env::temp_dir().join(format!("checkpoint-{}", std::process::id()));
+        let checkpoint = block_on(async {
+            let mut checkpoint = Checkpoint {
+                object: "a/b.mp4".to_owned(),
+                file_size: 300,
+                file_modified: 1,
+                part_size: 100,
+                upload_id: "upload".to_owned(),
+                parts: BTreeMap::new(),
+            };
+            checkpoint.parts.insert(1, "etag1".to_owned());
+            checkpoint.save(&path).await.unwrap();
+            let mut writer = OpenOptions::new().append(true).open(&path).await.unwrap();
+            writer
+                .write_all(b"part=x,etag\npart=2\npart=3,eta")
+                .await
+                .unwrap();
+            drop(writer);
+            let checkpoint = Checkpoint::load(&path).await;
+            let _ = fs::remove_file(&path).await;
+            checkpoint
+        })
+        .unwrap();
+        assert_eq!(checkpoint.upload_id, "upload");
+        assert_eq!(checkpoint.part_size, 100);
+        assert_eq!(
+            checkpoint.parts.into_iter().collect::<Vec<_>>(),
+            vec![(1, "etag1".to_owned())]
+        );
+    }
+}
@@ -1,10 +1,20 @@
 use crate::{
+    checksum::{Crc64, Md5},
     error::{normal_error, Error},
+    progress::{ProgressEvent, ProgressListener},
     request::{Oss, OssRequest},
 };
-use futures_util::StreamExt;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
 use hyper::{header, Body, Method};
-use tokio::{fs::File, io::BufReader};
+use std::{
+    error::Error as StdError,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, BufReader, SeekFrom},
+};
 use tokio_util::io::ReaderStream;
 
 /// 初始化分片上传
@@ -12,7 +22,8 @@ use tokio_util::io::ReaderStream;
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31993.html)
 pub struct UploadPart {
     req: OssRequest,
-    callback: Option<Box<dyn Fn(u64, u64) + Send + Sync + 'static>>,
+    verify: bool,
+    listener: Option<Arc<dyn ProgressListener>>,
 }
 impl UploadPart {
     pub(super) fn new(oss: Oss, part_number: u32, upload_id: impl ToString) -> Self {
@@ -21,10 +32,37 @@ impl UploadPart {
         req.insert_query("uploadId", upload_id);
         UploadPart {
             req,
-            callback: None,
+            verify: false,
+            listener: None,
         }
     }
-    /// 设置文件上传进度的回调方法，此方法仅对send_file()有效
+    /// 校验上传的分片数据
+    ///
+    /// send_content()会携带Content-MD5，由OSS进行校验；其他方法会在上传时计算CRC64，并与OSS返回的 x-oss-hash-crc64ecma 比对
+    ///
+    /// 校验不通过时，返回 Error::ChecksumMismatch
+    pub fn verify_checksum(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+    /// 限制上传速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.req.insert_header("x-oss-traffic-limit", traffic_limit);
+        self
+    }
+    /// 设置读取本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.req.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.req.oss.chunk_size = chunk_size.max(1024);
+        self
+    }
+    /// 设置文件上传进度的回调方法，此方法对send_file()、send_file_range()、send_reader()和send_stream()有效
     /// ```
     /// let callback = Box::new(|uploaded_size: u64, total_size: u64| {
     ///     let percentage = if total_size == 0 {
@@ -36,13 +74,18 @@ impl UploadPart {
     /// });
     /// ```
     pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
-        self.callback = Some(callback);
+        self.listener = Some(Arc::new(callback));
+        self
+    }
+    /// 设置上传进度的监听，与set_callback()的生效范围相同，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.listener = Some(listener);
         self
     }
     /// 将磁盘中的文件上传到OSS
     ///
     /// 返回值为ETag
-    pub async fn send_file(mut self, file: impl ToString) -> Result<String, Error> {
+    pub async fn send_file(self, file: impl ToString) -> Result<String, Error> {
         //打开文件
         let file = File::open(file.to_string()).await?;
         //读取文件大小
@@ -51,39 +94,96 @@ impl UploadPart {
             return Err(Error::InvalidFileSize);
         }
         //初始化文件内容读取数据流
-        let buf = BufReader::with_capacity(131072, file);
-        let stream = ReaderStream::with_capacity(buf, 16384);
-        //初始化已上传内容大小
+        let buf = BufReader::with_capacity(self.req.oss.buffer_size, file);
+        let stream = ReaderStream::with_capacity(buf, self.req.oss.chunk_size);
+        self.send_stream(stream, file_size).await
+    }
+    /// 将磁盘中文件的一部分上传到OSS
+    ///
+    /// 从文件的offset位置开始，读取len字节作为分片内容，适用于不预先拆分文件，直接从大文件中读取分片
+    ///
+    /// 返回值为ETag
+    pub async fn send_file_range(
+        self,
+        file: impl ToString,
+        offset: u64,
+        len: u64,
+    ) -> Result<String, Error> {
+        //打开文件
+        let mut file = File::open(file.to_string()).await?;
+        //检查读取范围
+        let file_size = file.metadata().await?.len();
+        if offset.saturating_add(len) > file_size {
+            return Err(Error::InvalidFileSize);
+        }
+        file.seek(SeekFrom::Start(offset)).await?;
+        //初始化文件内容读取数据流
+        let buf = BufReader::with_capacity(self.req.oss.buffer_size, file.take(len));
+        let stream = ReaderStream::with_capacity(buf, self.req.oss.chunk_size);
+        self.send_stream(stream, len).await
+    }
+    /// 从AsyncRead中读取len字节作为分片内容上传到OSS
+    ///
+    /// 适用于分片内容由数据管道实时生成（如边压缩边上传）的场景，无需将分片完整读取到内存中
+    ///
+    /// 返回值为ETag
+    pub async fn send_reader<R>(self, reader: R, len: u64) -> Result<String, Error>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        let stream = ReaderStream::with_capacity(reader.take(len), self.req.oss.chunk_size);
+        self.send_stream(stream, len).await
+    }
+    /// 将数据流上传到OSS
+    ///
+    /// len必须等于数据流的总字节数，否则OSS会拒绝请求
+    ///
+    /// 返回值为ETag
+    pub async fn send_stream<S, E>(mut self, stream: S, len: u64) -> Result<String, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+    {
+        if len >= 5_368_709_120 {
+            return Err(Error::InvalidFileSize);
+        }
+        self.req.insert_header(header::CONTENT_LENGTH, len);
+        //初始化已上传内容大小和CRC64
         let mut uploaded_size = 0;
+        let crc64 = Arc::new(Mutex::new(Crc64::new()));
+        let crc64_writer = self.verify.then(|| crc64.clone());
+        let listener = self.listener.take();
         //初始化上传请求
         let body = Body::wrap_stream(stream.map(move |result| match result {
             Ok(chunk) => {
-                if let Some(callback) = &self.callback {
-                    let upload_size = chunk.len() as u64;
-                    uploaded_size = uploaded_size + upload_size;
-                    callback(uploaded_size, file_size);
+                if let Some(crc64) = &crc64_writer {
+                    if let Ok(mut crc64) = crc64.lock() {
+                        crc64.update(&chunk);
+                    }
+                }
+                if let Some(listener) = &listener {
+                    uploaded_size += chunk.len() as u64;
+                    listener.on_event(&ProgressEvent::Progress {
+                        transferred: uploaded_size,
+                        total: len,
+                    });
                 }
                 Ok(chunk)
             }
             Err(err) => Err(err),
         }));
         self.req.set_body(body);
-        //上传文件
-        let response = self.req.send_to_oss()?.await?;
-        //拆解响应消息
-        let status_code = response.status();
-        match status_code {
-            code if code.is_success() => {
-                let e_tag = response
-                    .headers()
-                    .get("ETag")
-                    .map(|v| String::from_utf8(v.as_bytes().to_vec()).ok())
-                    .flatten()
-                    .unwrap_or_else(|| String::new());
-                Ok(e_tag)
+        let verify = self.verify;
+        let e_tag = self.send_request(|remote_crc64| {
+            let local_crc64 = crc64.lock().map(|v| v.finalize()).unwrap_or_default();
+            match remote_crc64 {
+                Some(remote_crc64) if verify && remote_crc64 != local_crc64.to_string() => Err(
+                    Error::ChecksumMismatch(local_crc64.to_string(), remote_crc64.to_owned()),
+                ),
+                _ => Ok(()),
             }
-            _ => Err(normal_error(response).await),
-        }
+        });
+        e_tag.await
     }
     /// 将内存中的数据上传到OSS
     ///
@@ -95,20 +195,38 @@ impl UploadPart {
             return Err(Error::InvalidFileSize);
         }
         self.req.insert_header(header::CONTENT_LENGTH, content_size);
+        //计算Content-MD5，由OSS校验
+        if self.verify {
+            let mut md5 = Md5::new();
+            md5.update(&content);
+            let content_md5 = md5.finalize_base64();
+            self.req.insert_header("Content-MD5", content_md5);
+        }
         //插入body
         self.req.set_body(content.into());
+        self.send_request(|_| Ok(())).await
+    }
+    //发送请求，返回ETag；check用于校验OSS返回的 x-oss-hash-crc64ecma
+    async fn send_request(
+        self,
+        check: impl FnOnce(Option<&str>) -> Result<(), Error>,
+    ) -> Result<String, Error> {
         //上传文件
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
         let status_code = response.status();
         match status_code {
             code if code.is_success() => {
-                let e_tag = response
-                    .headers()
+                let headers = response.headers();
+                check(
+                    headers
+                        .get("x-oss-hash-crc64ecma")
+                        .and_then(|v| v.to_str().ok()),
+                )?;
+                let e_tag = headers
                     .get("ETag")
-                    .map(|v| String::from_utf8(v.as_bytes().to_vec()).ok())
-                    .flatten()
-                    .unwrap_or_else(|| String::new());
+                    .and_then(|v| String::from_utf8(v.as_bytes().to_vec()).ok())
+                    .unwrap_or_default();
                 Ok(e_tag)
             }
             _ => Err(normal_error(response).await),
@@ -1,15 +1,20 @@
 use super::{
     del_object::DelObject, AbortUpload, AppendObject, CompleteUpload, CopyObject, CopyToPart,
-    DelObjectTagging, GetObject, GetObjectAcl, GetObjectMeta, GetObjectTagging, GetObjectUrl,
-    GetSymlink, HeadObject, InitUpload, ListParts, PutObject, PutObjectAcl, PutObjectTagging,
-    PutSymlink, RestoreObject, UploadPart,
+    DelObjectTagging, DownloadFile, GetObject, GetObjectAcl, GetObjectMeta, GetObjectTagging,
+    GetObjectUrl, GetSymlink, HeadObject, InitUpload, ListParts, PipeObject, PutObject,
+    PutObjectAcl, PutObjectTagging, PutSymlink, RestoreObject, UploadFile, UploadPart, UploadSink,
 };
-use crate::{common::Acl, request::Oss};
+use crate::{
+    common::{Acl, RequestRecorder, RestoreStatus},
+    request::Oss,
+    Error,
+};
+use std::time::Duration;
 
 /// OSS文件，实现了上传文件、删除文件等API
 #[derive(Debug, Clone)]
 pub struct OssObject {
-    oss: Oss,
+    pub(crate) oss: Oss,
 }
 
 impl OssObject {
@@ -17,6 +22,47 @@ impl OssObject {
         oss.set_object(object);
         OssObject { oss }
     }
+    /// 设置单次请求的超时时间，覆盖OssClient的配置，从发起连接开始到收到响应头为止
+    ///
+    /// 只影响通过此OssObject发起的请求，超时后返回 Error::Timeout
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.oss.timeout = Some(timeout);
+        self
+    }
+    /// 设置读取响应body的超时时间，覆盖OssClient的配置，两次收到数据的间隔超过此时间时中断读取
+    pub fn set_read_timeout(mut self, timeout: Duration) -> Self {
+        self.oss.read_timeout = Some(timeout);
+        self
+    }
+    /// 记录通过此OssObject发起的每个请求的Request ID，包括成功的请求
+    pub fn set_request_recorder(mut self, recorder: &RequestRecorder) -> Self {
+        self.oss.request_recorder = Some(recorder.clone());
+        self
+    }
+    /// 声明由请求者支付请求和流量费用，用于访问开启了请求者付费模式的存储空间
+    ///
+    /// 通过此OssObject发起的所有请求都会携带 x-oss-request-payer: requester 请求头
+    pub fn set_request_payer(mut self) -> Self {
+        self.oss.request_payer = true;
+        self
+    }
+    /// 设置STS临时凭证的安全令牌，覆盖OssClient的凭证中的令牌
+    ///
+    /// 只影响通过此OssObject发起的请求；AccessKey仍然使用OssClient的共享凭证，update_credentials 和凭证提供者的刷新对此OssObject同样生效
+    pub fn set_security_token(mut self, security_token: impl ToString) -> Self {
+        self.oss.set_security_token(security_token);
+        self
+    }
+    /// 设置读写本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置上传时每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.oss.chunk_size = chunk_size.max(1024);
+        self
+    }
     /// 上传文件到OSS
     pub fn put_object(&self) -> PutObject {
         PutObject::new(self.oss.clone())
@@ -61,6 +107,15 @@ impl OssObject {
     pub fn restore_object(&self) -> RestoreObject {
         RestoreObject::new(self.oss.clone())
     }
+    /// 查询归档文件的解冻状态
+    ///
+    /// 通过HeadObject读取响应头 x-oss-restore ，不使用元信息缓存
+    pub async fn restore_status(&self) -> Result<RestoreStatus, Error> {
+        let headers = HeadObject::new(self.oss.without_cache()).send().await?;
+        Ok(RestoreStatus::from_header(
+            headers.get("x-oss-restore").map(|v| v.as_str()),
+        ))
+    }
     /// 设置文件ACL
     pub fn put_object_acl(&self, acl: Acl) -> PutObjectAcl {
         PutObjectAcl::new(self.oss.clone(), acl)
@@ -109,6 +164,22 @@ impl OssObject {
     pub fn multipart_abort_upload(&self, upload_id: impl ToString) -> AbortUpload {
         AbortUpload::new(self.oss.clone(), upload_id)
     }
+    /// 分片上传文件，支持断点续传
+    pub fn multipart_upload_file(&self) -> UploadFile {
+        UploadFile::new(self.oss.clone())
+    }
+    /// 以Sink的方式分片上传文件，需要设置文件类型等信息时使用 InitUpload::into_sink()
+    pub fn multipart_upload_sink(&self) -> UploadSink {
+        self.multipart_init_upload().into_sink()
+    }
+    /// 分片下载文件，支持断点续传
+    pub fn multipart_download_file(&self) -> DownloadFile {
+        DownloadFile::new(self.oss.clone())
+    }
+    /// 将文件的内容以数据流的方式写入另一个文件，目标文件可以属于其他存储空间或者账号
+    pub fn pipe_to(&self, target: &OssObject) -> PipeObject {
+        PipeObject::new(self.oss.clone(), target.oss.clone())
+    }
     /// 列举指定Upload ID所属的所有已经上传成功Part
     pub fn multipart_list_parts(&self, upload_id: impl ToString) -> ListParts {
         ListParts::new(self.oss.clone(), upload_id)
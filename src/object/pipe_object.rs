@@ -0,0 +1,117 @@
+use super::{InitUpload, PutObject};
+use crate::{
+    error::{normal_error, Error},
+    request::{Oss, OssRequest},
+};
+use hyper::{header, Method};
+
+/// 将文件的内容以数据流的方式写入另一个文件
+///
+/// 边下载边上传，数据不落盘，也不会完整缓存在内存中；上传变慢时会暂停读取源文件，目标文件可以属于其他存储空间、地域或者账号（OssClient）
+///
+/// 文件大小未超过分片阈值时，使用PutObject上传；超过分片阈值时，使用分片上传，内存中最多缓存两个分片的数据
+///
+/// 默认保留源文件的mime类型和自定义metadata；同一地域内的复制，使用 CopyObject 或 TransferManager::copy() 更高效
+///
+/// ```
+/// let source = client_a.bucket("source-bucket", "oss-cn-hangzhou.aliyuncs.com").object("rust.mp4");
+/// let target = client_b.bucket("target-bucket", "oss-cn-zhangjiakou.aliyuncs.com").object("rust.mp4");
+/// source.pipe_to(&target).set_part_size(16 * 1024 * 1024).send().await?;
+/// ```
+pub struct PipeObject {
+    source: Oss,
+    target: Oss,
+    part_size: usize,
+    multipart_threshold: u64,
+    keep_metadata: bool,
+}
+impl PipeObject {
+    pub(super) fn new(source: Oss, target: Oss) -> Self {
+        PipeObject {
+            source,
+            target,
+            part_size: 8_388_608,
+            multipart_threshold: 104_857_600,
+            keep_metadata: true,
+        }
+    }
+    /// 设置分片大小，单位字节
+    ///
+    /// 默认值：8MB，最小值：100KB
+    pub fn set_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.max(102_400);
+        self
+    }
+    /// 设置使用分片上传的文件大小阈值，单位字节
+    ///
+    /// 默认值：100MB，最大值：5GB
+    pub fn set_multipart_threshold(mut self, multipart_threshold: u64) -> Self {
+        self.multipart_threshold = multipart_threshold.min(5_368_709_119);
+        self
+    }
+    /// 不保留源文件的mime类型和自定义metadata
+    pub fn discard_metadata(mut self) -> Self {
+        self.keep_metadata = false;
+        self
+    }
+    /// 开始传输
+    ///
+    pub async fn send(self) -> Result<(), Error> {
+        //下载源文件
+        let response = OssRequest::new(self.source, Method::GET)
+            .send_to_oss()?
+            .await?;
+        if !response.status().is_success() {
+            return Err(normal_error(response).await);
+        }
+        let content_length = response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or(Error::OssInvalidResponse(None))?;
+        //读取需要保留的元信息
+        let mut mime = None;
+        let mut metadata = Vec::new();
+        if self.keep_metadata {
+            for (key, value) in response.headers() {
+                let value = match value.to_str() {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+                if key == header::CONTENT_TYPE {
+                    mime = Some(value.to_owned());
+                } else if let Some(key) = key.as_str().strip_prefix("x-oss-meta-") {
+                    metadata.push((key.to_owned(), value.to_owned()));
+                }
+            }
+        }
+        //文件较小时直接上传
+        if content_length <= self.multipart_threshold {
+            let mut put_object = PutObject::new(self.target);
+            if let Some(mime) = mime {
+                put_object = put_object.set_mime(mime);
+            }
+            for (key, value) in metadata {
+                put_object = put_object.set_meta(key, value);
+            }
+            put_object
+                .send_body(response.into_body(), content_length)
+                .await?;
+            return Ok(());
+        }
+        //分片上传，Sink缓存已满时不再读取数据
+        let mut init = InitUpload::new(self.target);
+        if let Some(mime) = mime {
+            init = init.set_mime(mime);
+        }
+        for (key, value) in metadata {
+            init = init.set_meta(key, value);
+        }
+        init.into_sink()
+            .set_part_size(self.part_size)
+            .send_stream(response.into_body())
+            .await?;
+        Ok(())
+    }
+}
@@ -1,16 +1,36 @@
 use crate::{
+    checksum::{Crc64, Md5},
     common::{
-        invalid_metadata_key, url_encode, Acl, CacheControl, ContentDisposition, StorageClass,
+        check_tags, url_encode, Acl, CacheControl, ContentDisposition, ObjectMetadata, StorageClass,
     },
     error::{normal_error, Error},
+    mime::{detect_mime, sniff_stream, MimeDetector},
+    progress::{ProgressEvent, ProgressListener},
     request::{Oss, OssRequest},
 };
 use futures_util::StreamExt;
 use hyper::{header, Body, Method};
-use std::collections::HashMap;
+use serde_derive::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 use tokio::{fs::File, io::BufReader};
 use tokio_util::io::ReaderStream;
 
+/// 上传结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PutObjectResult {
+    /// 文件的ETag
+    pub e_tag: String,
+    /// 文件的CRC64
+    pub crc64: Option<u64>,
+    /// 文件的版本ID，仅在存储空间开启了版本控制时返回
+    pub version_id: Option<String>,
+    /// OSS返回的Request ID
+    pub request_id: Option<String>,
+}
+
 /// 上传文件
 ///
 /// 添加的Object大小不能超过 5GB
@@ -20,26 +40,39 @@ use tokio_util::io::ReaderStream;
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/31978.html)
 pub struct PutObject {
     req: OssRequest,
+    metadata: ObjectMetadata,
     mime: Option<String>,
     tags: HashMap<String, String>,
-    callback: Option<Box<dyn Fn(u64, u64) + Send + Sync + 'static>>,
+    verify: bool,
+    gzip: bool,
+    mime_detector: Option<Arc<dyn MimeDetector>>,
+    listener: Option<Arc<dyn ProgressListener>>,
 }
 impl PutObject {
     pub(super) fn new(oss: Oss) -> Self {
         PutObject {
             req: OssRequest::new(oss, Method::PUT),
+            metadata: ObjectMetadata::new(),
             mime: None,
+            mime_detector: None,
             tags: HashMap::new(),
-            callback: None,
+            verify: false,
+            gzip: false,
+            listener: None,
         }
     }
     /// 设置文件的mime类型
     ///
-    /// 如果未设置mime类型，请求发送时，会尝试从内容、本地路径、远程路径获取mime，如果依然未获取成功，则使用默认mime类型（application/octet-stream）
+    /// 如果未设置mime类型，请求发送时，会使用MimeDetector根据内容开头的数据、远程路径识别mime，如果依然未识别成功，则使用默认mime类型（application/octet-stream）
     pub fn set_mime(mut self, mime: impl ToString) -> Self {
         self.mime = Some(mime.to_string());
         self
     }
+    /// 设置文件类型的识别方式，未设置时使用 DefaultMimeDetector
+    pub fn set_mime_detector(mut self, mime_detector: Arc<dyn MimeDetector>) -> Self {
+        self.mime_detector = Some(mime_detector);
+        self
+    }
     /// 设置文件的访问权限
     pub fn set_acl(mut self, acl: Acl) -> Self {
         self.req.insert_header("x-oss-object-acl", acl);
@@ -68,21 +101,61 @@ impl PutObject {
     }
     /// 设置需要附加的metadata
     ///
-    /// key只允许存在英文字母、数字、连字符，如果存在其他字符，则metadata将直接被抛弃
+    /// key只允许存在英文字母、数字、连字符，不区分大小写；不符合要求时，发送请求时返回 Error::InvalidMetadata
     pub fn set_meta(mut self, key: impl ToString, value: impl ToString) -> Self {
-        let key = key.to_string();
-        if !invalid_metadata_key(&key) {
-            self.req
-                .insert_header(format!("x-oss-meta-{}", key.to_string()), value);
-        }
+        self.metadata.insert(key, value);
+        self
+    }
+    /// 设置需要附加的metadata，会覆盖之前设置的所有metadata
+    pub fn set_metadata(mut self, metadata: ObjectMetadata) -> Self {
+        self.metadata = metadata;
         self
     }
     /// 设置标签信息
+    ///
+    /// 标签限制见 check_tags，不符合要求时发送请求将返回 Error::InvalidTag
     pub fn set_tagging(mut self, key: impl ToString, value: impl ToString) -> Self {
         self.tags.insert(key.to_string(), value.to_string());
         self
     }
-    /// 设置文件上传进度的回调方法，此方法仅对send_file()有效
+    /// 校验上传的数据
+    ///
+    /// send_content()会携带Content-MD5，由OSS进行校验；send_file()会在上传时计算CRC64，并与OSS返回的 x-oss-hash-crc64ecma 比对
+    ///
+    /// 校验不通过时，返回 Error::ChecksumMismatch
+    pub fn verify_checksum(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+    /// 上传时使用gzip压缩，并设置 Content-Encoding: gzip
+    ///
+    /// 适合文本、日志类文件；文件类型按压缩前的内容识别，CRC64和Content-MD5按压缩后的数据计算，上传进度按压缩前的数据计算；
+    /// send_file()和send_body()压缩后的长度未知，使用分块传输上传
+    ///
+    /// 下载时可以使用 GetObject::decode_content 解压，浏览器等HTTP客户端也会自动解压
+    #[cfg(feature = "gzip")]
+    pub fn compress_gzip(mut self) -> Self {
+        self.gzip = true;
+        self
+    }
+    /// 限制上传速度，单位bit/s
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.req.insert_header("x-oss-traffic-limit", traffic_limit);
+        self
+    }
+    /// 设置读取本地文件时的缓冲区大小，覆盖OssClient的配置，单位字节，最小4KB
+    pub fn set_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.req.oss.buffer_size = buffer_size.max(4096);
+        self
+    }
+    /// 设置每次从文件读取并发送的数据块大小，覆盖OssClient的配置，单位字节，最小1KB
+    pub fn set_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.req.oss.chunk_size = chunk_size.max(1024);
+        self
+    }
+    /// 设置文件上传进度的回调方法，此方法对send_file()、send_content()、send_body()有效
     /// ```
     /// let callback = Box::new(|uploaded_size: u64, total_size: u64| {
     ///     let percentage = if total_size == 0 {
@@ -94,37 +167,25 @@ impl PutObject {
     /// });
     /// ```
     pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
-        self.callback = Some(callback);
+        self.listener = Some(Arc::new(callback));
+        self
+    }
+    /// 设置上传进度的监听，与set_callback()的生效范围相同，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.listener = Some(listener);
         self
     }
     /// 将磁盘中的文件上传到OSS
     ///
     pub async fn send_file(mut self, file: impl ToString) -> Result<(), Error> {
-        //生成文件类型
-        let file_type = match self.mime {
-            Some(mime) => mime,
-            None => match infer::get_from_path(&file.to_string())? {
-                Some(ext) => ext.mime_type().to_owned(),
-                None => mime_guess::from_path(
-                    &self
-                        .req
-                        .oss
-                        .object
-                        .clone()
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| String::new()),
-                )
-                .first()
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "application/octet-stream".to_owned())
-                .to_string(),
-            },
-        };
-        self.req.insert_header(header::CONTENT_TYPE, file_type);
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
-            .into_iter()
+            .iter()
             .map(|(key, value)| {
                 if value.is_empty() {
                     url_encode(&key.to_string())
@@ -149,29 +210,74 @@ impl PutObject {
             return Err(Error::InvalidFileSize);
         }
         //初始化文件内容读取数据流
-        let buf = BufReader::with_capacity(131072, file);
-        let stream = ReaderStream::with_capacity(buf, 16384);
-        //初始化已上传内容大小
+        let buf = BufReader::with_capacity(self.req.oss.buffer_size, file);
+        let stream = ReaderStream::with_capacity(buf, self.req.oss.chunk_size);
+        //生成文件类型，读取文件开头的数据进行识别
+        let (stream, file_type) = match self.mime.take() {
+            Some(mime) => (stream.left_stream(), mime),
+            None => {
+                let (head, stream) = sniff_stream(stream).await;
+                let file_type = detect_mime(
+                    self.mime_detector.as_ref(),
+                    &head,
+                    self.req.oss.object.as_deref().unwrap_or_default(),
+                );
+                (stream.right_stream(), file_type)
+            }
+        };
+        self.req.insert_header(header::CONTENT_TYPE, file_type);
+        //初始化已上传内容大小和CRC64
         let mut uploaded_size = 0;
+        let crc64 = Arc::new(Mutex::new(Crc64::new()));
+        let crc64_writer = (self.verify && !self.gzip).then(|| crc64.clone());
         //初始化上传请求
+        let listener = self.listener.take();
         let body = Body::wrap_stream(stream.map(move |result| match result {
             Ok(chunk) => {
-                if let Some(callback) = &self.callback {
+                if let Some(crc64) = &crc64_writer {
+                    if let Ok(mut crc64) = crc64.lock() {
+                        crc64.update(&chunk);
+                    }
+                }
+                if let Some(listener) = &listener {
                     let upload_size = chunk.len() as u64;
                     uploaded_size = uploaded_size + upload_size;
-                    callback(uploaded_size, file_size);
+                    listener.on_event(&ProgressEvent::Progress {
+                        transferred: uploaded_size,
+                        total: file_size,
+                    });
                 }
                 Ok(chunk)
             }
             Err(err) => Err(err),
         }));
+        #[cfg(feature = "gzip")]
+        let body = self.compress_body(body, &crc64);
         self.req.set_body(body);
         //上传文件
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
         let status_code = response.status();
         match status_code {
-            code if code.is_success() => Ok(()),
+            code if code.is_success() => {
+                //校验CRC64
+                let remote_crc64 = response
+                    .headers()
+                    .get("x-oss-hash-crc64ecma")
+                    .and_then(|v| v.to_str().ok());
+                let local_crc64 = crc64.lock().map(|v| v.finalize()).unwrap_or_default();
+                match remote_crc64 {
+                    Some(remote_crc64)
+                        if self.verify && remote_crc64 != local_crc64.to_string() =>
+                    {
+                        Err(Error::ChecksumMismatch(
+                            local_crc64.to_string(),
+                            remote_crc64.to_owned(),
+                        ))
+                    }
+                    _ => Ok(()),
+                }
+            }
             _ => Err(normal_error(response).await),
         }
     }
@@ -179,38 +285,114 @@ impl PutObject {
     ///
     pub async fn send_content(mut self, content: Vec<u8>) -> Result<(), Error> {
         //生成文件类型
-        let content_type = match self.mime {
+        let content_type = match self.mime.take() {
             Some(mime) => mime,
-            None => match infer::get(&content) {
-                Some(ext) => ext.mime_type().to_string(),
-                None => mime_guess::from_path(
-                    self.req
-                        .oss
-                        .object
-                        .clone()
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| String::new().into()),
-                )
-                .first()
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "application/octet-stream".to_owned())
-                .to_string(),
-            },
+            None => detect_mime(
+                self.mime_detector.as_ref(),
+                &content,
+                self.req.oss.object.as_deref().unwrap_or_default(),
+            ),
         };
-        self.req.insert_header(header::CONTENT_TYPE, content_type);
+        self.mime = Some(content_type);
+        //压缩后的长度已知，直接压缩全部数据，不再使用分块传输
+        #[cfg(feature = "gzip")]
+        let content = if std::mem::take(&mut self.gzip) {
+            self.req.insert_header(header::CONTENT_ENCODING, "gzip");
+            crate::compress::gzip_encode_bytes(&content)?
+        } else {
+            content
+        };
+        //计算Content-MD5，由OSS校验
+        if self.verify {
+            let mut md5 = Md5::new();
+            md5.update(&content);
+            self.req.insert_header("Content-MD5", md5.finalize_base64());
+        }
+        let content_size = content.len() as u64;
+        self.send_body(content, content_size).await.map(|_| ())
+    }
+    /// 将actix-multipart上传的文件转发到OSS，数据边接收边上传，不在内存中缓存
+    ///
+    /// content_length必须与文件的实际长度一致，通常由客户端通过其他表单字段或请求头告知；
+    /// 未设置文件类型时，使用表单中该文件的Content-Type
+    ///
+    /// ```
+    /// #[post("/files/{key}")]
+    /// async fn upload(key: web::Path<String>, mut payload: Multipart) -> actix_web::Result<String> {
+    ///     let field = payload.try_next().await?.ok_or_else(|| error::ErrorBadRequest("缺少文件"))?;
+    ///     let result = bucket
+    ///         .object(&key)
+    ///         .put_object()
+    ///         .send_multipart_field(field, content_length)
+    ///         .await
+    ///         .map_err(error::ErrorBadGateway)?;
+    ///     Ok(result.e_tag)
+    /// }
+    /// ```
+    #[cfg(feature = "actix")]
+    pub async fn send_multipart_field(
+        mut self,
+        mut field: actix_multipart::Field,
+        content_length: u64,
+    ) -> Result<PutObjectResult, Error> {
+        if self.mime.is_none() {
+            self.mime = field.content_type().map(|mime| mime.to_string());
+        }
+        //Field不能跨线程，在当前任务中读取数据并写入Body
+        let (mut sender, body) = Body::channel();
+        let pump = async move {
+            while let Some(chunk) = field.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        if sender.send_data(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                    //读取失败时中断上传
+                    Err(_) => {
+                        sender.abort();
+                        break;
+                    }
+                }
+            }
+        };
+        let (_, result) =
+            futures_util::future::join(pump, self.send_body(body, content_length)).await;
+        result
+    }
+    /// 上传数据到OSS，支持Vec<u8>、Bytes、String以及通过 Body::wrap_stream() 包装的数据流
+    ///
+    /// content_length必须与数据的实际长度一致，会作为Content-Length发送
+    ///
+    /// ```
+    /// let stream = ReaderStream::new(reader);
+    /// let result = object
+    ///     .put_object()
+    ///     .send_body(Body::wrap_stream(stream), content_length)
+    ///     .await?;
+    /// println!("{}", result.e_tag);
+    /// ```
+    pub async fn send_body(
+        mut self,
+        body: impl Into<Body>,
+        content_length: u64,
+    ) -> Result<PutObjectResult, Error> {
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
+        if content_length >= 5_368_709_120 {
+            return Err(Error::InvalidFileSize);
+        }
+        //校验标签
+        check_tags(&self.tags)?;
         //插入标签
         let tags = self
             .tags
-            .into_iter()
+            .iter()
             .map(|(key, value)| {
                 if value.is_empty() {
-                    url_encode(&key.to_string())
+                    url_encode(key)
                 } else {
-                    format!(
-                        "{}={}",
-                        url_encode(&key.to_string()),
-                        url_encode(&value.to_string())
-                    )
+                    format!("{}={}", url_encode(key), url_encode(value))
                 }
             })
             .collect::<Vec<_>>()
@@ -218,21 +400,102 @@ impl PutObject {
         if !tags.is_empty() {
             self.req.insert_header("x-oss-tagging", tags);
         }
-        //读取大小
-        let content_size = content.len() as u64;
-        if content_size >= 5_368_709_120 {
-            return Err(Error::InvalidFileSize);
-        }
-        self.req.insert_header(header::CONTENT_LENGTH, content_size);
-        //插入body
-        self.req.set_body(content.into());
+        self.req
+            .insert_header(header::CONTENT_LENGTH, content_length);
+        //生成文件类型，读取数据开头的内容进行识别
+        let body: Body = body.into();
+        let (body, content_type) = match self.mime.take() {
+            Some(mime) => (body, mime),
+            None => {
+                let (head, stream) = sniff_stream(body).await;
+                let content_type = detect_mime(
+                    self.mime_detector.as_ref(),
+                    &head,
+                    self.req.oss.object.as_deref().unwrap_or_default(),
+                );
+                (Body::wrap_stream(stream), content_type)
+            }
+        };
+        self.req.insert_header(header::CONTENT_TYPE, content_type);
+        //计算CRC64，并回调上传进度
+        let mut uploaded_size = 0;
+        let crc64 = Arc::new(Mutex::new(Crc64::new()));
+        let crc64_writer = (self.verify && !self.gzip).then(|| crc64.clone());
+        let listener = self.listener.take();
+        let body = match (&crc64_writer, &listener) {
+            (None, None) => body,
+            _ => Body::wrap_stream(body.map(move |result| {
+                if let Ok(chunk) = &result {
+                    if let Some(crc64) = &crc64_writer {
+                        if let Ok(mut crc64) = crc64.lock() {
+                            crc64.update(chunk);
+                        }
+                    }
+                    if let Some(listener) = &listener {
+                        uploaded_size += chunk.len() as u64;
+                        listener.on_event(&ProgressEvent::Progress {
+                            transferred: uploaded_size,
+                            total: content_length,
+                        });
+                    }
+                }
+                result
+            })),
+        };
+        #[cfg(feature = "gzip")]
+        let body = self.compress_body(body, &crc64);
+        self.req.set_body(body);
         //上传文件
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
         let status_code = response.status();
         match status_code {
-            code if code.is_success() => Ok(()),
+            code if code.is_success() => {
+                let headers = response.headers();
+                let get = |key: &str| {
+                    headers
+                        .get(key)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.to_owned())
+                };
+                let result = PutObjectResult {
+                    e_tag: get("ETag")
+                        .map(|v| v.trim_matches('"').to_owned())
+                        .unwrap_or_default(),
+                    crc64: get("x-oss-hash-crc64ecma").and_then(|v| v.parse().ok()),
+                    version_id: get("x-oss-version-id"),
+                    request_id: get("x-oss-request-id"),
+                };
+                //校验CRC64
+                let local_crc64 = crc64.lock().map(|v| v.finalize()).unwrap_or_default();
+                match result.crc64 {
+                    Some(remote_crc64) if self.verify && remote_crc64 != local_crc64 => Err(
+                        Error::ChecksumMismatch(local_crc64.to_string(), remote_crc64.to_string()),
+                    ),
+                    _ => Ok(result),
+                }
+            }
             _ => Err(normal_error(response).await),
         }
     }
+    //压缩上传的数据流，CRC64按压缩后的数据计算
+    #[cfg(feature = "gzip")]
+    fn compress_body(&mut self, body: Body, crc64: &Arc<Mutex<Crc64>>) -> Body {
+        if !self.gzip {
+            return body;
+        }
+        self.req.headers.remove(header::CONTENT_LENGTH);
+        self.req.insert_header(header::CONTENT_ENCODING, "gzip");
+        let body = crate::compress::gzip_encode(body);
+        if !self.verify {
+            return body;
+        }
+        let crc64 = crc64.clone();
+        Body::wrap_stream(body.map(move |result| {
+            if let (Ok(chunk), Ok(mut crc64)) = (&result, crc64.lock()) {
+                crc64.update(chunk);
+            }
+            result
+        }))
+    }
 }
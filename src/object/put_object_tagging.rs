@@ -1,12 +1,16 @@
 use crate::{
+    common::check_tags,
     error::normal_error,
     request::{Oss, OssRequest},
+    xml::XmlBuilder,
     Error,
 };
 use hyper::Method;
 
 /// 设置文件标签
 ///
+/// 标签限制见 check_tags，不符合要求时将直接返回 Error::InvalidTag
+///
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/114855.html)
 pub struct PutObjectTagging {
     req: OssRequest,
@@ -35,20 +39,20 @@ impl PutObjectTagging {
     /// 发送请求
     ///
     pub async fn send(mut self) -> Result<(), Error> {
+        //校验标签
+        check_tags(self.tags.iter().map(|(key, value)| (key, value)))?;
         //构建body
-        let tag_str = self
-            .tags
-            .iter()
-            .map(|(key, value)| {
-                if value.is_empty() {
-                    format!("<Tag><Key>{}</Key></Tag>", key)
-                } else {
-                    format!("<Tag><Key>{}</Key><Value>{}</Value></Tag>", key, value)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("");
-        let body = format!("<Tagging><TagSet>{}</TagSet></Tagging>", tag_str);
+        let mut xml = XmlBuilder::new();
+        xml.start("Tagging").start("TagSet");
+        for (key, value) in &self.tags {
+            xml.start("Tag").element("Key", key);
+            if !value.is_empty() {
+                xml.element("Value", value);
+            }
+            xml.end("Tag");
+        }
+        xml.end("TagSet").end("Tagging");
+        let body = xml.finish();
         self.req.insert_header("Content-Length", body.len());
         self.req.set_body(body.into());
         //构建http请求
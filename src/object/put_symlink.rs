@@ -1,5 +1,5 @@
 use crate::{
-    common::{Acl, StorageClass},
+    common::{Acl, ObjectMetadata, StorageClass},
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
@@ -11,13 +11,17 @@ use hyper::Method;
 /// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/document_detail/45126.html)
 pub struct PutSymlink {
     req: OssRequest,
+    metadata: ObjectMetadata,
 }
 impl PutSymlink {
     pub(super) fn new(oss: Oss, symlink_target: impl ToString) -> Self {
         let mut req = OssRequest::new(oss, Method::PUT);
         req.insert_query("symlink", "");
         req.insert_header("x-oss-symlink-target", symlink_target);
-        PutSymlink { req }
+        PutSymlink {
+            req,
+            metadata: ObjectMetadata::new(),
+        }
     }
     /// 设置文件的访问权限
     pub fn set_acl(mut self, acl: Acl) -> Self {
@@ -34,9 +38,23 @@ impl PutSymlink {
         self.req.insert_header("x-oss-forbid-overwrite", "true");
         self
     }
+    /// 设置需要附加的metadata
+    ///
+    /// key只允许存在英文字母、数字、连字符，不区分大小写；不符合要求时，发送请求时返回 Error::InvalidMetadata
+    pub fn set_meta(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+    /// 设置需要附加的metadata，会覆盖之前设置的所有metadata
+    pub fn set_metadata(mut self, metadata: ObjectMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
     /// 发送请求
     ///
-    pub async fn send(self) -> Result<(), Error> {
+    pub async fn send(mut self) -> Result<(), Error> {
+        //校验并写入元信息
+        self.metadata.apply(&mut self.req)?;
         //构建http请求
         let response = self.req.send_to_oss()?.await?;
         //拆解响应消息
@@ -1,10 +1,13 @@
+use super::HeadObject;
 use crate::{
-    common::RestoreTier,
+    common::{RestoreStatus, RestoreTier},
     error::normal_error,
     request::{Oss, OssRequest},
     Error,
 };
 use hyper::Method;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
 
 /// 解冻归档文件
 ///
@@ -61,4 +64,36 @@ impl RestoreObject {
             _ => Err(normal_error(response).await),
         }
     }
+    /// 发送请求，并等待文件解冻完成
+    ///
+    /// 提交解冻请求后，每隔 poll_interval 查询一次解冻状态，直到文件可以读取；超过 timeout 仍未完成，则返回 Error::Timeout
+    ///
+    /// 如果文件已经处于解冻中，则直接开始等待
+    pub async fn send_and_wait(
+        self,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        let oss = self.req.oss.without_cache();
+        //提交解冻请求
+        match self.send().await {
+            Ok(_) => {}
+            Err(Error::OssError(_, oss_error)) if oss_error.code == "RestoreAlreadyInProgress" => {}
+            Err(e) => return Err(e),
+        }
+        //轮询解冻状态
+        loop {
+            let headers = HeadObject::new(oss.clone()).send().await?;
+            let status =
+                RestoreStatus::from_header(headers.get("x-oss-restore").map(|v| v.as_str()));
+            if status.is_restored() {
+                return Ok(());
+            }
+            if Instant::now() + poll_interval > deadline {
+                return Err(Error::Timeout);
+            }
+            sleep(poll_interval).await;
+        }
+    }
 }
@@ -0,0 +1,316 @@
+//! RAM/STS 权限策略
+//!
+//! 生成授权策略的JSON字符串，可用于RAM用户授权，或在调用STS AssumeRole时传入Policy参数，限制临时凭证的访问范围
+//!
+//! ```
+//! use aliyun_oss_rs::policy::{Action, Condition, Policy, Statement};
+//!
+//! let policy = Policy::new()
+//!     .add_statement(
+//!         Statement::allow()
+//!             .add_actions([Action::GetObject, Action::PutObject])
+//!             .add_prefix("for-rs-test", "users/1001/"),
+//!     )
+//!     .add_statement(
+//!         Statement::allow()
+//!             .add_action(Action::ListObjects)
+//!             .add_bucket("for-rs-test")
+//!             .add_condition(Condition::Prefix(vec!["users/1001/*".to_owned()])),
+//!     )
+//!     .to_json();
+//! ```
+
+use crate::common::json_escape;
+use chrono::{DateTime, Utc};
+use std::{collections::BTreeMap, fmt};
+
+/// 授权效果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// 允许
+    Allow,
+    /// 拒绝
+    Deny,
+}
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Effect::Allow => f.write_str("Allow"),
+            Effect::Deny => f.write_str("Deny"),
+        }
+    }
+}
+
+/// OSS操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// 所有操作，oss:*
+    All,
+    /// 查询存储空间列表
+    ListBuckets,
+    /// 获取存储空间信息
+    GetBucketInfo,
+    /// 获取存储空间统计信息
+    GetBucketStat,
+    /// 列举文件
+    ListObjects,
+    /// 下载文件、查询文件元信息
+    GetObject,
+    /// 上传文件，包括简单上传、追加上传、分片上传、复制文件
+    PutObject,
+    /// 删除文件
+    DeleteObject,
+    /// 获取文件ACL
+    GetObjectAcl,
+    /// 设置文件ACL
+    PutObjectAcl,
+    /// 获取文件标签
+    GetObjectTagging,
+    /// 设置文件标签
+    PutObjectTagging,
+    /// 删除文件标签
+    DeleteObjectTagging,
+    /// 解冻文件
+    RestoreObject,
+    /// 列举分片上传事件
+    ListMultipartUploads,
+    /// 列举已上传的分片
+    ListParts,
+    /// 取消分片上传
+    AbortMultipartUpload,
+    /// 其他操作，传入完整的操作名称，如 oss:GetBucketLifecycle
+    Custom(String),
+}
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::All => "*",
+            Action::ListBuckets => "ListBuckets",
+            Action::GetBucketInfo => "GetBucketInfo",
+            Action::GetBucketStat => "GetBucketStat",
+            Action::ListObjects => "ListObjects",
+            Action::GetObject => "GetObject",
+            Action::PutObject => "PutObject",
+            Action::DeleteObject => "DeleteObject",
+            Action::GetObjectAcl => "GetObjectAcl",
+            Action::PutObjectAcl => "PutObjectAcl",
+            Action::GetObjectTagging => "GetObjectTagging",
+            Action::PutObjectTagging => "PutObjectTagging",
+            Action::DeleteObjectTagging => "DeleteObjectTagging",
+            Action::RestoreObject => "RestoreObject",
+            Action::ListMultipartUploads => "ListMultipartUploads",
+            Action::ListParts => "ListParts",
+            Action::AbortMultipartUpload => "AbortMultipartUpload",
+            Action::Custom(action) => return f.write_str(action),
+        };
+        write!(f, "oss:{}", name)
+    }
+}
+
+/// 授权条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// 限制访问来源IP，支持CIDR格式，如 192.168.0.0/16
+    SourceIp(Vec<String>),
+    /// 限制访问来源VPC
+    SourceVpc(Vec<String>),
+    /// 是否必须使用https访问
+    SecureTransport(bool),
+    /// 限制列举文件时的前缀，支持通配符 *
+    Prefix(Vec<String>),
+    /// 限制列举文件时的分组字符
+    Delimiter(Vec<String>),
+    /// 限制访问时间必须早于指定时间
+    CurrentTimeBefore(DateTime<Utc>),
+    /// 限制访问时间必须晚于指定时间
+    CurrentTimeAfter(DateTime<Utc>),
+    /// 其他条件
+    ///
+    /// - operator：条件运算符，如 StringEquals、StringLike、IpAddress
+    /// - key：条件关键字，如 acs:UserAgent
+    /// - values：条件值
+    Custom {
+        operator: String,
+        key: String,
+        values: Vec<String>,
+    },
+}
+impl Condition {
+    //拆分为 运算符、关键字、条件值
+    fn parts(&self) -> (&str, &str, Vec<String>) {
+        let format_time = |time: &DateTime<Utc>| time.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        match self {
+            Condition::SourceIp(ips) => ("IpAddress", "acs:SourceIp", ips.clone()),
+            Condition::SourceVpc(vpcs) => ("StringEquals", "acs:SourceVpc", vpcs.clone()),
+            Condition::SecureTransport(secure) => {
+                ("Bool", "acs:SecureTransport", vec![secure.to_string()])
+            }
+            Condition::Prefix(prefixes) => ("StringLike", "oss:Prefix", prefixes.clone()),
+            Condition::Delimiter(delimiters) => {
+                ("StringEquals", "oss:Delimiter", delimiters.clone())
+            }
+            Condition::CurrentTimeBefore(time) => {
+                ("DateLessThan", "acs:CurrentTime", vec![format_time(time)])
+            }
+            Condition::CurrentTimeAfter(time) => (
+                "DateGreaterThan",
+                "acs:CurrentTime",
+                vec![format_time(time)],
+            ),
+            Condition::Custom {
+                operator,
+                key,
+                values,
+            } => (operator, key, values.clone()),
+        }
+    }
+}
+
+/// 授权语句
+#[derive(Debug, Clone)]
+pub struct Statement {
+    effect: Effect,
+    actions: Vec<Action>,
+    resources: Vec<String>,
+    conditions: Vec<Condition>,
+}
+impl Statement {
+    pub fn new(effect: Effect) -> Self {
+        Statement {
+            effect,
+            actions: Vec::new(),
+            resources: Vec::new(),
+            conditions: Vec::new(),
+        }
+    }
+    /// 初始化一条允许的授权语句
+    pub fn allow() -> Self {
+        Self::new(Effect::Allow)
+    }
+    /// 初始化一条拒绝的授权语句
+    pub fn deny() -> Self {
+        Self::new(Effect::Deny)
+    }
+    /// 添加操作
+    pub fn add_action(mut self, action: Action) -> Self {
+        self.actions.push(action);
+        self
+    }
+    /// 添加多个操作
+    pub fn add_actions(mut self, actions: impl IntoIterator<Item = Action>) -> Self {
+        self.actions.extend(actions);
+        self
+    }
+    /// 添加存储空间资源，acs:oss:*:*:bucket
+    ///
+    /// 用于ListObjects、GetBucketInfo等存储空间级别的操作
+    pub fn add_bucket(self, bucket: impl ToString) -> Self {
+        self.add_resource(format!("acs:oss:*:*:{}", bucket.to_string()))
+    }
+    /// 添加文件资源，acs:oss:*:*:bucket/key
+    pub fn add_object(self, bucket: impl ToString, key: impl ToString) -> Self {
+        self.add_resource(format!(
+            "acs:oss:*:*:{}/{}",
+            bucket.to_string(),
+            key.to_string()
+        ))
+    }
+    /// 添加指定前缀下的所有文件资源，acs:oss:*:*:bucket/prefix*
+    pub fn add_prefix(self, bucket: impl ToString, prefix: impl ToString) -> Self {
+        self.add_resource(format!(
+            "acs:oss:*:*:{}/{}*",
+            bucket.to_string(),
+            prefix.to_string()
+        ))
+    }
+    /// 添加完整的资源描述
+    pub fn add_resource(mut self, resource: impl ToString) -> Self {
+        self.resources.push(resource.to_string());
+        self
+    }
+    /// 添加条件，多个条件需要同时满足
+    pub fn add_condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+    fn to_json(&self) -> String {
+        let mut json = format!(
+            "{{\"Effect\":{},\"Action\":{},\"Resource\":{}",
+            json_string(&self.effect.to_string()),
+            json_array(self.actions.iter().map(|v| v.to_string())),
+            json_array(self.resources.iter().cloned()),
+        );
+        if !self.conditions.is_empty() {
+            //相同运算符的条件合并到一起
+            let mut conditions: BTreeMap<&str, BTreeMap<&str, Vec<String>>> = BTreeMap::new();
+            for condition in &self.conditions {
+                let (operator, key, values) = condition.parts();
+                conditions
+                    .entry(operator)
+                    .or_default()
+                    .entry(key)
+                    .or_default()
+                    .extend(values);
+            }
+            let conditions = conditions
+                .into_iter()
+                .map(|(operator, keys)| {
+                    let keys = keys
+                        .into_iter()
+                        .map(|(key, values)| {
+                            format!("{}:{}", json_string(key), json_array(values.into_iter()))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    format!("{}:{{{}}}", json_string(operator), keys)
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            json.push_str(&format!(",\"Condition\":{{{}}}", conditions));
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// 权限策略
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    statements: Vec<Statement>,
+}
+impl Policy {
+    pub fn new() -> Self {
+        Policy::default()
+    }
+    /// 添加授权语句
+    pub fn add_statement(mut self, statement: Statement) -> Self {
+        self.statements.push(statement);
+        self
+    }
+    /// 生成JSON格式的权限策略
+    pub fn to_json(&self) -> String {
+        let statements = self
+            .statements
+            .iter()
+            .map(|statement| statement.to_json())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"Version\":\"1\",\"Statement\":[{}]}}", statements)
+    }
+}
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_json())
+    }
+}
+
+//生成JSON字符串
+fn json_string(value: &str) -> String {
+    format!("\"{}\"", json_escape(value))
+}
+
+//生成JSON字符串数组
+fn json_array(values: impl Iterator<Item = String>) -> String {
+    let values = values.map(|v| json_string(&v)).collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
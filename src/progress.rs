@@ -0,0 +1,171 @@
+//! 传输进度
+//!
+//! 上传、下载、复制时通过 ProgressListener 接收传输进度和传输事件；
+//! 闭包 `Fn(u64, u64)` 实现了 ProgressListener ，只接收传输进度
+//!
+//! 需要在异步代码中处理事件时，使用 channel() 将事件发送到异步通道
+//!
+//! ```
+//! use aliyun_oss_rs::progress::{self, ProgressEvent};
+//!
+//! let (listener, mut events) = progress::channel();
+//! tokio::spawn(async move {
+//!     while let Some(event) = events.recv().await {
+//!         if let ProgressEvent::Progress { transferred, total } = event {
+//!             println!("{}/{}", transferred, total);
+//!         }
+//!     }
+//! });
+//! object
+//!     .multipart_upload_file()
+//!     .set_progress_listener(listener)
+//!     .send_file("/data/rust.mp4")
+//!     .await?;
+//! ```
+
+use crate::Error;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// 传输事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// 传输进度，参数为已传输的大小和总大小；分片传输时汇总所有分片的进度
+    Progress { transferred: u64, total: u64 },
+    /// 一个分片传输完成
+    PartCompleted { part_number: u32, size: u64 },
+    /// 请求失败，即将重试；part_number为None时表示非分片请求
+    Retry {
+        part_number: Option<u32>,
+        attempt: u32,
+        error: String,
+    },
+    /// 传输完成
+    Finished,
+    /// 传输失败
+    Failed { error: String },
+}
+
+/// 传输进度的监听
+///
+/// 方法在传输过程中同步调用，不应执行耗时操作；耗时的处理请使用 channel() 转到异步任务中
+pub trait ProgressListener: Send + Sync {
+    /// 接收传输事件
+    fn on_event(&self, event: &ProgressEvent);
+}
+
+/// 闭包只接收传输进度，参数为已传输的大小和总大小
+impl<F> ProgressListener for F
+where
+    F: Fn(u64, u64) + Send + Sync,
+{
+    fn on_event(&self, event: &ProgressEvent) {
+        if let ProgressEvent::Progress { transferred, total } = event {
+            self(*transferred, *total)
+        }
+    }
+}
+
+/// 将传输事件发送到异步通道
+///
+/// 接收端被关闭后，事件将被丢弃
+#[derive(Debug, Clone)]
+pub struct ChannelListener {
+    sender: UnboundedSender<ProgressEvent>,
+}
+impl ProgressListener for ChannelListener {
+    fn on_event(&self, event: &ProgressEvent) {
+        let _ = self.sender.send(event.clone());
+    }
+}
+
+/// 创建异步的进度监听，返回监听和事件的接收端
+pub fn channel() -> (Arc<ChannelListener>, UnboundedReceiver<ProgressEvent>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (Arc::new(ChannelListener { sender }), receiver)
+}
+
+//通知传输结果
+pub(crate) fn finish<T>(listener: Option<&Arc<dyn ProgressListener>>, result: &Result<T, Error>) {
+    let listener = match listener {
+        Some(listener) => listener,
+        None => return,
+    };
+    match result {
+        Ok(_) => listener.on_event(&ProgressEvent::Finished),
+        Err(e) => listener.on_event(&ProgressEvent::Failed {
+            error: e.to_string(),
+        }),
+    }
+}
+
+// 汇总多个分片（或多个任务）的传输进度
+//
+// 每个分片的进度为累计值，转换为增量后汇总；重试导致进度回退时，不重复计算
+pub(crate) struct Aggregator {
+    listener: Arc<dyn ProgressListener>,
+    transferred: AtomicU64,
+    total: AtomicU64,
+}
+impl Aggregator {
+    pub fn new(listener: Arc<dyn ProgressListener>, transferred: u64, total: u64) -> Arc<Self> {
+        Arc::new(Aggregator {
+            listener,
+            transferred: AtomicU64::new(transferred),
+            total: AtomicU64::new(total),
+        })
+    }
+    //增加总大小
+    pub fn add_total(&self, size: u64) {
+        self.total.fetch_add(size, Ordering::SeqCst);
+        self.notify();
+    }
+    //创建单个分片的进度监听，分片的进度汇总后通知，传输结果由汇总方通知，其他事件直接转发
+    pub fn part(self: &Arc<Self>) -> Arc<dyn ProgressListener> {
+        Arc::new(PartListener {
+            aggregator: self.clone(),
+            transferred: AtomicU64::new(0),
+        })
+    }
+    //发送其他事件
+    pub fn emit(&self, event: ProgressEvent) {
+        self.listener.on_event(&event);
+    }
+    //通知传输结果
+    pub fn finish<T>(&self, result: &Result<T, Error>) {
+        finish(Some(&self.listener), result);
+    }
+    //通知当前的汇总进度
+    pub fn notify(&self) {
+        self.listener.on_event(&ProgressEvent::Progress {
+            transferred: self.transferred.load(Ordering::SeqCst),
+            total: self.total.load(Ordering::SeqCst),
+        });
+    }
+}
+
+struct PartListener {
+    aggregator: Arc<Aggregator>,
+    transferred: AtomicU64,
+}
+impl ProgressListener for PartListener {
+    fn on_event(&self, event: &ProgressEvent) {
+        match event {
+            ProgressEvent::Progress { transferred, .. } => {
+                let last = self.transferred.fetch_max(*transferred, Ordering::SeqCst);
+                if *transferred > last {
+                    self.aggregator
+                        .transferred
+                        .fetch_add(transferred - last, Ordering::SeqCst);
+                    self.aggregator.notify();
+                }
+            }
+            ProgressEvent::Finished | ProgressEvent::Failed { .. } => {}
+            event => self.aggregator.listener.on_event(event),
+        }
+    }
+}
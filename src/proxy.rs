@@ -0,0 +1,98 @@
+//! HTTP代理
+//!
+//! 通过HTTP CONNECT隧道连接OSS，http和https请求均经由隧道转发
+
+use crate::dns::DnsResolver;
+use base64::{engine::general_purpose, Engine};
+use futures_util::Future;
+use hyper::{client::connect::HttpConnector, service::Service, Uri};
+use percent_encoding::percent_decode_str;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+// 代理响应头的最大长度
+const MAX_RESPONSE_HEADER_LEN: usize = 8192;
+
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyConnector {
+    proxy: Uri,
+    http: HttpConnector<DnsResolver>,
+}
+impl ProxyConnector {
+    pub fn new(proxy: Uri, http: HttpConnector<DnsResolver>) -> Self {
+        ProxyConnector { proxy, http }
+    }
+    //代理地址中的用户名和密码
+    fn authorization(&self) -> Option<String> {
+        let authority = self.proxy.authority()?.as_str();
+        let (userinfo, _) = authority.rsplit_once('@')?;
+        let (username, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+        let credentials = format!(
+            "{}:{}",
+            percent_decode_str(username).decode_utf8_lossy(),
+            percent_decode_str(password).decode_utf8_lossy()
+        );
+        Some(format!(
+            "Basic {}",
+            general_purpose::STANDARD.encode(credentials)
+        ))
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = TcpStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, io::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(io::Error::other)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let host = dst.host().unwrap_or_default().to_owned();
+        let port = dst.port_u16().unwrap_or(match dst.scheme_str() {
+            Some("https") => 443,
+            _ => 80,
+        });
+        let authorization = self.authorization();
+        let connecting = self.http.call(self.proxy.clone());
+        Box::pin(async move {
+            let mut tcp = connecting.await.map_err(io::Error::other)?;
+            //建立隧道
+            let mut request = format!(
+                "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+                host = host,
+                port = port
+            );
+            if let Some(authorization) = authorization {
+                request.push_str(&format!("Proxy-Authorization: {}\r\n", authorization));
+            }
+            request.push_str("\r\n");
+            tcp.write_all(request.as_bytes()).await?;
+            //读取代理的响应头，逐字节读取，避免读取到隧道建立后的数据
+            let mut response = Vec::with_capacity(128);
+            while !response.ends_with(b"\r\n\r\n") {
+                if response.len() >= MAX_RESPONSE_HEADER_LEN {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "代理返回的响应头过长",
+                    ));
+                }
+                response.push(tcp.read_u8().await?);
+            }
+            let status_line = String::from_utf8_lossy(&response);
+            let status_line = status_line.lines().next().unwrap_or_default();
+            match status_line.split_whitespace().nth(1) {
+                Some(status) if status.starts_with('2') => Ok(tcp),
+                _ => Err(io::Error::other(format!("代理连接失败：{}", status_line))),
+            }
+        })
+    }
+}
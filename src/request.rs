@@ -1,13 +1,44 @@
-use crate::{common::url_encode, Error};
-use base64::{engine::general_purpose, Engine};
-use chrono::{NaiveDateTime, Utc};
-use hyper::{client::ResponseFuture, header, Body, Client, Method, Request};
+use crate::{
+    cache::MetaCache,
+    checksum::Sha256,
+    client::Region,
+    common::{
+        encode_key, request_id, uri_encode, CircuitBreaker, HedgePolicy, RateLimiter, RequestClass,
+        RequestInfo, RequestRecorder, RetryPolicy, ThrottleEvent, ThrottleListener,
+    },
+    credentials::CredentialsCache,
+    dns::{DnsCache, DnsResolver},
+    proxy::ProxyConnector,
+    signer::{HmacSigner, SignatureVersion, Signer},
+    trace,
+    transport::Transport,
+    validate, Error,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{
+    future::{self, Either},
+    stream, Future, StreamExt,
+};
+use hyper::{
+    body::HttpBody,
+    client::HttpConnector,
+    header::{self, HeaderName, HeaderValue},
+    http::request,
+    Body, Client, HeaderMap, Method, Request, Response, StatusCode, Uri,
+};
 use hyper_tls::HttpsConnector;
-use ring::hmac;
+use percent_encoding::percent_decode_str;
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time,
 };
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 const EXCLUDED_VALUES: [&str; 84] = [
     "acl",
@@ -96,64 +127,322 @@ const EXCLUDED_VALUES: [&str; 84] = [
     "rtc",
 ];
 
+//访问凭证
+#[derive(Clone)]
+pub(crate) struct Credentials {
+    pub signer: Arc<dyn Signer>,
+    pub security_token: Option<Cow<'static, str>>,
+}
+//日志中不输出安全令牌，签名器只输出AccessKey ID
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("signer", &self.signer)
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "******"),
+            )
+            .finish()
+    }
+}
+
+//仅对单个OssBucket、OssObject生效的安全令牌，日志中不输出
+#[derive(Clone)]
+pub(crate) struct SecurityToken(pub Cow<'static, str>);
+impl fmt::Debug for SecurityToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("******")
+    }
+}
+
+//默认的User-Agent，包含库的名称、版本、操作系统和CPU架构，如 aliyun-oss-rs/0.0.5 (linux; x86_64)
+fn default_user_agent() -> String {
+    format!(
+        "{}/{} ({}; {})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
 //Oss基础结构
 #[derive(Debug, Clone)]
 pub(crate) struct Oss {
-    pub ak_id: Cow<'static, str>,
-    pub ak_secret: Cow<'static, str>,
-    pub security_token: Option<Cow<'static, str>>,
+    //同一个OssClient创建的OssBucket、OssObject共享访问凭证，更新后立即生效
+    pub credentials: Arc<RwLock<Arc<Credentials>>>,
+    pub credentials_provider: Option<Arc<CredentialsCache>>,
+    //签名时覆盖共享凭证中的安全令牌，不替换共享凭证，凭证提供者刷新后的AccessKey仍然生效
+    pub security_token: Option<SecurityToken>,
+    //匿名访问，不进行签名
+    pub anonymous: bool,
+    pub request_payer: bool,
+    //OssClient::bucket_auto 查询到的存储空间所在地域，同一个OssClient共享
+    pub bucket_regions: Arc<Mutex<HashMap<String, Region>>>,
     pub endpoint: Cow<'static, str>,
     pub custom_domain: Option<Cow<'static, str>>,
     pub bucket: Option<Cow<'static, str>>,
     pub object: Option<Cow<'static, str>>,
+    pub encoded_object: Option<Cow<'static, str>>,
     pub enable_https: bool,
+    pub path_style: bool,
+    pub strict: bool,
+    pub clock_offset: Arc<AtomicI64>,
+    pub cache: Option<Arc<MetaCache>>,
+    pub connect_timeout: Option<time::Duration>,
+    pub timeout: Option<time::Duration>,
+    pub read_timeout: Option<time::Duration>,
+    pub buffer_size: usize,
+    pub chunk_size: usize,
+    pub proxy: Option<Uri>,
+    pub pool_idle_timeout: Option<time::Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub user_agent: Option<Cow<'static, str>>,
+    pub signature_version: SignatureVersion,
+    pub region: Option<Cow<'static, str>>,
+    pub cloudbox_id: Option<Cow<'static, str>>,
+    pub additional_headers: Vec<String>,
+    pub retry_policy: RetryPolicy,
+    pub throttle_listener: Option<ThrottleListener>,
+    pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub hedge_policy: Option<Arc<HedgePolicy>>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub concurrency_limit: Option<Arc<Semaphore>>,
+    pub dns_cache: Option<Arc<DnsCache>>,
+    pub request_recorder: Option<RequestRecorder>,
+    //同一个OssClient创建的OssBucket、OssObject共享连接池，修改连接相关的配置后需要重新创建
+    pub client: HttpClient,
 }
 impl Oss {
     pub fn new(ak_id: &str, ak_secret: &str) -> Self {
+        Oss::with_signer(Arc::new(HmacSigner::new(ak_id, ak_secret)))
+    }
+    pub fn with_signer(signer: Arc<dyn Signer>) -> Self {
         Oss {
-            ak_id: ak_id.to_owned().into(),
-            ak_secret: ak_secret.to_owned().into(),
+            credentials: Arc::new(RwLock::new(Arc::new(Credentials {
+                signer,
+                security_token: None,
+            }))),
+            credentials_provider: None,
             security_token: None,
+            anonymous: false,
+            request_payer: false,
+            bucket_regions: Arc::new(Mutex::new(HashMap::new())),
             endpoint: "oss.aliyuncs.com".to_owned().into(),
             custom_domain: None,
             bucket: None,
             object: None,
+            encoded_object: None,
             enable_https: true,
+            path_style: false,
+            strict: false,
+            clock_offset: Arc::new(AtomicI64::new(0)),
+            cache: None,
+            connect_timeout: None,
+            timeout: None,
+            read_timeout: None,
+            buffer_size: 131_072,
+            chunk_size: 16_384,
+            proxy: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            user_agent: Some(default_user_agent().into()),
+            signature_version: SignatureVersion::V1,
+            region: None,
+            cloudbox_id: None,
+            additional_headers: Vec::new(),
+            retry_policy: RetryPolicy::none(),
+            throttle_listener: None,
+            circuit_breaker: None,
+            hedge_policy: None,
+            rate_limiter: None,
+            concurrency_limit: None,
+            dns_cache: None,
+            request_recorder: None,
+            client: HttpClient::default(),
         }
     }
     pub fn set_bucket(&mut self, bucket: impl ToString) {
         self.bucket = Some(bucket.to_string().into());
     }
     pub fn set_endpoint(&mut self, endpoint: impl ToString) {
-        self.endpoint = endpoint.to_string().into();
+        self.endpoint = self.strip_scheme(endpoint.to_string()).into();
     }
     pub fn set_custom_domain(&mut self, custom_domain: impl ToString) {
-        self.custom_domain = Some(custom_domain.to_string().into());
+        self.custom_domain = Some(self.strip_scheme(custom_domain.to_string()).into());
+    }
+    //校正后的当前时间，根据OSS返回的RequestTimeTooSkewed错误校正本地时间的偏差
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(self.clock_offset.load(Ordering::Relaxed))
+    }
+    //去除地址中的协议，并按照协议设置是否启用https
+    fn strip_scheme(&mut self, address: String) -> String {
+        let address = if let Some(address) = address.strip_prefix("http://") {
+            self.enable_https = false;
+            address
+        } else if let Some(address) = address.strip_prefix("https://") {
+            self.enable_https = true;
+            address
+        } else {
+            &address
+        };
+        address.trim_end_matches('/').to_owned()
     }
     pub fn set_object(&mut self, object: impl ToString) {
         self.object = Some(object.to_string().trim_start_matches("/").to_owned().into());
+        self.encoded_object = None;
+    }
+    pub fn set_encoded_object(&mut self, encoded_object: impl ToString) {
+        let encoded_object = encoded_object.to_string();
+        let object = percent_decode_str(&encoded_object).decode_utf8_lossy();
+        self.object = Some(object.trim_start_matches('/').to_owned().into());
+        self.encoded_object = Some(encoded_object.into());
     }
     pub fn set_https(&mut self, https: bool) {
         self.enable_https = https;
     }
+    //不读取、不写入元信息缓存，用于解冻状态、断点续传的ETag等必须获取最新结果的内部查询
+    pub fn without_cache(&self) -> Oss {
+        let mut oss = self.clone();
+        oss.cache = None;
+        oss
+    }
+    //当前的访问凭证
+    pub fn credentials(&self) -> Arc<Credentials> {
+        match self.credentials.read() {
+            Ok(credentials) => credentials.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+    //更新访问凭证，共享此凭证的所有OssBucket、OssObject同时生效
+    pub fn update_credentials(&self, credentials: Credentials) {
+        let credentials = Arc::new(credentials);
+        match self.credentials.write() {
+            Ok(mut current) => *current = credentials,
+            Err(poisoned) => *poisoned.into_inner() = credentials,
+        }
+    }
+    //更新共享凭证中的安全令牌，保留当前的签名器
+    pub fn update_security_token(&self, security_token: impl ToString) {
+        self.update_credentials(Credentials {
+            signer: self.credentials().signer.clone(),
+            security_token: Some(security_token.to_string().into()),
+        });
+    }
+    //仅为当前实例设置安全令牌，不影响共享凭证的其他实例
+    pub fn set_security_token(&mut self, security_token: impl ToString) {
+        self.security_token = Some(SecurityToken(security_token.to_string().into()));
+    }
+    //签名使用的安全令牌，优先使用当前实例设置的令牌
+    fn security_token(&self, credentials: &Credentials) -> Option<Cow<'static, str>> {
+        match &self.security_token {
+            Some(SecurityToken(security_token)) => Some(security_token.clone()),
+            None => credentials.security_token.clone(),
+        }
+    }
+    pub fn set_path_style(&mut self, path_style: bool) {
+        self.path_style = path_style;
+    }
+    //V4签名使用的地域，未设置时从Endpoint推断，如 oss-cn-hangzhou-internal.aliyuncs.com 的地域为 cn-hangzhou
+    pub fn region(&self) -> Result<String, Error> {
+        if let Some(region) = &self.region {
+            return Ok(region.to_string());
+        }
+        self.endpoint
+            .split('.')
+            .next()
+            .and_then(|v| v.strip_prefix("oss-"))
+            .map(|v| v.trim_end_matches("-internal"))
+            .filter(|v| !v.is_empty() && !v.starts_with("accelerate"))
+            .map(|v| v.to_owned())
+            .ok_or_else(|| Error::MissingRegion(self.endpoint.to_string()))
+    }
+    //云盒ID，未设置时从Endpoint推断，如 cb-xxx.cn-hangzhou.oss-cloudbox.aliyuncs.com 的云盒ID为 cb-xxx
+    pub fn cloudbox_id(&self) -> Option<String> {
+        if let Some(cloudbox_id) = &self.cloudbox_id {
+            return Some(cloudbox_id.to_string());
+        }
+        let mut labels = self.endpoint.split('.');
+        let cloudbox_id = labels.next().filter(|v| v.starts_with("cb-"))?;
+        labels
+            .any(|v| v.starts_with("oss-cloudbox"))
+            .then(|| cloudbox_id.to_owned())
+    }
+    //V4签名范围中的地域和产品，云盒使用云盒ID作为地域，产品为 oss-cloudbox
+    fn v4_region(&self) -> Result<(String, &'static str), Error> {
+        match self.cloudbox_id() {
+            Some(cloudbox_id) => Ok((cloudbox_id, "oss-cloudbox")),
+            None => Ok((self.region()?, "oss")),
+        }
+    }
+    //云盒仅支持V4签名
+    fn use_v4(&self) -> bool {
+        self.signature_version == SignatureVersion::V4 || self.cloudbox_id().is_some()
+    }
+}
+//有序的查询参数，保留插入顺序，允许同名参数
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Querys(Vec<(String, String)>);
+impl Querys {
+    //设置参数，替换全部同名参数
+    pub fn insert(&mut self, key: String, value: String) {
+        match self.0.iter().position(|(k, _)| *k == key) {
+            Some(index) => {
+                self.0[index].1 = value;
+                let mut position = 0;
+                self.0.retain(|(k, _)| {
+                    position += 1;
+                    position - 1 <= index || *k != key
+                });
+            }
+            None => self.0.push((key, value)),
+        }
+    }
+    //追加参数，保留已存在的同名参数
+    pub fn append(&mut self, key: String, value: String) {
+        self.0.push((key, value));
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+//请求头的值，签名时按UTF-8处理
+fn header_str(value: &HeaderValue) -> Cow<'_, str> {
+    String::from_utf8_lossy(value.as_bytes())
 }
+
 // 迭代器
 #[derive(Debug)]
 pub(crate) struct OssRequest {
     pub oss: Oss,
     pub method: Method,
-    pub headers: HashMap<String, String>,
-    pub querys: HashMap<String, String>,
+    pub headers: HeaderMap,
+    pub querys: Querys,
     pub body: Body,
+    //添加了名称或值不合法的请求头，签名时返回 Error::InvalidCharacter
+    invalid_header: bool,
 }
 impl OssRequest {
     pub fn new(oss: Oss, method: Method) -> Self {
         OssRequest {
             oss,
             method,
-            headers: HashMap::with_capacity(10),
-            querys: HashMap::with_capacity(10),
+            headers: HeaderMap::with_capacity(10),
+            querys: Querys::default(),
+            body: Body::empty(),
+            invalid_header: false,
+        }
+    }
+    //复制请求，不包含body，用于分页等需要重复发送的请求
+    pub fn clone_without_body(&self) -> Self {
+        OssRequest {
+            oss: self.oss.clone(),
+            method: self.method.clone(),
+            headers: self.headers.clone(),
+            querys: self.querys.clone(),
             body: Body::empty(),
+            invalid_header: self.invalid_header,
         }
     }
     pub fn set_endpoint(&mut self, endpoint: impl ToString) {
@@ -162,12 +451,38 @@ impl OssRequest {
     pub fn set_https(&mut self, https: bool) {
         self.oss.enable_https = https;
     }
+    //设置请求头，替换已存在的同名请求头，名称不区分大小写
     pub fn insert_header(&mut self, key: impl ToString, value: impl ToString) {
-        self.headers.insert(key.to_string(), value.to_string());
+        if let Some((key, value)) = self.header_pair(key, value) {
+            self.headers.insert(key, value);
+        }
+    }
+    fn header_pair(
+        &mut self,
+        key: impl ToString,
+        value: impl ToString,
+    ) -> Option<(HeaderName, HeaderValue)> {
+        let key = HeaderName::from_bytes(key.to_string().as_bytes());
+        let value = HeaderValue::from_bytes(value.to_string().as_bytes());
+        match (key, value) {
+            (Ok(key), Ok(value)) => Some((key, value)),
+            _ => {
+                self.invalid_header = true;
+                None
+            }
+        }
+    }
+    //请求gzip压缩的响应，响应在send_to_oss中透明解压；未启用gzip特性时不做任何处理
+    pub fn accept_gzip(&mut self) {
+        #[cfg(feature = "gzip")]
+        self.insert_header(header::ACCEPT_ENCODING, "gzip");
     }
     pub fn insert_query(&mut self, key: impl ToString, value: impl ToString) {
         self.querys.insert(key.to_string(), value.to_string());
     }
+    pub fn append_query(&mut self, key: impl ToString, value: impl ToString) {
+        self.querys.append(key.to_string(), value.to_string());
+    }
     pub fn set_body(&mut self, body: Body) {
         self.body = body;
     }
@@ -178,9 +493,11 @@ impl OssRequest {
         } else {
             "http://"
         };
-        //Host
+        //Host，使用自定义域名时，不再拼接bucket
         let host = if let Some(custom_domain) = self.oss.custom_domain.clone() {
             custom_domain.to_string()
+        } else if self.oss.path_style {
+            self.oss.endpoint.to_string()
         } else {
             format!(
                 "{}{}",
@@ -192,16 +509,22 @@ impl OssRequest {
                 self.oss.endpoint
             )
         };
+        //路径，path-style时bucket位于路径中
+        let bucket_path = match &self.oss.bucket {
+            Some(bucket) if self.oss.path_style && self.oss.custom_domain.is_none() => {
+                format!("{}/", bucket)
+            }
+            _ => String::new(),
+        };
         //查询参数
         let query = self
             .querys
             .iter()
             .map(|(key, value)| {
-                let value = value.to_string();
                 if value.is_empty() {
-                    key.to_string()
+                    uri_encode(key)
                 } else {
-                    format!("{}={}", key, url_encode(&value))
+                    format!("{}={}", uri_encode(key), uri_encode(value))
                 }
             })
             .collect::<Vec<_>>()
@@ -211,211 +534,831 @@ impl OssRequest {
         } else {
             format!("?{}", query)
         };
+        //文件路径，已编码的路径原样使用
+        let object_path = match (&self.oss.encoded_object, &self.oss.object) {
+            (Some(encoded_object), _) => encoded_object.trim_start_matches('/').to_owned(),
+            (None, Some(object)) => encode_key(object),
+            (None, None) => String::new(),
+        };
         //生成url
         format!(
-            "{}{}/{}{}",
-            protocol,
-            host,
-            url_encode(
-                &self
-                    .oss
-                    .object
-                    .clone()
-                    .unwrap_or_else(|| String::new().into())
-            ),
-            query_str
+            "{}{}/{}{}{}",
+            protocol, host, bucket_path, object_path, query_str
         )
     }
-    pub fn query_sign(&mut self, expires: NaiveDateTime) {
-        //提取header数据
-        let mut content_type = String::new();
-        let mut content_md5 = String::new();
-        let mut canonicalized_ossheaders = BTreeMap::new();
-        self.headers.iter().for_each(|(key, value)| {
-            if key.starts_with("x-oss-") {
-                canonicalized_ossheaders.insert(key, value);
-            };
-            if key.starts_with(&header::CONTENT_TYPE.to_string()) {
-                content_type = value.to_string();
-            };
-            if key == "Content-MD5" {
-                content_md5 = value.to_string();
-            };
-        });
-        //处理canonicalized_ossheaders
-        let mut canonicalized_ossheaders = canonicalized_ossheaders
+    pub fn query_sign(&mut self, expires: i64) -> Result<(), Error> {
+        if self.invalid_header {
+            return Err(Error::InvalidCharacter);
+        }
+        if self.oss.anonymous {
+            return Ok(());
+        }
+        if self.oss.use_v4() {
+            return self.query_sign_v4(expires, self.oss.now());
+        }
+        let credentials = self.oss.credentials();
+        //插入security-token，需要参与签名
+        if let Some(security_token) = self.oss.security_token(&credentials) {
+            self.insert_query("security-token", security_token);
+        };
+        //生成待签名字符串
+        let unsign_str = self.v1_string_to_sign(&expires.to_string());
+        //计算签名值
+        let sign_str = credentials.signer.sign(&unsign_str)?;
+        self.insert_header(
+            header::DATE,
+            self.oss
+                .now()
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        );
+        self.insert_query("Expires", expires);
+        self.insert_query("Signature", sign_str);
+        self.insert_query("OSSAccessKeyId", credentials.signer.access_key_id());
+        Ok(())
+    }
+    pub fn header_sign(&mut self) -> Result<(), Error> {
+        if self.invalid_header {
+            return Err(Error::InvalidCharacter);
+        }
+        if self.oss.anonymous {
+            return Ok(());
+        }
+        if self.oss.use_v4() {
+            return self.header_sign_v4(self.oss.now());
+        }
+        let credentials = self.oss.credentials();
+        //插入x-oss-security-token，需要参与签名
+        if let Some(security_token) = self.oss.security_token(&credentials) {
+            self.insert_header("x-oss-security-token", security_token);
+        };
+        //生成待签名字符串
+        let date = self
+            .oss
+            .now()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        let unsign_str = self.v1_string_to_sign(&date);
+        //计算签名值
+        let sign_str = credentials.signer.sign(&unsign_str)?;
+        self.insert_header(header::DATE, date);
+        self.insert_header(
+            header::AUTHORIZATION,
+            format!("OSS {}:{}", credentials.signer.access_key_id(), sign_str),
+        );
+        Ok(())
+    }
+    //构建V1签名的待签名字符串，date为请求头签名的Date或URL签名的过期时间
+    //
+    //签名在每个请求上执行，直接写入同一个String，避免中间的Vec和String
+    fn v1_string_to_sign(&self, date: &str) -> String {
+        let mut string_to_sign = String::with_capacity(256);
+        string_to_sign.push_str(self.method.as_str());
+        string_to_sign.push('\n');
+        for name in [HeaderName::from_static("content-md5"), header::CONTENT_TYPE] {
+            if let Some(value) = self.headers.get(&name) {
+                string_to_sign.push_str(&header_str(value));
+            }
+            string_to_sign.push('\n');
+        }
+        string_to_sign.push_str(date);
+        string_to_sign.push('\n');
+        //canonicalized_ossheaders，按名称排序，同名请求头的值以逗号连接
+        let mut oss_headers = self
+            .headers
+            .keys()
+            .filter(|key| key.as_str().starts_with("x-oss-"))
+            .collect::<Vec<_>>();
+        oss_headers.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+        for key in oss_headers {
+            string_to_sign.push_str(key.as_str());
+            string_to_sign.push(':');
+            for (index, value) in self.headers.get_all(key).iter().enumerate() {
+                if index > 0 {
+                    string_to_sign.push(',');
+                }
+                string_to_sign.push_str(&header_str(value));
+            }
+            string_to_sign.push('\n');
+        }
+        //canonicalized_resource
+        string_to_sign.push('/');
+        if let Some(bucket) = &self.oss.bucket {
+            string_to_sign.push_str(bucket);
+            string_to_sign.push('/');
+        }
+        if let Some(object) = &self.oss.object {
+            string_to_sign.push_str(object);
+        }
+        //sub_resource，按名称排序
+        let mut sub_resources = self
+            .querys
+            .iter()
+            .filter(|(key, _)| EXCLUDED_VALUES.contains(key))
+            .collect::<Vec<_>>();
+        sub_resources.sort_unstable();
+        for (index, (key, value)) in sub_resources.into_iter().enumerate() {
+            string_to_sign.push(if index == 0 { '?' } else { '&' });
+            string_to_sign.push_str(key);
+            if !value.is_empty() {
+                string_to_sign.push('=');
+                string_to_sign.push_str(value);
+            }
+        }
+        string_to_sign
+    }
+    //V4签名，签名信息位于查询参数中
+    fn query_sign_v4(&mut self, expires: i64, now: DateTime<Utc>) -> Result<(), Error> {
+        let credentials = self.oss.credentials();
+        let (region, product) = self.oss.v4_region()?;
+        let (date, scope) = v4_scope(&now, &region, product);
+        //插入x-oss-security-token，需要参与签名
+        if let Some(security_token) = self.oss.security_token(&credentials) {
+            self.insert_query("x-oss-security-token", security_token);
+        };
+        self.insert_query("x-oss-signature-version", V4_ALGORITHM);
+        self.insert_query(
+            "x-oss-credential",
+            format!("{}/{}", credentials.signer.access_key_id(), scope),
+        );
+        self.insert_query("x-oss-date", now.format("%Y%m%dT%H%M%SZ"));
+        self.insert_query("x-oss-expires", (expires - now.timestamp()).max(1));
+        //额外参与签名的请求头列表写入url，使用url发起请求时必须携带相同的请求头
+        let additional_headers = self
+            .oss
+            .additional_headers
+            .iter()
+            .filter(|v| *v == "host" || self.headers.contains_key(v.as_str()))
+            .cloned()
+            .collect::<BTreeSet<_>>()
             .into_iter()
-            .map(|(key, value)| format!("{}:{}", key, value))
-            .collect::<Vec<String>>()
-            .join("\n");
-        if !canonicalized_ossheaders.is_empty() {
-            canonicalized_ossheaders.push_str("\n")
-        }
-        //构建sub_resource
-        let sub_resource = self
+            .collect::<Vec<_>>();
+        if !additional_headers.is_empty() {
+            self.insert_query("x-oss-additional-headers", additional_headers.join(";"));
+        }
+        //计算签名值
+        let (canonical_request, _) = self.v4_canonical_request(&additional_headers);
+        let sign_str = credentials.signer.sign_v4(
+            &date,
+            &region,
+            product,
+            &v4_string_to_sign(&now, &scope, &canonical_request),
+        )?;
+        self.insert_query("x-oss-signature", sign_str);
+        Ok(())
+    }
+    //V4签名，签名信息位于Authorization请求头中
+    fn header_sign_v4(&mut self, now: DateTime<Utc>) -> Result<(), Error> {
+        let credentials = self.oss.credentials();
+        let (region, product) = self.oss.v4_region()?;
+        let (date, scope) = v4_scope(&now, &region, product);
+        //插入x-oss-security-token，需要参与签名
+        if let Some(security_token) = self.oss.security_token(&credentials) {
+            self.insert_header("x-oss-security-token", security_token);
+        };
+        self.insert_header("x-oss-date", now.format("%Y%m%dT%H%M%SZ"));
+        self.insert_header("x-oss-content-sha256", V4_UNSIGNED_PAYLOAD);
+        //计算签名值
+        let additional_headers = self.oss.additional_headers.clone();
+        let (canonical_request, additional_headers) =
+            self.v4_canonical_request(&additional_headers);
+        let sign_str = credentials.signer.sign_v4(
+            &date,
+            &region,
+            product,
+            &v4_string_to_sign(&now, &scope, &canonical_request),
+        )?;
+        let mut authorization = format!(
+            "{} Credential={}/{}",
+            V4_ALGORITHM,
+            credentials.signer.access_key_id(),
+            scope
+        );
+        if !additional_headers.is_empty() {
+            authorization.push_str(&format!(",AdditionalHeaders={}", additional_headers));
+        }
+        authorization.push_str(&format!(",Signature={}", sign_str));
+        self.insert_header(header::AUTHORIZATION, authorization);
+        Ok(())
+    }
+    //构建V4签名的规范请求，返回规范请求和实际参与签名的额外请求头
+    fn v4_canonical_request(&self, additional_headers: &[String]) -> (String, String) {
+        //规范URI，包含存储空间和文件路径
+        let mut canonical_uri = String::from("/");
+        if let Some(bucket) = &self.oss.bucket {
+            canonical_uri.push_str(&format!("{}/", bucket));
+        }
+        if let Some(object) = &self.oss.object {
+            canonical_uri.push_str(
+                &object
+                    .split('/')
+                    .map(uri_encode)
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            );
+        }
+        //规范查询参数，包含全部查询参数
+        let canonical_query = self
             .querys
             .iter()
-            .filter_map(|(key, value)| {
-                if EXCLUDED_VALUES.contains(&key.as_str()) {
-                    Some((key.to_owned(), value.to_owned()))
-                } else {
-                    None
-                }
-            })
-            .collect::<BTreeMap<String, String>>()
+            .map(|(key, value)| (uri_encode(key), uri_encode(value)))
+            .collect::<BTreeSet<_>>()
             .into_iter()
             .map(|(key, value)| {
                 if value.is_empty() {
-                    key.to_owned()
+                    key
                 } else {
                     format!("{}={}", key, value)
                 }
             })
             .collect::<Vec<_>>()
             .join("&");
-        //构建canonicalized_resource
-        let mut canonicalized_resource = format!(
-            "/{}{}",
-            self.oss
-                .bucket
-                .as_deref()
-                .map_or(String::new(), |v| format!("{}/", v)),
-            self.oss
-                .object
-                .as_deref()
-                .map_or(String::new(), |v| format!("{}", v))
-        );
-        if !sub_resource.is_empty() {
-            canonicalized_resource.push_str(&format!("?{}", sub_resource));
+        //规范请求头，包含x-oss-*、content-type、content-md5和额外指定的请求头
+        let mut canonical_headers = BTreeMap::new();
+        let mut signed_headers = Vec::new();
+        for key in self.headers.keys() {
+            let key = key.as_str();
+            if additional_headers.iter().any(|v| v == key) {
+                signed_headers.push(key.to_owned());
+            } else if !key.starts_with("x-oss-") && key != "content-type" && key != "content-md5" {
+                continue;
+            }
+            //同名请求头的值以逗号连接
+            let value = self
+                .headers
+                .get_all(key)
+                .iter()
+                .map(|v| header_str(v).trim().to_owned())
+                .collect::<Vec<_>>()
+                .join(",");
+            canonical_headers.insert(key.to_owned(), value);
         }
-        //生成待签名字符串
-        let unsign_str = format!(
-            "{}\n{}\n{}\n{}\n{}{}",
+        //Host请求头由HTTP客户端自动添加
+        if additional_headers.iter().any(|v| v == "host") && !canonical_headers.contains_key("host")
+        {
+            if let Some(authority) = self
+                .uri()
+                .parse::<Uri>()
+                .ok()
+                .and_then(|uri| uri.authority().cloned())
+            {
+                canonical_headers.insert("host".to_owned(), authority.to_string());
+                signed_headers.push("host".to_owned());
+            }
+        }
+        signed_headers.sort();
+        let signed_headers = signed_headers.join(";");
+        let canonical_headers = canonical_headers
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}\n", key, value))
+            .collect::<String>();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
             self.method,
-            content_md5,
-            content_type,
-            expires.timestamp(),
-            canonicalized_ossheaders,
-            canonicalized_resource
-        );
-        //计算签名值
-        let key_str = hmac::Key::new(
-            hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
-            self.oss.ak_secret.as_bytes(),
+            canonical_uri,
+            canonical_query,
+            canonical_headers,
+            signed_headers,
+            V4_UNSIGNED_PAYLOAD
         );
-        let sign_str =
-            general_purpose::STANDARD.encode(hmac::sign(&key_str, unsign_str.as_bytes()));
-        self.insert_header(
-            header::DATE,
-            Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        (canonical_request, signed_headers)
+    }
+    pub fn send_to_oss(
+        mut self,
+    ) -> Result<impl Future<Output = Result<Response<Body>, Error>>, Error> {
+        //严格校验参数
+        if self.oss.strict {
+            validate::request(&self)?;
+        }
+        //写请求清除相关的缓存
+        if let Some(cache) = &self.oss.cache {
+            if self.method != Method::GET && self.method != Method::HEAD {
+                cache.invalidate(&self.oss);
+            }
+        }
+        //插入User-Agent，不参与签名
+        if let Some(user_agent) = self.oss.user_agent.clone() {
+            if !self.headers.contains_key(header::USER_AGENT) {
+                self.insert_header(header::USER_AGENT, user_agent);
+            }
+        }
+        //访问请求者付费的存储空间，需要参与签名
+        if self.oss.request_payer {
+            self.insert_header("x-oss-request-payer", "requester");
+        }
+        //只有不携带body的读取、删除请求可以安全地重新发送
+        let retry_policy = match self.method {
+            Method::GET | Method::HEAD | Method::DELETE if self.body.is_end_stream() => {
+                self.oss.retry_policy
+            }
+            _ => RetryPolicy::none(),
+        };
+        //对冲请求同样只用于不携带body的读取请求
+        let hedge_policy = match self.method {
+            Method::GET | Method::HEAD if self.body.is_end_stream() => {
+                self.oss.hedge_policy.clone()
+            }
+            _ => None,
+        };
+        let method = self.method.clone();
+        let content_length = self
+            .headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok()?.parse::<u64>().ok());
+        let credentials_provider = self.oss.credentials_provider.clone();
+        let client = self.oss.client.clone();
+        let timeout = self.oss.timeout;
+        let read_timeout = self.oss.read_timeout;
+        let clock_offset = self.oss.clock_offset.clone();
+        let throttle_listener = self.oss.throttle_listener.clone();
+        let circuit_breaker = self.oss.circuit_breaker.clone();
+        let rate_limiter = self.oss.rate_limiter.clone();
+        let concurrency_limit = self.oss.concurrency_limit.clone();
+        let request_class = RequestClass::classify(&self.method, self.oss.object.is_some());
+        let request_recorder = self.oss.request_recorder.clone();
+        #[cfg(feature = "gzip")]
+        let accept_gzip = self
+            .headers
+            .get(header::ACCEPT_ENCODING)
+            .is_some_and(|v| v == "gzip");
+        let span = trace::request_span(
+            &method,
+            self.oss.bucket.as_deref(),
+            self.oss.object.as_deref(),
+            content_length,
         );
-        self.insert_query("Signature", sign_str);
-        self.insert_query("OSSAccessKeyId", &self.oss.ak_id.clone());
-    }
-    pub fn header_sign(&mut self) {
-        //提取header数据
-        let mut content_type = String::new();
-        let mut content_md5 = String::new();
-        let mut canonicalized_ossheaders = BTreeMap::new();
-        self.headers.iter().for_each(|(key, value)| {
-            if key.starts_with("x-oss-") {
-                canonicalized_ossheaders.insert(key, value);
+        let request_span = span.clone();
+        let future = async move {
+            //使用凭证提供者时，签名前按需刷新凭证
+            if let Some(credentials_provider) = &credentials_provider {
+                credentials_provider.refresh(&self.oss, false).await?;
+            }
+            //等待并发名额，名额在响应body读取完毕或被丢弃后释放，重试期间不释放
+            let permit = match &concurrency_limit {
+                Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+                None => None,
             };
-            if key.starts_with(&header::CONTENT_TYPE.to_string()) {
-                content_type = value.to_string();
+            //完成签名
+            self.header_sign()?;
+            //构建http请求
+            let mut request = Request::builder()
+                .method(&self.method)
+                .uri(&self.uri())
+                .body(self.body)?;
+            *request.headers_mut() = self.headers;
+            let (parts, body) = request.into_parts();
+            let endpoint = parts
+                .uri
+                .authority()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            let mut body = Some(body);
+            let mut attempt = 0;
+            let response = loop {
+                let mut request = Request::new(body.take().unwrap_or_else(Body::empty));
+                *request.method_mut() = parts.method.clone();
+                *request.uri_mut() = parts.uri.clone();
+                *request.headers_mut() = parts.headers.clone();
+                if let Some(circuit_breaker) = &circuit_breaker {
+                    circuit_breaker.check(&endpoint)?;
+                }
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire(request_class).await;
+                }
+                let sending = match &hedge_policy {
+                    Some(hedge_policy) => Either::Left(hedged_request(
+                        &client,
+                        &parts,
+                        hedge_policy,
+                        rate_limiter.as_deref().map(|v| (v, request_class)),
+                        concurrency_limit.as_ref(),
+                    )),
+                    None => Either::Right(client.request(request)),
+                };
+                let result = match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, sending)
+                        .await
+                        .unwrap_or(Err(Error::Timeout)),
+                    None => sending.await,
+                };
+                if let Some(circuit_breaker) = &circuit_breaker {
+                    let success = match &result {
+                        Ok(response) => !response.status().is_server_error(),
+                        Err(_) => false,
+                    };
+                    circuit_breaker.record(&endpoint, success);
+                }
+                let (retryable, throttled) = match &result {
+                    Ok(response) => (
+                        response.status().is_server_error()
+                            || response.status() == StatusCode::TOO_MANY_REQUESTS,
+                        is_throttled(response.status()),
+                    ),
+                    Err(e) => (e.is_retryable(), false),
+                };
+                let will_retry = retryable && attempt < retry_policy.max_retries();
+                //限流时读取OSS要求的等待时间，并通知回调
+                let mut retry_after = None;
+                if let (true, Ok(response)) = (throttled, &result) {
+                    retry_after = parse_retry_after(response.headers());
+                    if let Some(listener) = &throttle_listener {
+                        (listener.0)(&ThrottleEvent {
+                            method: parts.method.to_string(),
+                            url: request_url(&parts.uri),
+                            status: response.status().as_u16(),
+                            retry_after,
+                            attempt,
+                            will_retry,
+                        });
+                    }
+                }
+                if !will_retry {
+                    if let Err(e) = &result {
+                        trace::record_error(&request_span, attempt, e);
+                    }
+                    break result?;
+                }
+                match &result {
+                    Ok(response) => trace::retry(attempt + 1, &response.status()),
+                    Err(e) => trace::retry(attempt + 1, e),
+                }
+                attempt += 1;
+                let delay = retry_policy.delay(attempt);
+                tokio::time::sleep(retry_after.map_or(delay, |v| v.max(delay))).await;
             };
-            if key == "Content-MD5" {
-                content_md5 = value.to_string();
+            //请求时间与OSS服务器时间相差超过15分钟时，OSS返回403 RequestTimeTooSkewed，根据服务器时间校正后续请求
+            //
+            //其他403错误（无权限、签名错误等）不校正，需要读取错误body中的错误码，读取后重新放回响应
+            let response = match response.status() {
+                StatusCode::FORBIDDEN => {
+                    let (parts, body) = response.into_parts();
+                    let body = hyper::body::to_bytes(body).await?;
+                    if String::from_utf8_lossy(&body).contains("<Code>RequestTimeTooSkewed</Code>")
+                    {
+                        let server_time = parts
+                            .headers
+                            .get(header::DATE)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+                        if let Some(server_time) = server_time {
+                            let offset =
+                                (server_time.with_timezone(&Utc) - Utc::now()).num_seconds();
+                            let current = clock_offset.load(Ordering::Relaxed);
+                            if (offset - current).abs() > 900 {
+                                clock_offset.store(offset, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                    Response::from_parts(parts, Body::from(body))
+                }
+                _ => response,
             };
-        });
-        //处理canonicalized_ossheaders
-        let mut canonicalized_ossheaders = canonicalized_ossheaders
-            .into_iter()
-            .map(|(key, value)| format!("{}:{}", key, value))
-            .collect::<Vec<String>>()
-            .join("\n");
-        if !canonicalized_ossheaders.is_empty() {
-            canonicalized_ossheaders.push_str("\n")
-        }
-        //构建sub_resource
-        let sub_resource = self
-            .querys
-            .iter()
-            .filter_map(|(key, value)| {
-                if EXCLUDED_VALUES.contains(&key.as_str()) {
-                    Some((key.to_owned(), value.to_owned()))
-                } else {
-                    None
+            if let Some(recorder) = &request_recorder {
+                recorder.record(RequestInfo {
+                    method: parts.method.to_string(),
+                    url: request_url(&parts.uri),
+                    status: response.status().as_u16(),
+                    request_id: request_id(response.headers()),
+                });
+            }
+            let response = trace::record_response(request_span, attempt, response);
+            //请求了gzip压缩的响应时，透明解压
+            #[cfg(feature = "gzip")]
+            let response = match accept_gzip {
+                true => crate::compress::decode_response(response),
+                false => response,
+            };
+            //读取响应body时，两次收到数据的间隔超过read_timeout则中断
+            let response = match read_timeout {
+                Some(read_timeout) if !response.body().is_end_stream() => {
+                    let (parts, body) = response.into_parts();
+                    Response::from_parts(parts, idle_timeout(body, read_timeout))
                 }
-            })
-            .collect::<BTreeMap<String, String>>()
-            .into_iter()
-            .map(|(key, value)| {
-                if value.is_empty() {
-                    key.to_owned()
-                } else {
-                    format!("{}={}", key, value)
+                _ => response,
+            };
+            //响应body未读取完毕前继续占用并发名额
+            match permit {
+                Some(permit) if !response.body().is_end_stream() => {
+                    let (parts, body) = response.into_parts();
+                    Ok(Response::from_parts(parts, hold_permit(body, permit)))
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("&");
-        //构建canonicalized_resource
-        let mut canonicalized_resource = format!(
-            "/{}{}",
-            self.oss
-                .bucket
-                .as_deref()
-                .map_or(String::new(), |v| format!("{}/", v)),
-            self.oss
-                .object
-                .as_deref()
-                .map_or(String::new(), |v| format!("{}", v))
-        );
-        if !sub_resource.is_empty() {
-            canonicalized_resource.push_str(&format!("?{}", sub_resource));
+                _ => Ok(response),
+            }
+        };
+        Ok(trace::instrument(span, future))
+    }
+}
+
+//对冲请求：超过延迟仍未返回时发送第二个相同的请求，使用最先成功的响应
+//第二个请求不等待限速令牌和并发名额，获取不到时只等待第一个请求
+async fn hedged_request(
+    client: &HttpClient,
+    parts: &request::Parts,
+    hedge_policy: &HedgePolicy,
+    rate_limiter: Option<(&RateLimiter, RequestClass)>,
+    concurrency_limit: Option<&Arc<Semaphore>>,
+) -> Result<Response<Body>, Error> {
+    let send = || {
+        let mut request = Request::new(Body::empty());
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = parts.uri.clone();
+        *request.headers_mut() = parts.headers.clone();
+        let start = time::Instant::now();
+        Box::pin(async move { (client.request(request).await, start.elapsed()) })
+    };
+    let first = send();
+    let delay = Box::pin(tokio::time::sleep(hedge_policy.delay()));
+    let (result, latency) = match future::select(first, delay).await {
+        Either::Left((output, _)) => output,
+        Either::Right((_, first)) => {
+            //第二个请求的名额在两个请求都结束后释放，响应body的读取由发起请求时获取的名额约束
+            let permit = match concurrency_limit {
+                Some(semaphore) => semaphore.clone().try_acquire_owned().map(Some),
+                None => Ok(None),
+            };
+            let hedge = permit.is_ok()
+                && rate_limiter.is_none_or(|(rate_limiter, class)| rate_limiter.try_acquire(class));
+            if !hedge {
+                first.await
+            } else {
+                let output = match future::select(first, send()).await {
+                    Either::Left(((Ok(response), latency), _))
+                    | Either::Right(((Ok(response), latency), _)) => (Ok(response), latency),
+                    Either::Left(((Err(_), _), other)) | Either::Right(((Err(_), _), other)) => {
+                        other.await
+                    }
+                };
+                drop(permit);
+                output
+            }
         }
-        //生成待签名字符串
-        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
-        let unsign_str = format!(
-            "{}\n{}\n{}\n{}\n{}{}",
-            self.method,
-            content_md5,
-            content_type,
-            date,
-            canonicalized_ossheaders,
-            canonicalized_resource
+    };
+    if result.is_ok() {
+        hedge_policy.record(latency);
+    }
+    result
+}
+
+//V4签名的算法和不校验body时的哈希值
+const V4_ALGORITHM: &str = "OSS4-HMAC-SHA256";
+const V4_UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+//V4签名的日期和范围，如 20240101 和 20240101/cn-hangzhou/oss/aliyun_v4_request
+fn v4_scope(now: &DateTime<Utc>, region: &str, product: &str) -> (String, String) {
+    let date = now.format("%Y%m%d").to_string();
+    let scope = format!("{}/{}/{}/aliyun_v4_request", date, region, product);
+    (date, scope)
+}
+
+//V4签名的待签名字符串
+fn v4_string_to_sign(now: &DateTime<Utc>, scope: &str, canonical_request: &str) -> String {
+    let mut sha256 = Sha256::new();
+    sha256.update(canonical_request.as_bytes());
+    format!(
+        "{}\n{}\n{}\n{}",
+        V4_ALGORITHM,
+        now.format("%Y%m%dT%H%M%SZ"),
+        scope,
+        sha256.finalize_hex()
+    )
+}
+
+//OSS限流时返回429或503
+fn is_throttled(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+}
+
+//解析响应头 Retry-After ，支持秒数和HTTP日期两种格式
+fn parse_retry_after(headers: &header::HeaderMap) -> Option<time::Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(time::Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+//请求地址，不包含查询参数
+fn request_url(uri: &Uri) -> String {
+    format!(
+        "{}://{}{}",
+        uri.scheme_str().unwrap_or("https"),
+        uri.authority().map(|v| v.as_str()).unwrap_or_default(),
+        uri.path()
+    )
+}
+
+//为响应body增加读取超时，超时后返回 Error::Timeout 并结束
+fn idle_timeout(body: Body, read_timeout: time::Duration) -> Body {
+    Body::wrap_stream(stream::unfold(Some(body), move |body| async move {
+        let mut body = body?;
+        match tokio::time::timeout(read_timeout, body.next()).await {
+            Ok(Some(item)) => Some((item.map_err(Error::from), Some(body))),
+            Ok(None) => None,
+            Err(_) => Some((Err(Error::Timeout), None)),
+        }
+    }))
+}
+
+//响应body读取完毕或被丢弃时释放并发名额
+fn hold_permit(body: Body, permit: OwnedSemaphorePermit) -> Body {
+    Body::wrap_stream(stream::unfold(Some((body, permit)), |state| async move {
+        let (mut body, permit) = state?;
+        let item = body.next().await?;
+        Some((item, Some((body, permit))))
+    }))
+}
+
+//按照客户端配置创建的http客户端，内部使用连接池，克隆后共享连接
+//HttpsConnector同时支持http和https，切换协议不需要重新创建
+#[derive(Clone)]
+pub(crate) enum HttpClient {
+    Direct(Client<HttpsConnector<HttpConnector<DnsResolver>>>),
+    Proxy(Client<HttpsConnector<ProxyConnector>>),
+    Custom(Arc<dyn Transport>),
+}
+impl HttpClient {
+    pub fn new(oss: &Oss) -> Self {
+        let mut http = HttpConnector::new_with_resolver(DnsResolver::new(oss.dns_cache.clone()));
+        http.enforce_http(false);
+        http.set_connect_timeout(oss.connect_timeout);
+        let mut builder = Client::builder();
+        if let Some(pool_idle_timeout) = oss.pool_idle_timeout {
+            builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = oss.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        match &oss.proxy {
+            Some(proxy) => HttpClient::Proxy(builder.build(HttpsConnector::new_with_connector(
+                ProxyConnector::new(proxy.clone(), http),
+            ))),
+            None => HttpClient::Direct(builder.build(HttpsConnector::new_with_connector(http))),
+        }
+    }
+    pub(crate) async fn request(&self, request: Request<Body>) -> Result<Response<Body>, Error> {
+        let response = match self {
+            HttpClient::Direct(client) => client.request(request).await?,
+            HttpClient::Proxy(client) => client.request(request).await?,
+            HttpClient::Custom(transport) => transport.send(request).await?,
+        };
+        Ok(response)
+    }
+}
+impl fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpClient::Direct(client) => f.debug_tuple("Direct").field(client).finish(),
+            HttpClient::Proxy(client) => f.debug_tuple("Proxy").field(client).finish(),
+            HttpClient::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+impl Default for HttpClient {
+    fn default() -> Self {
+        let mut http = HttpConnector::new_with_resolver(DnsResolver::new(None));
+        http.enforce_http(false);
+        HttpClient::Direct(Client::builder().build(HttpsConnector::new_with_connector(http)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{self, StaticProvider};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn security_token_override_keeps_provider_credentials_shared() {
+        let mut oss = Oss::new("", "");
+        oss.credentials_provider = Some(Arc::new(CredentialsCache::new(StaticProvider::new(
+            credentials::Credentials::new("rotated-id", "rotated-secret")
+                .set_security_token("rotated-token"),
+        ))));
+        let mut detached = oss.clone();
+        detached.set_security_token("user-token");
+        //由设置了安全令牌的实例触发刷新，刷新后的凭证仍然写入共享凭证
+        let provider = detached.credentials_provider.clone().unwrap();
+        block_on(provider.refresh(&detached, false)).unwrap();
+        let shared = oss.credentials();
+        assert_eq!(shared.signer.access_key_id(), "rotated-id");
+        assert_eq!(shared.security_token.as_deref(), Some("rotated-token"));
+        //设置了安全令牌的实例使用刷新后的AccessKey，安全令牌保持不变
+        let mut req = OssRequest::new(detached, Method::GET);
+        req.header_sign().unwrap();
+        assert_eq!(req.headers["x-oss-security-token"], "user-token");
+        assert!(header_str(&req.headers[header::AUTHORIZATION]).starts_with("OSS rotated-id:"));
+        //其他实例不受影响
+        let mut req = OssRequest::new(oss, Method::GET);
+        req.header_sign().unwrap();
+        assert_eq!(req.headers["x-oss-security-token"], "rotated-token");
+    }
+
+    //阿里云官方Go SDK（alibabacloud-oss-go-sdk-v2）V4签名测试中的请求，期望的签名值取自其测试用例
+    //
+    //AccessKey为ak/sk，地域cn-hangzhou，文件路径和查询参数包含需要编码的字符
+    fn v4_example_request(content_type: &str, security_token: Option<&str>) -> OssRequest {
+        let mut oss = Oss::new("ak", "sk");
+        oss.set_endpoint("oss-cn-hangzhou.aliyuncs.com");
+        oss.set_bucket("bucket");
+        oss.set_object("1234+-/123/1.txt");
+        oss.signature_version = SignatureVersion::V4;
+        oss.region = Some("cn-hangzhou".into());
+        if let Some(security_token) = security_token {
+            oss.set_security_token(security_token);
+        }
+        let mut req = OssRequest::new(oss, Method::PUT);
+        for (key, value) in [
+            ("x-oss-head1", "value"),
+            ("abc", "value"),
+            ("ZAbc", "value"),
+            ("XYZ", "value"),
+            ("content-type", content_type),
+        ] {
+            req.insert_header(key, value);
+        }
+        for (key, value) in [
+            ("param1", "value1"),
+            ("+param1", "value3"),
+            ("|param1", "value4"),
+            ("+param2", ""),
+            ("|param2", ""),
+            ("param2", ""),
+        ] {
+            req.insert_query(key, value);
+        }
+        req
+    }
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn v4_header_signature_matches_published_example() {
+        let mut req = v4_example_request("text/plain", None);
+        req.header_sign_v4(at(1702743657)).unwrap();
+        assert_eq!(
+            req.headers[header::AUTHORIZATION],
+            "OSS4-HMAC-SHA256 Credential=ak/20231216/cn-hangzhou/oss/aliyun_v4_request,\
+             Signature=e21d18daa82167720f9b1047ae7e7f1ce7cb77a31e8203a7d5f4624fa0284afe"
         );
-        //计算签名值
-        let key_str = hmac::Key::new(
-            hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
-            self.oss.ak_secret.as_bytes(),
+    }
+
+    #[test]
+    fn v4_header_signature_with_security_token() {
+        let mut req = v4_example_request("text/plain", Some("token"));
+        req.header_sign_v4(at(1702784856)).unwrap();
+        assert_eq!(req.headers["x-oss-security-token"], "token");
+        assert_eq!(
+            req.headers[header::AUTHORIZATION],
+            "OSS4-HMAC-SHA256 Credential=ak/20231217/cn-hangzhou/oss/aliyun_v4_request,\
+             Signature=b94a3f999cf85bcdc00d332fbd3734ba03e48382c36fa4d5af5df817395bd9ea"
         );
-        let sign_str =
-            general_purpose::STANDARD.encode(hmac::sign(&key_str, unsign_str.as_bytes()));
-        self.insert_header(header::DATE, date);
-        self.insert_header(
-            header::AUTHORIZATION,
-            format!("OSS {}:{}", self.oss.ak_id, sign_str),
+    }
+
+    #[test]
+    fn v4_header_signature_with_additional_headers() {
+        let mut req = v4_example_request("text/plain", None);
+        req.oss.additional_headers = vec!["zabc".to_owned(), "abc".to_owned()];
+        req.header_sign_v4(at(1702747512)).unwrap();
+        assert_eq!(
+            req.headers[header::AUTHORIZATION],
+            "OSS4-HMAC-SHA256 Credential=ak/20231216/cn-hangzhou/oss/aliyun_v4_request,\
+             AdditionalHeaders=abc;zabc,\
+             Signature=4a4183c187c07c8947db7620deb0a6b38d9fbdd34187b6dbaccb316fa251212f"
         );
     }
-    pub fn send_to_oss(mut self) -> Result<ResponseFuture, Error> {
-        //插入x-oss-security-token
-        if let Some(security_token) = self.oss.security_token.clone() {
-            self.insert_header("x-oss-security-token", security_token);
+
+    #[test]
+    fn v4_query_signature_matches_published_example() {
+        let mut req = v4_example_request("application/octet-stream", None);
+        req.query_sign_v4(1702782276, at(1702781677)).unwrap();
+        let query = |key: &str| {
+            req.querys
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
         };
-        //完成签名
-        self.header_sign();
-        //构建http请求
-        let mut req = Request::builder().method(&self.method).uri(&self.uri());
-        for (key, value) in self.headers.iter() {
-            req = req.header(key, value);
-        }
-        let request = req.body(self.body)?;
-        if self.oss.enable_https {
-            let client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
-            Ok(client.request(request))
-        } else {
-            Ok(Client::new().request(request))
-        }
+        assert_eq!(
+            query("x-oss-signature-version").as_deref(),
+            Some("OSS4-HMAC-SHA256")
+        );
+        assert_eq!(query("x-oss-expires").as_deref(), Some("599"));
+        assert_eq!(
+            query("x-oss-credential").as_deref(),
+            Some("ak/20231217/cn-hangzhou/oss/aliyun_v4_request")
+        );
+        assert_eq!(
+            query("x-oss-signature").as_deref(),
+            Some("a39966c61718be0d5b14e668088b3fa07601033f6518ac7b523100014269c0fe")
+        );
+        assert_eq!(query("x-oss-additional-headers"), None);
     }
 }
@@ -0,0 +1,209 @@
+//! 请求签名
+//!
+//! 默认使用内存中的AccessKey Secret计算签名；如需将密钥保存在HSM、KMS或独立的签名服务中，可以实现 Signer trait ，通过 OssClient::from_signer 初始化
+//!
+//! ```
+//! use aliyun_oss_rs::{signer::Signer, Error, OssClient};
+//!
+//! #[derive(Debug)]
+//! struct HsmSigner {
+//!     ak_id: String,
+//! }
+//! impl Signer for HsmSigner {
+//!     fn access_key_id(&self) -> &str {
+//!         &self.ak_id
+//!     }
+//!     fn sign(&self, string_to_sign: &str) -> Result<String, Error> {
+//!         // 调用HSM计算HMAC-SHA1，返回base64编码的签名值
+//!         hsm_hmac_sha1_base64(string_to_sign)
+//!     }
+//! }
+//!
+//! let client = OssClient::from_signer(HsmSigner { ak_id: "AccessKey ID".to_owned() });
+//! ```
+//!
+//! 调用本库尚未实现的API时，可以自行构建请求，通过 OssClient::sign_request 或 OssBucket::sign_request 完成签名后发送
+//!
+//! ```
+//! let mut request = hyper::Request::get("https://for-rs-test.oss-cn-zhangjiakou.aliyuncs.com/?inventory&inventoryId=list1")
+//!     .body(hyper::Body::empty())?;
+//! bucket.sign_request(&mut request)?;
+//! let response = hyper_client.request(request).await?;
+//! ```
+
+use crate::{
+    backend::{HmacSha1, HmacSha256},
+    request::{Oss, OssRequest},
+    Error,
+};
+use base64::{engine::general_purpose, Engine};
+use hyper::Request;
+use percent_encoding::percent_decode_str;
+use std::{
+    fmt::{self, Write},
+    sync::Mutex,
+};
+
+/// 请求签名器
+pub trait Signer: fmt::Debug + Send + Sync {
+    /// 签名使用的AccessKey ID
+    fn access_key_id(&self) -> &str;
+    /// 计算待签名字符串的签名值
+    ///
+    /// 使用AccessKey Secret作为密钥计算HMAC-SHA1，返回base64编码的结果
+    fn sign(&self, string_to_sign: &str) -> Result<String, Error>;
+    /// 计算V4签名的签名值
+    ///
+    /// 使用 "aliyun_v4" + AccessKey Secret 依次对 date（格式为 20240101）、region、product、"aliyun_v4_request" 计算HMAC-SHA256得到签名密钥，
+    /// 再使用签名密钥计算待签名字符串的HMAC-SHA256，返回小写十六进制的结果
+    ///
+    /// product通常为 "oss"，访问云盒时为 "oss-cloudbox"，此时region为云盒ID
+    ///
+    /// 默认返回错误，自定义签名器需要实现此方法才能使用V4签名
+    fn sign_v4(
+        &self,
+        date: &str,
+        region: &str,
+        product: &str,
+        string_to_sign: &str,
+    ) -> Result<String, Error> {
+        let _ = (date, region, product, string_to_sign);
+        Err(Error::CryptoError("签名器不支持V4签名".to_owned()))
+    }
+}
+
+/// 签名版本
+///
+/// - V1：HMAC-SHA1签名，默认使用
+/// - V4：HMAC-SHA256签名，签名中包含地域信息，需要能够从Endpoint推断地域或者通过 set_region 设置
+///
+/// 具体详情查阅 [阿里云官方文档](https://help.aliyun.com/zh/oss/developer-reference/recommend-to-use-signature-version-4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureVersion {
+    #[default]
+    V1,
+    V4,
+}
+
+/// 默认的签名器，使用内存中的AccessKey Secret计算签名
+///
+/// V1签名的密钥在创建时初始化；V4签名的密钥按日期、地域、产品派生，缓存最近一次使用的密钥，同一天内的请求不再重复派生
+pub struct HmacSigner {
+    ak_id: String,
+    key: HmacSha1,
+    v4_key: String,
+    //最近一次派生的V4签名密钥，以 date/region/product 作为标识
+    v4_signing_key: Mutex<Option<(String, HmacSha256)>>,
+}
+impl HmacSigner {
+    /// - ak_id ： 阿里云AccessKey ID
+    /// - ak_secret：阿里云AccessKey Secret
+    pub fn new(ak_id: &str, ak_secret: &str) -> Self {
+        HmacSigner {
+            ak_id: ak_id.to_owned(),
+            key: HmacSha1::new(ak_secret.as_bytes()),
+            v4_key: format!("aliyun_v4{}", ak_secret),
+            v4_signing_key: Mutex::new(None),
+        }
+    }
+}
+impl fmt::Debug for HmacSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSigner")
+            .field("ak_id", &self.ak_id)
+            .finish_non_exhaustive()
+    }
+}
+impl Signer for HmacSigner {
+    fn access_key_id(&self) -> &str {
+        &self.ak_id
+    }
+    fn sign(&self, string_to_sign: &str) -> Result<String, Error> {
+        Ok(general_purpose::STANDARD.encode(self.key.sign(string_to_sign.as_bytes())?))
+    }
+    fn sign_v4(
+        &self,
+        date: &str,
+        region: &str,
+        product: &str,
+        string_to_sign: &str,
+    ) -> Result<String, Error> {
+        let scope = format!("{}/{}/{}", date, region, product);
+        let mut signing_key = match self.v4_signing_key.lock() {
+            Ok(signing_key) => signing_key,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let signing_key = match &mut *signing_key {
+            Some((cached, key)) if *cached == scope => key,
+            signing_key => {
+                let mut key = self.v4_key.as_bytes().to_vec();
+                for data in [date, region, product, "aliyun_v4_request"] {
+                    key = HmacSha256::new(&key).sign(data.as_bytes())?;
+                }
+                &mut signing_key.insert((scope, HmacSha256::new(&key))).1
+            }
+        };
+        let signature = signing_key.sign(string_to_sign.as_bytes())?;
+        let mut hex = String::with_capacity(signature.len() * 2);
+        for v in signature {
+            let _ = write!(hex, "{:02x}", v);
+        }
+        Ok(hex)
+    }
+}
+
+//为自行构建的请求签名，签名相关的请求头写回请求中
+pub(crate) fn sign_request<B>(mut oss: Oss, request: &mut Request<B>) -> Result<(), Error> {
+    //文件路径，path-style时去除路径中的存储空间
+    let path = request.uri().path().trim_start_matches('/');
+    let path = match (&oss.bucket, oss.path_style) {
+        (Some(bucket), true) => path
+            .strip_prefix(bucket.as_ref())
+            .map(|v| v.trim_start_matches('/'))
+            .unwrap_or(path),
+        _ => path,
+    };
+    if !path.is_empty() {
+        oss.set_encoded_object(path);
+    }
+    let mut req = OssRequest::new(oss, request.method().clone());
+    //查询参数
+    if let Some(query) = request.uri().query() {
+        for pair in query.split('&').filter(|v| !v.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            req.append_query(
+                percent_decode_str(key).decode_utf8_lossy(),
+                percent_decode_str(value).decode_utf8_lossy(),
+            );
+        }
+    }
+    //请求头，保留同名请求头
+    for (key, value) in request.headers() {
+        req.headers.append(key, value.clone());
+    }
+    req.header_sign()?;
+    //写回签名时添加或更新的请求头
+    *request.headers_mut() = req.headers;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //缓存的派生密钥在日期、地域变化后重新派生，结果与新建的签名器一致
+    #[test]
+    fn v4_signing_key_is_derived_per_scope() {
+        let signer = HmacSigner::new("ak", "sk");
+        for (date, region) in [
+            ("20231216", "cn-hangzhou"),
+            ("20231217", "cn-hangzhou"),
+            ("20231217", "cn-shanghai"),
+            ("20231216", "cn-hangzhou"),
+        ] {
+            let cached = signer.sign_v4(date, region, "oss", "string to sign");
+            let fresh = HmacSigner::new("ak", "sk").sign_v4(date, region, "oss", "string to sign");
+            assert_eq!(cached.unwrap(), fresh.unwrap());
+        }
+    }
+}
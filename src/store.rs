@@ -0,0 +1,344 @@
+//! object_store 集成
+//!
+//! OssStore 实现了 [object_store](https://docs.rs/object_store) 的 ObjectStore trait，
+//! DataFusion、Arrow、Parquet等基于object_store的工具可以通过它直接读写OSS
+//!
+//! ```
+//! let bucket = client.bucket("for-rs-test", "oss-cn-zhangjiakou.aliyuncs.com");
+//! let store: Arc<dyn ObjectStore> = Arc::new(OssStore::new(bucket));
+//! let ctx = SessionContext::new();
+//! ctx.runtime_env()
+//!     .register_object_store(&Url::parse("oss://for-rs-test")?, store);
+//! ```
+
+use crate::{
+    common::{encode_key, OssDateTime, Paginator},
+    object::UploadSink,
+    Error, OssBucket,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
+use hyper::{header, HeaderMap, StatusCode};
+use object_store::{
+    path::Path, GetOptions, GetResult, GetResultPayload, ListResult, MultipartId, ObjectMeta,
+    ObjectStore,
+};
+use std::{fmt, ops::Range};
+use tokio::io::AsyncWrite;
+
+// 错误信息中的存储名称
+const STORE: &str = "AliyunOSS";
+
+/// 基于OssBucket的ObjectStore实现
+#[derive(Debug, Clone)]
+pub struct OssStore {
+    bucket: OssBucket,
+}
+
+impl OssStore {
+    /// 使用OssBucket初始化，OssBucket的https、自定义域名等设置均会生效
+    pub fn new(bucket: OssBucket) -> Self {
+        OssStore { bucket }
+    }
+    //复制文件时的来源
+    fn copy_source(&self, from: &Path) -> String {
+        format!(
+            "/{}/{}",
+            self.bucket.oss.bucket.clone().unwrap_or_default(),
+            encode_key(from.as_ref())
+        )
+    }
+}
+
+impl fmt::Display for OssStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OssStore({})",
+            self.bucket.oss.bucket.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for OssStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> object_store::Result<()> {
+        let len = bytes.len() as u64;
+        self.bucket
+            .object(location)
+            .put_object()
+            .send_body(bytes, len)
+            .await
+            .map(|_| ())
+            .map_err(|e| store_error(e, location))
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        let object = self.bucket.object(location);
+        let upload_id = object
+            .multipart_init_upload()
+            .send()
+            .await
+            .map_err(|e| store_error(e, location))?;
+        let sink = UploadSink::from_upload_id(object.oss, upload_id.clone());
+        Ok((upload_id, Box::new(sink)))
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> object_store::Result<()> {
+        self.bucket
+            .object(location)
+            .multipart_abort_upload(multipart_id)
+            .send()
+            .await
+            .map_err(|e| store_error(e, location))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let mut get_object = self.bucket.object(location).get_object();
+        if let Some(if_match) = &options.if_match {
+            get_object = get_object.set_if_match(if_match);
+        }
+        if let Some(if_none_match) = &options.if_none_match {
+            get_object = get_object.set_if_none_match(if_none_match);
+        }
+        if let Some(if_modified_since) = options.if_modified_since {
+            get_object = get_object.set_if_modified_since(if_modified_since.naive_utc());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            get_object = get_object.set_if_unmodified_since(if_unmodified_since.naive_utc());
+        }
+        if let Some(range) = options
+            .range
+            .as_ref()
+            .filter(|range| range.end > range.start)
+        {
+            get_object = get_object.set_range(range.start, Some(range.end - 1));
+        }
+        let response = get_object
+            .into_response()
+            .await
+            .map_err(|e| store_error(e, location))?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Err(object_store::Error::NotModified {
+                path: location.to_string(),
+                source: "文件未修改".into(),
+            });
+        }
+        let (range, size) = content_range(response.headers());
+        let meta = ObjectMeta {
+            location: location.clone(),
+            last_modified: last_modified(response.headers().get(header::LAST_MODIFIED)),
+            size,
+            e_tag: response
+                .headers()
+                .get(header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned()),
+        };
+        let stream = response.into_body().map_err(generic_error).boxed();
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(stream),
+            meta,
+            range,
+        })
+    }
+
+    async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+        let meta = self
+            .bucket
+            .object(location)
+            .get_object_meta()
+            .send()
+            .await
+            .map_err(|e| store_error(e, location))?;
+        Ok(ObjectMeta {
+            location: location.clone(),
+            last_modified: meta.last_modified.datetime().unwrap_or_default(),
+            size: meta.content_length.parse().unwrap_or_default(),
+            e_tag: Some(meta.e_tag),
+        })
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.bucket
+            .object(location)
+            .del_object()
+            .send()
+            .await
+            .map_err(|e| store_error(e, location))
+    }
+
+    async fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> object_store::Result<BoxStream<'_, object_store::Result<ObjectMeta>>> {
+        let stream = self
+            .bucket
+            .list_objects()
+            .set_prefix(list_prefix(prefix))
+            .into_stream()
+            .map(|item| {
+                item.map(|object| {
+                    object_meta(
+                        &object.key,
+                        object.size,
+                        object.e_tag,
+                        &object.last_modified,
+                    )
+                })
+                .map_err(generic_error)
+            })
+            .boxed();
+        Ok(stream)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        let mut result = ListResult {
+            common_prefixes: Vec::new(),
+            objects: Vec::new(),
+        };
+        let mut continuation_token = None;
+        loop {
+            let mut list_objects = self
+                .bucket
+                .list_objects()
+                .set_prefix(list_prefix(prefix))
+                .set_delimiter("/")
+                .set_max_keys(1000);
+            if let Some(token) = continuation_token.take() {
+                list_objects = list_objects.set_continuation_token(token);
+            }
+            let page = list_objects.send().await.map_err(generic_error)?;
+            result
+                .objects
+                .extend(page.contents.unwrap_or_default().into_iter().map(|object| {
+                    object_meta(
+                        &object.key,
+                        object.size,
+                        object.e_tag,
+                        &object.last_modified,
+                    )
+                }));
+            result.common_prefixes.extend(
+                page.common_prefixes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|common_prefix| Path::from(common_prefix.prefix.trim_end_matches('/'))),
+            );
+            match page.next_continuation_token {
+                Some(token) if page.is_truncated => continuation_token = Some(token),
+                _ => return Ok(result),
+            }
+        }
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.bucket
+            .object(to)
+            .copy_object(&self.copy_source(from))
+            .send()
+            .await
+            .map_err(|e| store_error(e, from))
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.bucket
+            .object(to)
+            .copy_object(&self.copy_source(from))
+            .forbid_overwrite()
+            .send()
+            .await
+            .map_err(|e| store_error(e, to))
+    }
+}
+
+//列举时使用的前缀，object_store的前缀按目录匹配
+fn list_prefix(prefix: Option<&Path>) -> String {
+    match prefix.map(|prefix| prefix.as_ref()) {
+        Some(prefix) if !prefix.is_empty() => format!("{}/", prefix),
+        _ => String::new(),
+    }
+}
+
+//转换文件信息
+fn object_meta(key: &str, size: u64, e_tag: String, last_modified: &OssDateTime) -> ObjectMeta {
+    ObjectMeta {
+        location: Path::from(key),
+        last_modified: last_modified.datetime().unwrap_or_default(),
+        size: size as usize,
+        e_tag: Some(e_tag),
+    }
+}
+
+//解析响应头 Last-Modified
+fn last_modified(value: Option<&hyper::header::HeaderValue>) -> DateTime<Utc> {
+    value
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| OssDateTime::parse(v).datetime())
+        .unwrap_or_default()
+}
+
+//解析返回的范围和文件总大小
+fn content_range(headers: &HeaderMap) -> (Range<usize>, usize) {
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or_default();
+    //格式为 bytes 0-99/1000
+    let content_range = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("bytes "))
+        .and_then(|v| v.split_once('/'))
+        .and_then(|(range, size)| {
+            let (start, end) = range.split_once('-')?;
+            Some((
+                start.parse::<usize>().ok()?..end.parse::<usize>().ok()? + 1,
+                size.parse::<usize>().ok()?,
+            ))
+        });
+    content_range.unwrap_or((0..content_length, content_length))
+}
+
+//转换错误
+fn store_error(e: Error, path: &Path) -> object_store::Error {
+    let path = path.to_string();
+    match e.status() {
+        Some(StatusCode::NOT_FOUND) => object_store::Error::NotFound {
+            path,
+            source: Box::new(e),
+        },
+        Some(StatusCode::PRECONDITION_FAILED) => object_store::Error::Precondition {
+            path,
+            source: Box::new(e),
+        },
+        Some(StatusCode::CONFLICT) if e.oss_code() == Some("FileAlreadyExists") => {
+            object_store::Error::AlreadyExists {
+                path,
+                source: Box::new(e),
+            }
+        }
+        _ => generic_error(e),
+    }
+}
+
+fn generic_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> object_store::Error {
+    object_store::Error::Generic {
+        store: STORE,
+        source: e.into(),
+    }
+}
@@ -0,0 +1,137 @@
+use super::{local_sync::list_remote_files, SyncReport};
+use crate::{request::Oss, transfer::TransferManager, Error, OssBucket};
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+/// 同步存储空间中的文件到另一个存储空间
+///
+/// 列举源存储空间中指定前缀下的文件，与目标存储空间进行对比，复制新增或发生变化的文件；文件大小不同，或者源文件的修改时间晚于目标文件的修改时间时，视为发生变化
+///
+/// 两个存储空间位于同一地域时，复制过程在OSS服务端完成，不经过本地；较大的文件使用分片复制，复制时保留文件的mime类型、自定义metadata、标签和ACL
+///
+/// OSS不支持跨地域复制文件，位于不同地域时改为边下载边上传（PipeObject），数据经过本地但不落盘，只保留mime类型和自定义metadata
+///
+/// ```
+/// let report = BucketSync::new(&source_bucket, &target_bucket)
+///     .set_prefix("images/")
+///     .set_target_prefix("backup/images/")
+///     .send()
+///     .await;
+/// ```
+pub struct BucketSync {
+    source: OssBucket,
+    target: OssBucket,
+    prefix: String,
+    target_prefix: Option<String>,
+    delete_extra: bool,
+    dry_run: bool,
+    manager: TransferManager,
+}
+impl BucketSync {
+    pub fn new(source: &OssBucket, target: &OssBucket) -> Self {
+        BucketSync {
+            source: source.clone(),
+            target: target.clone(),
+            prefix: String::new(),
+            target_prefix: None,
+            delete_extra: false,
+            dry_run: false,
+            manager: TransferManager::new(),
+        }
+    }
+    /// 只同步源存储空间中指定前缀下的文件
+    pub fn set_prefix(mut self, prefix: impl ToString) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+    /// 设置目标存储空间中的前缀，文件路径中的源前缀将被替换为目标前缀
+    ///
+    /// 默认与源前缀相同
+    pub fn set_target_prefix(mut self, target_prefix: impl ToString) -> Self {
+        self.target_prefix = Some(target_prefix.to_string());
+        self
+    }
+    /// 删除目标存储空间中存在、但源存储空间中不存在的文件
+    pub fn delete_extra(mut self) -> Self {
+        self.delete_extra = true;
+        self
+    }
+    /// 只对比文件，不执行复制和删除，返回将要执行的操作
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+    /// 设置复制文件使用的传输管理器，可用于设置并发数、分片大小、重试和进度
+    pub fn set_transfer_manager(mut self, manager: TransferManager) -> Self {
+        self.manager = manager;
+        self
+    }
+    /// 开始同步
+    ///
+    pub async fn send(self) -> Result<SyncReport, Error> {
+        let target_prefix = self.target_prefix.as_ref().unwrap_or(&self.prefix);
+        //列举文件
+        let source_files = list_remote_files(&self.source, &self.prefix).await?;
+        let mut target_files = list_remote_files(&self.target, target_prefix).await?;
+        //对比文件
+        let mut report = SyncReport::default();
+        let mut copies = Vec::new();
+        for (key, source_file) in source_files {
+            let target_key = format!(
+                "{}{}",
+                target_prefix,
+                key.strip_prefix(&self.prefix).unwrap_or(&key)
+            );
+            let changed = match target_files.remove(&target_key) {
+                None => true,
+                Some(target_file) => {
+                    target_file.size != source_file.size
+                        || source_file.last_modified > target_file.last_modified
+                }
+            };
+            if changed {
+                copies.push((key, target_key));
+            } else {
+                report.skipped += 1;
+            }
+        }
+        //剩余的目标文件即为多余文件
+        let deletes = match self.delete_extra {
+            true => target_files.into_keys().collect::<Vec<_>>(),
+            false => Vec::new(),
+        };
+        if self.dry_run {
+            report.transferred = copies.into_iter().map(|(_, key)| key).collect();
+            report.deleted = deletes;
+            return Ok(report);
+        }
+        //复制文件，并发数与传输管理器同时进行的任务数量一致
+        let (source, target, manager) = (&self.source, &self.target, &self.manager);
+        let server_side = same_region(&source.oss, &target.oss);
+        report.transferred = stream::iter(copies)
+            .map(|(key, target_key)| async move {
+                let (source, target) = (source.object(&key), target.object(&target_key));
+                match server_side {
+                    true => manager.copy(&source, &target).await,
+                    false => source.pipe_to(&target).send().await,
+                }
+                .map(|_| target_key)
+            })
+            .buffer_unordered(manager.max_transfers())
+            .try_collect()
+            .await?;
+        //删除多余文件，每次最多删除1000个
+        for keys in deletes.chunks(1000) {
+            self.target.del_objects(keys.to_vec()).send().await?;
+        }
+        report.deleted = deletes;
+        Ok(report)
+    }
+}
+
+//两个存储空间是否位于同一地域，无法从Endpoint推断地域时按Endpoint是否相同判断
+fn same_region(source: &Oss, target: &Oss) -> bool {
+    match (source.region(), target.region()) {
+        (Ok(source), Ok(target)) => source == target,
+        _ => source.endpoint == target.endpoint,
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{checksum::file_crc64, common::Paginator, transfer::TransferManager, Error, OssBucket};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde_derive::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::UNIX_EPOCH,
+};
+use tokio::fs;
+
+/// 判断文件是否发生变化的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompareMode {
+    /// 文件大小不同，或者本地文件的修改时间晚于OSS文件的修改时间
+    #[default]
+    SizeAndModified,
+    /// 文件大小不同，或者CRC64不同
+    ///
+    /// 需要读取本地文件计算CRC64，并对每个文件发起一次HeadObject请求
+    Crc64,
+}
+
+/// 同步结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    /// 上传或复制的文件（包含新增和发生变化的文件）
+    pub transferred: Vec<String>,
+    /// 删除的目标文件
+    pub deleted: Vec<String>,
+    /// 未发生变化，跳过的文件数量
+    pub skipped: usize,
+}
+
+// OSS文件信息
+pub(super) struct RemoteFile {
+    pub size: u64,
+    pub last_modified: i64,
+}
+
+/// 同步本地目录到OSS
+///
+/// 递归列举本地目录中的所有文件，与OSS中指定前缀下的文件进行对比，只上传新增或发生变化的文件
+///
+/// 文件路径为 前缀 + 文件相对本地目录的路径，路径分隔符统一为 /
+///
+/// ```
+/// let report = LocalSync::new(&bucket, "/data/images", "images/")
+///     .delete_extra()
+///     .dry_run()
+///     .send()
+///     .await;
+/// ```
+pub struct LocalSync {
+    bucket: OssBucket,
+    local_dir: PathBuf,
+    prefix: String,
+    compare_mode: CompareMode,
+    delete_extra: bool,
+    dry_run: bool,
+    manager: TransferManager,
+}
+impl LocalSync {
+    pub fn new(bucket: &OssBucket, local_dir: impl Into<PathBuf>, prefix: impl ToString) -> Self {
+        LocalSync {
+            bucket: bucket.clone(),
+            local_dir: local_dir.into(),
+            prefix: prefix.to_string(),
+            compare_mode: CompareMode::default(),
+            delete_extra: false,
+            dry_run: false,
+            manager: TransferManager::new(),
+        }
+    }
+    /// 设置判断文件是否发生变化的方式
+    ///
+    /// 默认值：CompareMode::SizeAndModified
+    pub fn set_compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.compare_mode = compare_mode;
+        self
+    }
+    /// 删除OSS中存在、但本地不存在的文件
+    pub fn delete_extra(mut self) -> Self {
+        self.delete_extra = true;
+        self
+    }
+    /// 只对比文件，不执行上传和删除，返回将要执行的操作
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+    /// 设置上传文件使用的传输管理器，可用于设置并发数、分片大小、限速、重试和进度
+    pub fn set_transfer_manager(mut self, manager: TransferManager) -> Self {
+        self.manager = manager;
+        self
+    }
+    /// 开始同步
+    ///
+    pub async fn send(self) -> Result<SyncReport, Error> {
+        //列举本地文件
+        let local_files = list_local_files(&self.local_dir).await?;
+        //列举OSS文件
+        let remote_files = list_remote_files(&self.bucket, &self.prefix).await?;
+        //对比文件，按CRC64对比时需要请求OSS，并发数与传输管理器同时进行的任务数量一致
+        let (bucket, compare_mode) = (&self.bucket, self.compare_mode);
+        let compared = stream::iter(local_files)
+            .map(|(relative, path, size, modified)| {
+                let key = format!("{}{}", self.prefix, relative);
+                let remote = remote_files.get(&key);
+                async move {
+                    let changed = match remote {
+                        None => true,
+                        Some(remote) if remote.size != size => true,
+                        Some(remote) => match compare_mode {
+                            CompareMode::SizeAndModified => modified > remote.last_modified,
+                            CompareMode::Crc64 => {
+                                let headers = bucket.object(&key).head_object().send().await?;
+                                let remote_crc64 = headers.get("x-oss-hash-crc64ecma");
+                                remote_crc64 != Some(&file_crc64(&path).await?.to_string())
+                            }
+                        },
+                    };
+                    Ok::<_, Error>((key, path, changed))
+                }
+            })
+            .buffer_unordered(self.manager.max_transfers())
+            .try_collect::<Vec<_>>()
+            .await?;
+        let mut report = SyncReport::default();
+        let mut uploads = Vec::new();
+        let mut local_keys = HashSet::with_capacity(compared.len());
+        for (key, path, changed) in compared {
+            if changed {
+                uploads.push((key.clone(), path));
+            } else {
+                report.skipped += 1;
+            }
+            local_keys.insert(key);
+        }
+        let deletes = match self.delete_extra {
+            true => remote_files
+                .into_keys()
+                .filter(|key| !local_keys.contains(key))
+                .collect::<Vec<_>>(),
+            false => Vec::new(),
+        };
+        if self.dry_run {
+            report.transferred = uploads.into_iter().map(|(key, _)| key).collect();
+            report.deleted = deletes;
+            return Ok(report);
+        }
+        //上传文件，并发数由传输管理器控制
+        let manager = &self.manager;
+        report.transferred = stream::iter(uploads)
+            .map(|(key, path)| async move {
+                manager
+                    .upload(path.to_string_lossy(), &bucket.object(&key))
+                    .await
+                    .map(|_| key)
+            })
+            .buffer_unordered(manager.max_transfers())
+            .try_collect()
+            .await?;
+        //删除多余文件，每次最多删除1000个
+        for keys in deletes.chunks(1000) {
+            self.bucket.del_objects(keys.to_vec()).send().await?;
+        }
+        report.deleted = deletes;
+        Ok(report)
+    }
+}
+
+//列举OSS中指定前缀下的所有文件
+pub(super) async fn list_remote_files(
+    bucket: &OssBucket,
+    prefix: &str,
+) -> Result<HashMap<String, RemoteFile>, Error> {
+    let mut remote_files = HashMap::new();
+    for object in bucket.list_objects().set_prefix(prefix).all().await? {
+        let last_modified = object.last_modified.timestamp().unwrap_or_default();
+        remote_files.insert(
+            object.key,
+            RemoteFile {
+                size: object.size,
+                last_modified,
+            },
+        );
+    }
+    Ok(remote_files)
+}
+
+//递归列举目录中的所有文件，返回 (相对路径,完整路径,文件大小,修改时间)
+async fn list_local_files(dir: &PathBuf) -> Result<Vec<(String, PathBuf, u64, i64)>, Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.clone()];
+    while let Some(current) = dirs.pop() {
+        let mut entries = fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let metadata = fs::metadata(&path).await?;
+            if metadata.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let relative = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|v| v.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|v| v.duration_since(UNIX_EPOCH).ok())
+                .map(|v| v.as_secs() as i64)
+                .unwrap_or_default();
+            files.push((relative, path, metadata.len(), modified));
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,9 @@
+//! 数据同步，对比本地文件和OSS文件列表，只传输新增或发生变化的文件
+
+pub use self::{
+    bucket_sync::BucketSync,
+    local_sync::{CompareMode, LocalSync, SyncReport},
+};
+
+mod bucket_sync;
+mod local_sync;
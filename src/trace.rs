@@ -0,0 +1,132 @@
+//! 链路追踪
+//!
+//! 开启 tracing feature 后，每个OSS请求创建一个名为 oss.request 的span，记录存储空间、文件路径、请求方法、
+//! 状态码、Request ID、重试次数和收发的字节数；span持续到响应body读取完毕或者被丢弃为止
+//!
+//! 未开启时，所有方法均为空实现
+
+use crate::Error;
+use futures_util::Future;
+use hyper::{Body, Method, Response};
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::Span;
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub(crate) struct Span;
+
+//创建请求的span
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(
+    method: &Method,
+    bucket: Option<&str>,
+    key: Option<&str>,
+    bytes_sent: Option<u64>,
+) -> Span {
+    tracing::info_span!(
+        "oss.request",
+        method = %method,
+        bucket = bucket.unwrap_or_default(),
+        key = key.unwrap_or_default(),
+        bytes_sent = bytes_sent.unwrap_or_default(),
+        status = tracing::field::Empty,
+        request_id = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+        bytes_received = tracing::field::Empty,
+        error = tracing::field::Empty,
+    )
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn request_span(
+    _method: &Method,
+    _bucket: Option<&str>,
+    _key: Option<&str>,
+    _bytes_sent: Option<u64>,
+) -> Span {
+    Span
+}
+
+//在span中执行请求
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument<F: Future>(span: Span, future: F) -> impl Future<Output = F::Output> {
+    tracing::Instrument::instrument(future, span)
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn instrument<F: Future>(_span: Span, future: F) -> impl Future<Output = F::Output> {
+    future
+}
+
+//记录一次重试
+#[cfg(feature = "tracing")]
+pub(crate) fn retry(attempt: u32, reason: &dyn std::fmt::Display) {
+    tracing::warn!(attempt, reason = %reason, "OSS请求失败，准备重试");
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn retry(_attempt: u32, _reason: &dyn std::fmt::Display) {}
+
+//记录请求失败
+#[cfg(feature = "tracing")]
+pub(crate) fn record_error(span: &Span, attempts: u32, e: &Error) {
+    span.record("attempts", attempts + 1);
+    span.record("error", tracing::field::display(e));
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_error(_span: &Span, _attempts: u32, _e: &Error) {}
+
+//记录响应信息，并统计读取的响应body字节数
+#[cfg(feature = "tracing")]
+pub(crate) fn record_response(
+    span: Span,
+    attempts: u32,
+    response: Response<Body>,
+) -> Response<Body> {
+    use futures_util::StreamExt;
+    use hyper::body::HttpBody;
+
+    span.record("status", response.status().as_u16());
+    span.record("attempts", attempts + 1);
+    if let Some(request_id) = response
+        .headers()
+        .get("x-oss-request-id")
+        .and_then(|v| v.to_str().ok())
+    {
+        span.record("request_id", request_id);
+    }
+    if response.body().is_end_stream() {
+        span.record("bytes_received", 0);
+        return response;
+    }
+    //body读取完毕或者被丢弃时，记录实际读取的字节数
+    struct Counter {
+        span: Span,
+        bytes: u64,
+    }
+    impl Counter {
+        fn add(&mut self, len: usize) {
+            self.bytes += len as u64;
+        }
+    }
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            self.span.record("bytes_received", self.bytes);
+        }
+    }
+    let mut counter = Counter { span, bytes: 0 };
+    let (parts, body) = response.into_parts();
+    let body = body.map(move |item| {
+        if let Ok(chunk) = &item {
+            counter.add(chunk.len());
+        }
+        item
+    });
+    Response::from_parts(parts, Body::wrap_stream(body))
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_response(
+    _span: Span,
+    _attempts: u32,
+    response: Response<Body>,
+) -> Response<Body> {
+    response
+}
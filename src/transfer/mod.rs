@@ -0,0 +1,5 @@
+//! 传输管理，统一管理上传、下载、复制任务的并发数、分片大小、限速、重试和进度
+
+pub use self::transfer_manager::TransferManager;
+
+mod transfer_manager;
@@ -0,0 +1,422 @@
+use crate::{
+    common::{cancellable, encode_key, Acl},
+    error::{normal_error, Error},
+    progress::{Aggregator, ProgressEvent, ProgressListener},
+    request::OssRequest,
+    OssObject,
+};
+use futures_util::{stream, StreamExt};
+use hyper::{header, Method};
+use std::sync::Arc;
+use tokio::{
+    fs,
+    sync::{Semaphore, SemaphorePermit},
+};
+use tokio_util::sync::CancellationToken;
+
+/// 传输管理器
+///
+/// 统一管理多个上传、下载、复制任务，所有任务共享并发数限制、分片策略、限速、重试策略，并汇总所有任务的传输进度
+///
+/// 文件大小未超过分片阈值时，使用单个请求完成传输；超过分片阈值时，使用分片并发传输
+///
+/// ```
+/// let manager = TransferManager::new()
+///     .set_max_transfers(2)
+///     .set_part_size(20 * 1024 * 1024)
+///     .set_callback(Box::new(|transferred_size, total_size| {
+///         println!("{}/{}", transferred_size, total_size);
+///     }));
+/// let object = client.bucket("example-bucket", "oss-cn-zhangjiakou.aliyuncs.com").object("rust.mp4");
+/// let result = manager.upload("/data/rust.mp4", &object).await;
+/// ```
+#[derive(Clone)]
+pub struct TransferManager {
+    part_size: u64,
+    multipart_threshold: u64,
+    parallel: usize,
+    max_retries: u32,
+    traffic_limit: Option<u64>,
+    max_transfers: usize,
+    semaphore: Arc<Semaphore>,
+    progress: Option<Arc<Aggregator>>,
+    cancellation_token: Option<CancellationToken>,
+}
+impl TransferManager {
+    pub fn new() -> Self {
+        TransferManager {
+            part_size: 10_485_760,
+            multipart_threshold: 104_857_600,
+            parallel: 4,
+            max_retries: 2,
+            traffic_limit: None,
+            max_transfers: 4,
+            semaphore: Arc::new(Semaphore::new(4)),
+            progress: None,
+            cancellation_token: None,
+        }
+    }
+    /// 设置分片大小，单位字节
+    ///
+    /// 默认值：10MB，最小值：100KB
+    pub fn set_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(102_400);
+        self
+    }
+    /// 设置使用分片传输的文件大小阈值，单位字节
+    ///
+    /// 默认值：100MB
+    pub fn set_multipart_threshold(mut self, multipart_threshold: u64) -> Self {
+        self.multipart_threshold = multipart_threshold;
+        self
+    }
+    /// 设置单个任务同时传输的分片数量
+    ///
+    /// 默认值：4
+    pub fn set_parallel(mut self, parallel: usize) -> Self {
+        self.parallel = parallel.max(1);
+        self
+    }
+    /// 设置同时进行的任务数量，超出的任务将等待
+    ///
+    /// 默认值：4
+    pub fn set_max_transfers(mut self, max_transfers: usize) -> Self {
+        self.max_transfers = max_transfers.max(1);
+        self.semaphore = Arc::new(Semaphore::new(self.max_transfers));
+        self
+    }
+    //同时进行的任务数量，批量任务按此数量并发提交
+    pub(crate) fn max_transfers(&self) -> usize {
+        self.max_transfers
+    }
+    /// 设置请求失败时的最大重试次数，仅对网络异常、OSS服务端错误和数据校验失败进行重试
+    ///
+    /// 默认值：2
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// 限制每个请求的传输速度，单位bit/s，由OSS服务端进行限速，对复制任务无效
+    ///
+    /// 取值范围：819200 - 838860800
+    pub fn set_traffic_limit(mut self, traffic_limit: u64) -> Self {
+        self.traffic_limit = Some(traffic_limit);
+        self
+    }
+    /// 设置传输进度的回调方法，参数为所有任务已传输的大小和总大小
+    ///
+    /// 需要在提交任务之前设置
+    pub fn set_callback(mut self, callback: Box<dyn Fn(u64, u64) + Send + Sync + 'static>) -> Self {
+        self.progress = Some(Aggregator::new(Arc::new(callback), 0, 0));
+        self
+    }
+    /// 设置传输进度的监听，传输进度为所有任务汇总的进度；分片完成、重试、完成和失败事件按任务分别通知
+    ///
+    /// 需要在提交任务之前设置，与set_callback()只能生效一个，后设置的覆盖先设置的
+    pub fn set_progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.progress = Some(Aggregator::new(listener, 0, 0));
+        self
+    }
+    /// 设置取消令牌，令牌被触发后所有任务停止传输，返回 Error::Cancelled
+    ///
+    /// 等待中的任务不再开始；未完成的分片上传、分片复制会被取消，未完成的下载会删除临时文件。
+    /// 同步目录时使用同一个传输管理器，可以通过令牌停止同步
+    ///
+    /// ```
+    /// let token = CancellationToken::new();
+    /// let manager = TransferManager::new().set_cancellation_token(token.clone());
+    /// tokio::spawn(async move {
+    ///     tokio::signal::ctrl_c().await.ok();
+    ///     token.cancel();
+    /// });
+    /// ```
+    pub fn set_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+    /// 上传本地文件到OSS
+    ///
+    pub async fn upload(&self, file: impl ToString, object: &OssObject) -> Result<(), Error> {
+        let result = self.upload_file(file.to_string(), object).await;
+        self.finish(&result);
+        result
+    }
+    async fn upload_file(&self, file_path: String, object: &OssObject) -> Result<(), Error> {
+        let _permit = self.acquire().await?;
+        let file_size = fs::metadata(&file_path).await?.len();
+        let listener = self.add_task(file_size);
+        if file_size > self.multipart_threshold {
+            let mut upload = object
+                .multipart_upload_file()
+                .set_part_size(self.part_size)
+                .set_parallel(self.parallel)
+                .set_max_retries(self.max_retries);
+            if let Some(listener) = listener {
+                upload = upload.set_progress_listener(listener);
+            }
+            if let Some(traffic_limit) = self.traffic_limit {
+                upload = upload.set_traffic_limit(traffic_limit);
+            }
+            if let Some(token) = &self.cancellation_token {
+                upload = upload.set_cancellation_token(token.clone());
+            }
+            upload.send_file(&file_path).await?;
+            return Ok(());
+        }
+        let mut retries = 0;
+        loop {
+            let mut put_object = object.put_object();
+            if let Some(listener) = &listener {
+                put_object = put_object.set_progress_listener(listener.clone());
+            }
+            if let Some(traffic_limit) = self.traffic_limit {
+                put_object = put_object.set_traffic_limit(traffic_limit);
+            }
+            let result = cancellable(
+                self.cancellation_token.as_ref(),
+                put_object.send_file(&file_path),
+            )
+            .await?;
+            match result {
+                Err(e) if retries < self.max_retries && e.is_retryable() => {
+                    retries += 1;
+                    retry_event(listener.as_deref(), None, retries, &e);
+                }
+                result => return result,
+            }
+        }
+    }
+    /// 下载OSS文件到本地
+    ///
+    /// 不支持网络路径，如果需要保存到smb\nfs等网络存储，请先挂载到本地，再使用本地路径地址
+    pub async fn download(&self, object: &OssObject, save_path: &str) -> Result<(), Error> {
+        let result = self.download_file(object, save_path).await;
+        self.finish(&result);
+        result
+    }
+    async fn download_file(&self, object: &OssObject, save_path: &str) -> Result<(), Error> {
+        let _permit = self.acquire().await?;
+        let meta = OssObject {
+            oss: object.oss.without_cache(),
+        }
+        .get_object_meta()
+        .send()
+        .await?;
+        let file_size: u64 = meta
+            .content_length
+            .parse()
+            .map_err(|_| Error::OssInvalidResponse(None))?;
+        let listener = self.add_task(file_size);
+        //未超过分片阈值时，使用单个分片下载
+        let part_size = if file_size > self.multipart_threshold {
+            self.part_size
+        } else {
+            file_size.max(102_400)
+        };
+        let mut download = object
+            .multipart_download_file()
+            .set_part_size(part_size)
+            .set_parallel(self.parallel)
+            .set_max_retries(self.max_retries);
+        if let Some(listener) = listener {
+            download = download.set_progress_listener(listener);
+        }
+        if let Some(traffic_limit) = self.traffic_limit {
+            download = download.set_traffic_limit(traffic_limit);
+        }
+        if let Some(token) = &self.cancellation_token {
+            download = download.set_cancellation_token(token.clone());
+        }
+        download.download_to_file(save_path).await
+    }
+    /// 复制OSS文件，复制过程在OSS服务端完成，不经过本地
+    ///
+    /// 源文件和目标文件需要位于同一地域；复制时保留源文件的mime类型、自定义metadata、标签和ACL
+    pub async fn copy(&self, source: &OssObject, target: &OssObject) -> Result<(), Error> {
+        let result = self.copy_object(source, target).await;
+        self.finish(&result);
+        result
+    }
+    async fn copy_object(&self, source: &OssObject, target: &OssObject) -> Result<(), Error> {
+        let _permit = self.acquire().await?;
+        let meta = OssObject {
+            oss: source.oss.without_cache(),
+        }
+        .get_object_meta()
+        .send()
+        .await?;
+        let file_size: u64 = meta
+            .content_length
+            .parse()
+            .map_err(|_| Error::OssInvalidResponse(None))?;
+        let listener = self.add_task(file_size);
+        let copy_source = format!(
+            "/{}/{}",
+            source.oss.bucket.clone().unwrap_or_default(),
+            encode_key(&source.oss.object.clone().unwrap_or_default())
+        );
+        let if_match = format!("\"{}\"", meta.e_tag);
+        //未超过分片阈值时，使用CopyObject完成复制
+        if file_size <= self.multipart_threshold {
+            let mut retries = 0;
+            loop {
+                let copy_object = target.copy_object(&copy_source).set_if_match(&if_match);
+                let result =
+                    cancellable(self.cancellation_token.as_ref(), copy_object.send()).await?;
+                match result {
+                    Err(e) if retries < self.max_retries && e.is_retryable() => {
+                        retries += 1;
+                        retry_event(listener.as_deref(), None, retries, &e);
+                    }
+                    result => {
+                        result?;
+                        progress_event(listener.as_deref(), file_size, file_size);
+                        return copy_acl(source, target).await;
+                    }
+                }
+            }
+        }
+        //分片复制，分片数量不超过10000
+        let part_size = self.part_size.max(file_size.div_ceil(10_000));
+        let part_count = file_size.div_ceil(part_size) as u32;
+        //分片复制不会自动复制元信息，需要手动设置
+        //HeadObject的结果中不包含Content-Type，直接读取响应头
+        let response = OssRequest::new(source.oss.clone(), Method::HEAD)
+            .send_to_oss()?
+            .await?;
+        if !response.status().is_success() {
+            return Err(normal_error(response).await);
+        }
+        let mut init = target.multipart_init_upload();
+        for (key, value) in response.headers() {
+            let value = match value.to_str() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            if key == header::CONTENT_TYPE {
+                init = init.set_mime(value);
+            } else if let Some(key) = key.as_str().strip_prefix("x-oss-meta-") {
+                init = init.set_meta(key, value);
+            }
+        }
+        for tag in source
+            .get_object_tagging()
+            .send()
+            .await?
+            .unwrap_or_default()
+        {
+            init = init.set_tagging(tag.key, tag.value);
+        }
+        let upload_id = init.send().await?;
+        let mut copies = stream::iter(1..=part_count)
+            .map(|part_number| {
+                let start = (part_number as u64 - 1) * part_size;
+                let len = part_size.min(file_size - start);
+                let (upload_id, copy_source, if_match) = (&upload_id, &copy_source, &if_match);
+                let listener = listener.as_deref();
+                async move {
+                    let mut retries = 0;
+                    loop {
+                        let result = target
+                            .multipart_copy_part(part_number, upload_id, copy_source)
+                            .set_source_range(start as usize, Some((start + len - 1) as usize))
+                            .set_if_match(if_match)
+                            .send()
+                            .await;
+                        match result {
+                            Err(e) if retries < self.max_retries && e.is_retryable() => {
+                                retries += 1;
+                                retry_event(listener, Some(part_number), retries, &e);
+                            }
+                            result => return result.map(|e_tag| (part_number, e_tag, len)),
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(self.parallel);
+        let mut parts = Vec::with_capacity(part_count as usize);
+        let mut copied_size = 0;
+        while let Some(result) = cancellable(self.cancellation_token.as_ref(), copies.next())
+            .await
+            .transpose()
+        {
+            match result.and_then(|result| result) {
+                Ok((part_number, e_tag, len)) => {
+                    parts.push((part_number, e_tag));
+                    copied_size += len;
+                    if let Some(listener) = &listener {
+                        listener.on_event(&ProgressEvent::PartCompleted {
+                            part_number,
+                            size: len,
+                        });
+                    }
+                    progress_event(listener.as_deref(), copied_size, file_size);
+                }
+                Err(e) => {
+                    drop(copies);
+                    let _ = target.multipart_abort_upload(&upload_id).send().await;
+                    return Err(e);
+                }
+            }
+        }
+        drop(copies);
+        target
+            .multipart_complete_upload(&upload_id)
+            .add_parts(parts)
+            .send()
+            .await?;
+        copy_acl(source, target).await
+    }
+    //新增一个传输任务，返回任务的进度监听
+    fn add_task(&self, size: u64) -> Option<Arc<dyn ProgressListener>> {
+        let progress = self.progress.as_ref()?;
+        progress.add_total(size);
+        Some(progress.part())
+    }
+    //通知任务的传输结果
+    fn finish<T>(&self, result: &Result<T, Error>) {
+        if let Some(progress) = &self.progress {
+            progress.finish(result);
+        }
+    }
+    //获取任务许可，信号量不会被关闭，获取失败时不限制并发
+    async fn acquire(&self) -> Result<Option<SemaphorePermit<'_>>, Error> {
+        cancellable(self.cancellation_token.as_ref(), self.semaphore.acquire())
+            .await
+            .map(|permit| permit.ok())
+    }
+}
+
+fn progress_event(listener: Option<&dyn ProgressListener>, transferred: u64, total: u64) {
+    if let Some(listener) = listener {
+        listener.on_event(&ProgressEvent::Progress { transferred, total });
+    }
+}
+
+fn retry_event(
+    listener: Option<&dyn ProgressListener>,
+    part_number: Option<u32>,
+    attempt: u32,
+    error: &Error,
+) {
+    if let Some(listener) = listener {
+        listener.on_event(&ProgressEvent::Retry {
+            part_number,
+            attempt,
+            error: error.to_string(),
+        });
+    }
+}
+
+//复制文件ACL，源文件ACL为继承存储空间ACL时无需设置
+async fn copy_acl(source: &OssObject, target: &OssObject) -> Result<(), Error> {
+    match source.get_object_acl().send().await?.acl {
+        Acl::Default => Ok(()),
+        acl => target.put_object_acl(acl).send().await,
+    }
+}
+impl Default for TransferManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
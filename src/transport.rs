@@ -0,0 +1,122 @@
+//! 自定义HTTP传输
+//!
+//! 所有请求在签名完成后通过 Transport 发送，默认使用hyper的连接池；通过 OssClientBuilder::set_transport 替换后，
+//! 可以在单元测试中返回预设的响应，并检查签名后的请求，无需访问OSS
+//!
+//! ```
+//! use aliyun_oss_rs::transport::MockTransport;
+//!
+//! let transport = MockTransport::new();
+//! transport.push_response(StatusCode::OK, "hello");
+//! let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+//!     .set_transport(transport.clone())
+//!     .build()?;
+//! let object = client.bucket("for-rs-test", "oss-cn-zhangjiakou.aliyuncs.com").object("hello.txt");
+//! assert_eq!(object.get_object().download().await?, "hello");
+//! let requests = transport.requests();
+//! assert_eq!(requests[0].method, Method::GET);
+//! assert!(requests[0].headers.contains_key("authorization"));
+//! ```
+//!
+//! 开启 replay feature 后，RecordTransport 将访问OSS的真实请求和响应录制到记录文件中，ReplayTransport 读取记录文件并按顺序回放，
+//! 用于编写确定性的集成测试；录制时签名和临时凭证会被脱敏，使用 RecordTransport 时，OssClientBuilder 的代理、DNS和连接池设置不生效
+
+use crate::Error;
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use hyper::{body::to_bytes, Body, HeaderMap, Method, Request, Response, StatusCode, Uri};
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "replay")]
+pub use self::replay::{RecordTransport, ReplayTransport};
+
+/// HTTP传输
+///
+/// 请求已经完成签名，实现时不应修改参与签名的请求头
+pub trait Transport: Send + Sync {
+    /// 发送请求，返回OSS的响应
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, Result<Response<Body>, Error>>;
+}
+
+/// MockTransport记录的请求
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// 请求方法
+    pub method: Method,
+    /// 请求地址，包含查询参数
+    pub uri: Uri,
+    /// 请求头，包含签名
+    pub headers: HeaderMap,
+    /// 请求body
+    pub body: Bytes,
+}
+
+/// 用于测试的传输，按顺序返回预设的响应，并记录所有请求
+///
+/// 预设的响应用完后，请求返回 io::ErrorKind::NotConnected 错误；克隆后共享预设的响应和请求记录
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    inner: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<Response<Bytes>>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+    /// 添加一个预设的响应
+    pub fn push_response(&self, status: StatusCode, body: impl Into<Bytes>) {
+        let mut response = Response::new(body.into());
+        *response.status_mut() = status;
+        self.push(response);
+    }
+    /// 添加一个带有响应头的预设响应
+    pub fn push(&self, response: Response<Bytes>) {
+        self.lock().responses.push_back(response);
+    }
+    /// 已经发送的所有请求
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        let mock = self.clone();
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = to_bytes(body).await?;
+            let mut inner = mock.lock();
+            inner.requests.push(RecordedRequest {
+                method: parts.method,
+                uri: parts.uri,
+                headers: parts.headers,
+                body,
+            });
+            match inner.responses.pop_front() {
+                Some(response) => Ok(response.map(Body::from)),
+                None => Err(Error::IoError(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "MockTransport没有预设的响应",
+                ))),
+            }
+        })
+    }
+}
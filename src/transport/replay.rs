@@ -0,0 +1,249 @@
+use super::Transport;
+use crate::{request::HttpClient, Error};
+use base64::{engine::general_purpose, Engine};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use hyper::{
+    body::to_bytes,
+    header::{HeaderName, HeaderValue},
+    Body, Method, Request, Response, StatusCode, Uri,
+};
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+// 需要脱敏的查询参数，预签名URL中的签名和临时凭证
+const REDACTED_QUERYS: [&str; 6] = [
+    "Signature",
+    "OSSAccessKeyId",
+    "security-token",
+    "x-oss-signature",
+    "x-oss-credential",
+    "x-oss-security-token",
+];
+
+// 录制的一次请求
+//
+// 记录文件中每行一个 key=value ，一次请求以空行结束：
+// method、url 为请求方法和脱敏后的请求地址，status 为响应状态码，
+// header 为响应头（每个一行，格式为 名称: 值），body 为base64编码的响应body
+#[derive(Debug)]
+struct Exchange {
+    method: Method,
+    url: String,
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+impl Exchange {
+    fn encode(&self) -> String {
+        let mut content = format!(
+            "method={}\nurl={}\nstatus={}\n",
+            self.method,
+            self.url,
+            self.status.as_u16()
+        );
+        for (key, value) in &self.headers {
+            let _ = writeln!(content, "header={}: {}", key, value);
+        }
+        let _ = writeln!(
+            content,
+            "body={}\n",
+            general_purpose::STANDARD.encode(&self.body)
+        );
+        content
+    }
+    fn decode(block: &str) -> Option<Exchange> {
+        let (mut method, mut url, mut status, mut body) = (None, None, None, Bytes::new());
+        let mut headers = Vec::new();
+        for line in block.lines() {
+            let (key, value) = line.split_once('=')?;
+            match key {
+                "method" => method = value.parse().ok(),
+                "url" => url = Some(value.to_owned()),
+                "status" => status = value.parse::<u16>().ok(),
+                "header" => {
+                    let (name, value) = value.split_once(": ")?;
+                    headers.push((name.to_owned(), value.to_owned()));
+                }
+                "body" => body = general_purpose::STANDARD.decode(value).ok()?.into(),
+                _ => {}
+            }
+        }
+        Some(Exchange {
+            method: method?,
+            url: url?,
+            status: StatusCode::from_u16(status?).ok()?,
+            headers,
+            body,
+        })
+    }
+}
+
+//脱敏后的请求地址
+fn redacted_url(uri: &Uri) -> String {
+    let mut url = format!(
+        "{}://{}{}",
+        uri.scheme_str().unwrap_or("https"),
+        uri.authority().map(|v| v.as_str()).unwrap_or_default(),
+        uri.path()
+    );
+    if let Some(query) = uri.query() {
+        let query = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if REDACTED_QUERYS.contains(&key) => format!("{}=REDACTED", key),
+                _ => pair.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        url.push('?');
+        url.push_str(&query);
+    }
+    url
+}
+
+/// 录制请求的传输
+///
+/// 通过默认的HTTP客户端访问OSS，并将每次请求的方法、地址和完整的响应追加到记录文件中，供 ReplayTransport 回放；
+/// 记录中不包含请求头，请求地址中的签名和临时凭证会被替换为 REDACTED
+///
+/// 录制时响应body会被完整读取到内存中，不适合录制大文件的下载
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_transport(RecordTransport::new("tests/fixtures/upload.oss"))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordTransport {
+    path: PathBuf,
+    client: HttpClient,
+    //多个请求同时完成时，依次写入记录文件
+    writing: Arc<tokio::sync::Mutex<()>>,
+}
+impl RecordTransport {
+    /// 记录文件已存在时，新的记录追加到文件末尾
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        RecordTransport {
+            path: path.into(),
+            client: HttpClient::default(),
+            writing: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+}
+impl Transport for RecordTransport {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        let transport = self.clone();
+        Box::pin(async move {
+            let method = request.method().clone();
+            let url = redacted_url(request.uri());
+            let response = transport.client.request(request).await?;
+            let (parts, body) = response.into_parts();
+            let body = to_bytes(body).await?;
+            let exchange = Exchange {
+                method,
+                url,
+                status: parts.status,
+                headers: parts
+                    .headers
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((key.to_string(), value.to_str().ok()?.to_owned()))
+                    })
+                    .collect(),
+                body: body.clone(),
+            };
+            let _writing = transport.writing.lock().await;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&transport.path)
+                .await?;
+            file.write_all(exchange.encode().as_bytes()).await?;
+            file.flush().await?;
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+/// 回放录制的请求
+///
+/// 按照录制的顺序返回响应，不访问网络；请求的方法或地址与记录不一致时返回 io::ErrorKind::InvalidData 错误，
+/// 比较地址时忽略签名和临时凭证
+///
+/// ```
+/// let client = OssClient::builder("AccessKey ID", "AccessKey Secret")
+///     .set_transport(ReplayTransport::load("tests/fixtures/upload.oss")?)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    exchanges: Arc<Mutex<VecDeque<Exchange>>>,
+}
+impl ReplayTransport {
+    /// 读取记录文件，文件内容无法解析时返回 io::ErrorKind::InvalidData 错误
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        let exchanges = content
+            .split("\n\n")
+            .filter(|block| !block.trim().is_empty())
+            .map(|block| Exchange::decode(block).ok_or_else(|| invalid_data("无法解析记录文件")))
+            .collect::<Result<VecDeque<_>, _>>()?;
+        Ok(ReplayTransport {
+            exchanges: Arc::new(Mutex::new(exchanges)),
+        })
+    }
+    /// 尚未回放的请求数量
+    pub fn remaining(&self) -> usize {
+        self.lock().len()
+    }
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Exchange>> {
+        match self.exchanges.lock() {
+            Ok(exchanges) => exchanges,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+impl Transport for ReplayTransport {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        let method = request.method().clone();
+        let url = redacted_url(request.uri());
+        let exchange = self.lock().pop_front();
+        Box::pin(async move {
+            let exchange = match exchange {
+                Some(exchange) if exchange.method == method && exchange.url == url => exchange,
+                Some(exchange) => {
+                    return Err(invalid_data(&format!(
+                        "请求与记录不一致，记录：{} {}，实际：{} {}",
+                        exchange.method, exchange.url, method, url
+                    )))
+                }
+                None => return Err(invalid_data("没有可以回放的记录")),
+            };
+            let mut response = Response::new(Body::from(exchange.body));
+            *response.status_mut() = exchange.status;
+            for (key, value) in exchange.headers {
+                if let (Ok(key), Ok(value)) = (
+                    HeaderName::from_bytes(key.as_bytes()),
+                    HeaderValue::from_str(&value),
+                ) {
+                    response.headers_mut().append(key, value);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::IoError(io::Error::new(
+        io::ErrorKind::InvalidData,
+        message.to_owned(),
+    ))
+}
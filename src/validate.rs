@@ -0,0 +1,89 @@
+//! 参数校验
+//!
+//! 默认情况下，大部分参数不进行校验，由OSS返回错误；通过 OssClient::enable_strict_validation() 开启严格校验后，发送请求前会检查以下内容，不符合要求时直接返回本地错误：
+//!
+//! - 存储空间名称：长度3-63个字符，只允许小写字母、数字和短横线（-），且不能以短横线开头或结尾
+//! - 文件路径：参见 [key](crate::key) 模块的命名规则
+//! - 自定义元信息：key只允许英文字母、数字和短横线（-），value只允许可见的ASCII字符，所有元信息的总大小不超过8KB
+//!
+//! 标签以及通过set_meta()、set_metadata()设置的元信息，不论是否开启严格校验，都会在发送请求前进行校验
+//!
+//! 也可以直接调用此模块中的方法，在业务代码中提前校验参数
+
+use crate::{common::invalid_metadata_key, key, request::OssRequest, Error};
+
+/// 自定义元信息的最大总大小，单位字节
+pub const MAX_METADATA_SIZE: usize = 8192;
+
+/// 校验存储空间名称
+pub fn bucket_name(bucket: &str) -> Result<(), Error> {
+    let len = bucket.len();
+    if !(3..=63).contains(&len) {
+        return Err(Error::InvalidBucketName(format!(
+            "长度需在3-63个字符之间：{}",
+            bucket
+        )));
+    }
+    if !bucket
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(Error::InvalidBucketName(format!(
+            "只允许小写字母、数字和短横线：{}",
+            bucket
+        )));
+    }
+    if bucket.starts_with('-') || bucket.ends_with('-') {
+        return Err(Error::InvalidBucketName(format!(
+            "不能以短横线开头或结尾：{}",
+            bucket
+        )));
+    }
+    Ok(())
+}
+
+/// 校验文件路径
+pub fn object_key(object: &str) -> Result<(), Error> {
+    key::validate(object)
+}
+
+/// 校验单个自定义元信息，key不包含 x-oss-meta- 前缀
+pub fn metadata(key: &str, value: &str) -> Result<(), Error> {
+    if key.is_empty() || invalid_metadata_key(key) {
+        return Err(Error::InvalidMetadata(format!(
+            "key只允许英文字母、数字和短横线：{}",
+            key
+        )));
+    }
+    if !value.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        return Err(Error::InvalidMetadata(format!(
+            "value只允许可见的ASCII字符：{}",
+            key
+        )));
+    }
+    Ok(())
+}
+
+//校验请求中的参数
+pub(crate) fn request(req: &OssRequest) -> Result<(), Error> {
+    if let Some(bucket) = &req.oss.bucket {
+        bucket_name(bucket)?;
+    }
+    if let Some(object) = &req.oss.object {
+        object_key(object)?;
+    }
+    let mut metadata_size = 0;
+    for (key, value) in &req.headers {
+        if let Some(meta_key) = key.as_str().strip_prefix("x-oss-meta-") {
+            metadata(meta_key, &String::from_utf8_lossy(value.as_bytes()))?;
+            metadata_size += key.as_str().len() + value.len();
+        }
+    }
+    if metadata_size > MAX_METADATA_SIZE {
+        return Err(Error::InvalidMetadata(format!(
+            "元信息总大小不能超过{}字节",
+            MAX_METADATA_SIZE
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! XML的构建与增量解析
+//!
+//! XmlBuilder用于构建请求body，文本内容自动转义，文件路径中的 & < > 等字符不会破坏XML结构
+//!
+//! OSS列举类API的响应结构扁平：根元素下依次排列字段和重复的条目元素（如Contents）。
+//! XmlSplitter按收到的数据块增量扫描，每当根元素下的一个子元素完整到达时立即返回，
+//! 调用方再按元素名称分别解析，不需要缓存完整的响应
+
+use crate::{common::uri_encode, Error};
+use bytes::Bytes;
+use std::fmt;
+
+//构建请求body中的XML
+#[derive(Debug)]
+pub(crate) struct XmlBuilder {
+    xml: String,
+    url_encode: bool,
+}
+impl XmlBuilder {
+    //以XML声明开头的文档
+    pub fn new() -> Self {
+        XmlBuilder {
+            xml: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"),
+            url_encode: false,
+        }
+    }
+    //文件路径使用url编码，请求需要同时携带 encoding-type=url ；XML 1.0不允许出现控制字符，包含控制字符的文件路径只能使用url编码
+    pub fn set_url_encode(mut self, url_encode: bool) -> Self {
+        self.url_encode = url_encode;
+        self
+    }
+    //开始标签
+    pub fn start(&mut self, name: &str) -> &mut Self {
+        self.xml.push('<');
+        self.xml.push_str(name);
+        self.xml.push('>');
+        self
+    }
+    //结束标签
+    pub fn end(&mut self, name: &str) -> &mut Self {
+        self.xml.push_str("</");
+        self.xml.push_str(name);
+        self.xml.push('>');
+        self
+    }
+    //只包含文本的元素，文本自动转义
+    pub fn element(&mut self, name: &str, text: impl fmt::Display) -> &mut Self {
+        self.start(name);
+        escape_into(&mut self.xml, &text.to_string());
+        self.end(name)
+    }
+    //文件路径元素，按设置进行url编码
+    pub fn key(&mut self, name: &str, key: &str) -> &mut Self {
+        match self.url_encode {
+            true => self.element(name, uri_encode(key)),
+            false => self.element(name, key),
+        }
+    }
+    pub fn finish(self) -> String {
+        self.xml
+    }
+}
+
+//转义XML文本中的特殊字符
+fn escape_into(output: &mut String, input: &str) {
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            c => output.push(c),
+        }
+    }
+}
+
+//根元素下的一个完整子元素
+#[derive(Debug)]
+pub(crate) struct XmlElement {
+    //元素名称
+    pub name: String,
+    //包含起止标签的完整元素
+    pub raw: String,
+}
+impl XmlElement {
+    //元素的文本内容，已反转义，CDATA中的内容原样返回
+    pub fn text(&self) -> String {
+        let inner = match self.raw.find('>') {
+            Some(start) if !self.raw[..start].ends_with('/') => {
+                let end = self.raw.len() - self.name.len() - 3;
+                &self.raw[start + 1..end.max(start + 1)]
+            }
+            _ => "",
+        };
+        let mut output = String::with_capacity(inner.len());
+        let mut rest = inner;
+        while let Some(start) = rest.find("<![CDATA[") {
+            output.push_str(&unescape(&rest[..start]));
+            let cdata = &rest[start + 9..];
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            output.push_str(&cdata[..end]);
+            rest = &cdata[(end + 3).min(cdata.len())..];
+        }
+        output.push_str(&unescape(rest));
+        output
+    }
+    //解析为结构体
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_xml_rs::from_str(&self.raw)
+            .map_err(|_| Error::OssInvalidResponse(Some(Bytes::from(self.raw.clone()))))
+    }
+}
+
+//增量拆分根元素下的子元素
+#[derive(Debug, Default)]
+pub(crate) struct XmlSplitter {
+    buf: Vec<u8>,
+    pos: usize,
+    in_root: bool,
+    finished: bool,
+}
+impl XmlSplitter {
+    //追加收到的数据
+    pub fn push(&mut self, chunk: &[u8]) {
+        //丢弃已经解析的部分，避免缓冲区持续增长
+        if self.pos > 0 && self.pos * 2 >= self.buf.len() {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+    //根元素是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+    //返回下一个完整的子元素，数据不足或根元素已经结束时返回None
+    pub fn next_element(&mut self) -> Result<Option<XmlElement>, Error> {
+        loop {
+            if self.finished {
+                return Ok(None);
+            }
+            self.skip_whitespace();
+            let rest = &self.buf[self.pos..];
+            if rest.len() < 2 {
+                return Ok(None);
+            }
+            if rest[0] != b'<' {
+                return Err(self.invalid());
+            }
+            //跳过XML声明、注释
+            if rest.starts_with(b"<?") || rest.starts_with(b"<!") {
+                let end = match rest.starts_with(b"<!--") {
+                    true => find(rest, b"-->").map(|v| v + 3),
+                    false => find(rest, b">").map(|v| v + 1),
+                };
+                match end {
+                    Some(end) => self.pos += end,
+                    None => return Ok(None),
+                }
+                continue;
+            }
+            let Some(tag_end) = find(rest, b">") else {
+                return Ok(None);
+            };
+            //进入根元素，自闭合的根元素没有子元素
+            if !self.in_root {
+                self.in_root = true;
+                self.finished = rest[tag_end - 1] == b'/';
+                self.pos += tag_end + 1;
+                continue;
+            }
+            //根元素结束
+            if rest[1] == b'/' {
+                self.finished = true;
+                self.pos += tag_end + 1;
+                return Ok(None);
+            }
+            let name_end = rest[1..tag_end]
+                .iter()
+                .position(|v| v.is_ascii_whitespace() || *v == b'/')
+                .map_or(tag_end, |v| v + 1);
+            let name = String::from_utf8_lossy(&rest[1..name_end]).into_owned();
+            //自闭合元素，如 <Prefix/>
+            let end = if rest[tag_end - 1] == b'/' {
+                tag_end + 1
+            } else {
+                let close = format!("</{}>", name);
+                match find_close(&rest[tag_end..], close.as_bytes()) {
+                    Some(v) => tag_end + v + close.len(),
+                    None => return Ok(None),
+                }
+            };
+            let raw = String::from_utf8(rest[..end].to_vec()).map_err(|_| self.invalid())?;
+            self.pos += end;
+            return Ok(Some(XmlElement { name, raw }));
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        while self
+            .buf
+            .get(self.pos)
+            .is_some_and(|v| v.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+    fn invalid(&self) -> Error {
+        Error::OssInvalidResponse(Some(Bytes::copy_from_slice(&self.buf[self.pos..])))
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+//查找结束标签，跳过CDATA中与结束标签相同的内容
+fn find_close(haystack: &[u8], close: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let rest = &haystack[pos..];
+        let end = find(rest, close)?;
+        match find(rest, b"<![CDATA[") {
+            Some(cdata) if cdata < end => pos += cdata + find(&rest[cdata..], b"]]>")? + 3,
+            _ => return Some(pos + end),
+        }
+    }
+}
+
+//反转义XML文本中的实体
+pub(crate) fn unescape(input: &str) -> String {
+    if !input.contains('&') {
+        return input.to_owned();
+    }
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let decoded = entity.and_then(|(entity, end)| {
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    Some(hex) => char::from_u32(u32::from_str_radix(hex, 16).ok()?)?,
+                    None => char::from_u32(entity.strip_prefix('#')?.parse().ok()?)?,
+                },
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                output.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //按指定大小切分数据块逐块写入，返回全部子元素的名称和文本
+    fn split(xml: &str, chunk_size: usize) -> Vec<(String, String)> {
+        let mut splitter = XmlSplitter::default();
+        let mut elements = Vec::new();
+        for chunk in xml.as_bytes().chunks(chunk_size) {
+            splitter.push(chunk);
+            while let Some(element) = splitter.next_element().unwrap() {
+                elements.push((element.name.clone(), element.text()));
+            }
+        }
+        assert!(splitter.is_finished());
+        elements
+    }
+
+    fn assert_all_chunk_sizes(xml: &str, expected: &[(&str, &str)]) {
+        let expected = expected
+            .iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect::<Vec<_>>();
+        for chunk_size in 1..=xml.len() {
+            assert_eq!(
+                split(xml, chunk_size),
+                expected,
+                "chunk size {}",
+                chunk_size
+            );
+        }
+    }
+
+    #[test]
+    fn splits_elements_across_chunk_boundaries() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult>
+  <Name>examplebucket</Name>
+  <Contents><Key>a/b.txt</Key><Size>10</Size></Contents>
+  <IsTruncated>false</IsTruncated>
+</ListBucketResult>"#;
+        assert_all_chunk_sizes(
+            xml,
+            &[
+                ("Name", "examplebucket"),
+                ("Contents", "<Key>a/b.txt</Key><Size>10</Size>"),
+                ("IsTruncated", "false"),
+            ],
+        );
+    }
+
+    #[test]
+    fn handles_attributes_and_self_closing_tags() {
+        let xml = r#"<ListBucketResult xmlns="http://doc.oss-cn-hangzhou.aliyuncs.com"><Prefix/><Delimiter /><Key type="x">k</Key></ListBucketResult>"#;
+        assert_all_chunk_sizes(xml, &[("Prefix", ""), ("Delimiter", ""), ("Key", "k")]);
+    }
+
+    #[test]
+    fn self_closing_root_has_no_children() {
+        assert_all_chunk_sizes("<ListBucketResult/>", &[]);
+    }
+
+    #[test]
+    fn decodes_entities() {
+        let xml = "<R><Key>a&amp;b&lt;c&gt;&quot;&apos;&#x4E2D;&#25991;&#X41;&unknown;</Key></R>";
+        assert_all_chunk_sizes(xml, &[("Key", "a&b<c>\"'中文A&unknown;")]);
+    }
+
+    #[test]
+    fn keeps_cdata_verbatim() {
+        let xml = "<R><Key><![CDATA[a&amp;</Key>]]>&amp;b</Key><Size>1</Size></R>";
+        assert_all_chunk_sizes(xml, &[("Key", "a&amp;</Key>&b"), ("Size", "1")]);
+    }
+
+    #[test]
+    fn skips_comments_containing_angle_brackets() {
+        let xml = "<R><!-- a > b --><Key>k</Key></R>";
+        assert_all_chunk_sizes(xml, &[("Key", "k")]);
+    }
+
+    #[test]
+    fn builder_escapes_text() {
+        let mut xml = XmlBuilder::new();
+        xml.start("Delete").element("Key", "a&<b>\"'").end("Delete");
+        assert!(xml
+            .finish()
+            .ends_with("<Delete><Key>a&amp;&lt;b&gt;&quot;&apos;</Key></Delete>"));
+    }
+}